@@ -0,0 +1,236 @@
+use super::value::{Table, Value};
+
+/// Standard atomic weights in g/mol (IUPAC conventional values). Elements without
+/// stable isotopes use the mass number of their longest-lived isotope.
+const ELEMENTS: [(&str, f64); 118] = [
+    ("H", 1.008), ("He", 4.0026), ("Li", 6.94), ("Be", 9.0122), ("B", 10.81),
+    ("C", 12.011), ("N", 14.007), ("O", 15.999), ("F", 18.998), ("Ne", 20.180),
+    ("Na", 22.990), ("Mg", 24.305), ("Al", 26.982), ("Si", 28.085), ("P", 30.974),
+    ("S", 32.06), ("Cl", 35.45), ("Ar", 39.948), ("K", 39.098), ("Ca", 40.078),
+    ("Sc", 44.956), ("Ti", 47.867), ("V", 50.942), ("Cr", 51.996), ("Mn", 54.938),
+    ("Fe", 55.845), ("Co", 58.933), ("Ni", 58.693), ("Cu", 63.546), ("Zn", 65.38),
+    ("Ga", 69.723), ("Ge", 72.630), ("As", 74.922), ("Se", 78.971), ("Br", 79.904),
+    ("Kr", 83.798), ("Rb", 85.468), ("Sr", 87.62), ("Y", 88.906), ("Zr", 91.224),
+    ("Nb", 92.906), ("Mo", 95.95), ("Tc", 98.0), ("Ru", 101.07), ("Rh", 102.91),
+    ("Pd", 106.42), ("Ag", 107.87), ("Cd", 112.41), ("In", 114.82), ("Sn", 118.71),
+    ("Sb", 121.76), ("Te", 127.60), ("I", 126.90), ("Xe", 131.29), ("Cs", 132.91),
+    ("Ba", 137.33), ("La", 138.91), ("Ce", 140.12), ("Pr", 140.91), ("Nd", 144.24),
+    ("Pm", 145.0), ("Sm", 150.36), ("Eu", 151.96), ("Gd", 157.25), ("Tb", 158.93),
+    ("Dy", 162.50), ("Ho", 164.93), ("Er", 167.26), ("Tm", 168.93), ("Yb", 173.05),
+    ("Lu", 174.97), ("Hf", 178.49), ("Ta", 180.95), ("W", 183.84), ("Re", 186.21),
+    ("Os", 190.23), ("Ir", 192.22), ("Pt", 195.08), ("Au", 196.97), ("Hg", 200.59),
+    ("Tl", 204.38), ("Pb", 207.2), ("Bi", 208.98), ("Po", 209.0), ("At", 210.0),
+    ("Rn", 222.0), ("Fr", 223.0), ("Ra", 226.0), ("Ac", 227.0), ("Th", 232.04),
+    ("Pa", 231.04), ("U", 238.03), ("Np", 237.0), ("Pu", 244.0), ("Am", 243.0),
+    ("Cm", 247.0), ("Bk", 247.0), ("Cf", 251.0), ("Es", 252.0), ("Fm", 257.0),
+    ("Md", 258.0), ("No", 259.0), ("Lr", 266.0), ("Rf", 267.0), ("Db", 268.0),
+    ("Sg", 269.0), ("Bh", 270.0), ("Hs", 269.0), ("Mt", 278.0), ("Ds", 281.0),
+    ("Rg", 282.0), ("Cn", 285.0), ("Nh", 286.0), ("Fl", 289.0), ("Mc", 290.0),
+    ("Lv", 293.0), ("Ts", 294.0), ("Og", 294.0),
+];
+
+/// Deepest nesting of parentheses and brackets in a formula.
+const MAX_DEPTH: usize = 32;
+
+/// Molar masses and conversions between mass, amount and concentration. Masses are in
+/// grams, amounts in moles and volumes in litres; wherever a formula is expected, a
+/// molar mass in g/mol may be given instead.
+pub fn evaluate(name: &str, args: &[Value]) -> Result<Value, String> {
+    let number = |i: usize| args[i].to_f64();
+    match (name, args.len()) {
+        ("molar", 1) => Ok(Value::Number(molar_mass(&args[0])?)),
+        ("composition", 1) => composition(&args[0]).map(Value::Table),
+        // n = m / M
+        ("moles", 2) => Ok(Value::Number(number(0)? / molar_mass(&args[1])?)),
+        // m = n·M
+        ("mass", 2) => Ok(Value::Number(number(0)? * molar_mass(&args[1])?)),
+        // c = m / (M·V)
+        ("molarity", 3) => Ok(Value::Number(number(0)? / molar_mass(&args[1])? / volume(name, number(2)?)?)),
+        // m = c·V·M
+        ("solute", 3) => Ok(Value::Number(number(0)? * number(1)? * molar_mass(&args[2])?)),
+        // c1·V1 = c2·V2
+        ("dilute", 3) => {
+            let c2 = number(2)?;
+            if c2 <= 0.0 {
+                return Err("dilute requires a positive final concentration".to_string());
+            }
+            Ok(Value::Number(number(0)? * number(1)? / c2))
+        }
+        ("molar" | "composition", _) => Err(format!("{} requires a formula, e.g. {}(\"Ca(OH)2\")", name, name)),
+        ("moles", _) => Err("moles requires a mass in grams and a formula, e.g. moles(10, \"NaCl\")".to_string()),
+        ("mass", _) => Err("mass requires an amount in moles and a formula, e.g. mass(0.5, \"H2O\")".to_string()),
+        ("molarity", _) => Err("molarity requires grams, a formula and litres, e.g. molarity(5.84, \"NaCl\", 0.1)".to_string()),
+        ("solute", _) => Err("solute requires mol/L, litres and a formula, e.g. solute(0.1, 0.25, \"NaCl\")".to_string()),
+        _ => Err("dilute requires c1, V1 and c2, e.g. dilute(2, 0.05, 0.5)".to_string()),
+    }
+}
+
+fn volume(name: &str, litres: f64) -> Result<f64, String> {
+    if litres <= 0.0 {
+        return Err(format!("{} requires a positive volume in litres", name));
+    }
+    Ok(litres)
+}
+
+/// The molar mass of a formula given as text, or a molar mass given directly.
+fn molar_mass(value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Text(formula) => Ok(parse_formula(formula)?.iter().map(|(symbol, count)| weight(symbol) * count).sum()),
+        other => {
+            let mass = other.to_f64().map_err(|_| "Expected a formula in quotes, e.g. \"H2O\", or a molar mass".to_string())?;
+            if mass <= 0.0 {
+                return Err("A molar mass must be positive".to_string());
+            }
+            Ok(mass)
+        }
+    }
+}
+
+fn weight(symbol: &str) -> f64 {
+    ELEMENTS.iter().find(|(s, _)| *s == symbol).map_or(0.0, |(_, w)| *w)
+}
+
+/// Atoms, mass and mass fraction of each element, in order of first appearance.
+fn composition(value: &Value) -> Result<Table, String> {
+    let formula = match value {
+        Value::Text(formula) => formula,
+        _ => return Err("composition requires a formula in quotes, e.g. composition(\"H2O\")".to_string()),
+    };
+    let atoms = parse_formula(formula)?;
+    let total: f64 = atoms.iter().map(|(symbol, count)| weight(symbol) * count).sum();
+    Ok(Table {
+        title: format!("Composition of {} ({:.3} g/mol)", formula, total),
+        headers: ["Element", "Atoms", "g/mol", "Mass %"].map(String::from).to_vec(),
+        rows: atoms
+            .iter()
+            .map(|(symbol, count)| {
+                let mass = weight(symbol) * count;
+                vec![
+                    Value::Text(symbol.clone()),
+                    Value::Number(*count),
+                    Value::Number(mass),
+                    Value::Number(100.0 * mass / total),
+                ]
+            })
+            .collect(),
+    })
+}
+
+/// Counts the atoms of each element in a formula such as `Ca(OH)2`, `K4[Fe(CN)6]` or
+/// the hydrate `CuSO4·5H2O` (`*` and `.` also separate hydrate parts).
+fn parse_formula(formula: &str) -> Result<Vec<(String, f64)>, String> {
+    let chars: Vec<char> = formula.chars().filter(|c| !c.is_whitespace()).collect();
+    if chars.is_empty() {
+        return Err("Empty chemical formula".to_string());
+    }
+    let mut atoms = Vec::new();
+    for part in chars.split(|&c| matches!(c, '·' | '*' | '.' | '•')) {
+        // A hydrate part may start with a multiplier, as in 5H2O
+        let digits = part.iter().take_while(|c| c.is_ascii_digit()).count();
+        let multiplier = if digits == 0 { 1.0 } else { count(&part[..digits], formula)? };
+        let mut reader = FormulaReader { chars: &part[digits..], position: 0, formula };
+        let group = reader.sequence(0)?;
+        if reader.position < reader.chars.len() {
+            return Err(format!("Unexpected '{}' in formula '{}'", reader.chars[reader.position], formula));
+        }
+        if group.is_empty() {
+            return Err(format!("Missing part in formula '{}'", formula));
+        }
+        add_atoms(&mut atoms, group, multiplier);
+    }
+    Ok(atoms)
+}
+
+fn add_atoms(atoms: &mut Vec<(String, f64)>, group: Vec<(String, f64)>, multiplier: f64) {
+    for (symbol, n) in group {
+        match atoms.iter_mut().find(|(s, _)| *s == symbol) {
+            Some((_, total)) => *total += n * multiplier,
+            None => atoms.push((symbol, n * multiplier)),
+        }
+    }
+}
+
+fn count(digits: &[char], formula: &str) -> Result<f64, String> {
+    match digits.iter().collect::<String>().parse::<u32>() {
+        Ok(0) => Err(format!("Zero count in formula '{}'", formula)),
+        Ok(n) => Ok(f64::from(n)),
+        Err(_) => Err(format!("Count too large in formula '{}'", formula)),
+    }
+}
+
+struct FormulaReader<'a> {
+    chars: &'a [char],
+    position: usize,
+    formula: &'a str,
+}
+
+impl FormulaReader<'_> {
+    /// Elements and bracketed groups, each with an optional count, up to a closing
+    /// bracket or the end.
+    fn sequence(&mut self, depth: usize) -> Result<Vec<(String, f64)>, String> {
+        if depth > MAX_DEPTH {
+            return Err(format!("Formula '{}' is nested too deeply", self.formula));
+        }
+        let mut atoms = Vec::new();
+        while let Some(&c) = self.chars.get(self.position) {
+            let group = match c {
+                '(' | '[' => {
+                    let close = if c == '(' { ')' } else { ']' };
+                    self.position += 1;
+                    let inner = self.sequence(depth + 1)?;
+                    if self.chars.get(self.position) != Some(&close) {
+                        return Err(format!("Missing '{}' in formula '{}'", close, self.formula));
+                    }
+                    if inner.is_empty() {
+                        return Err(format!("Empty group in formula '{}'", self.formula));
+                    }
+                    self.position += 1;
+                    inner
+                }
+                'A'..='Z' => {
+                    let start = self.position;
+                    self.position += 1;
+                    while matches!(self.chars.get(self.position), Some(c) if c.is_ascii_lowercase()) {
+                        self.position += 1;
+                    }
+                    let symbol: String = self.chars[start..self.position].iter().collect();
+                    if !ELEMENTS.iter().any(|(s, _)| *s == symbol) {
+                        return Err(format!("Unknown element '{}' in formula '{}'", symbol, self.formula));
+                    }
+                    vec![(symbol, 1.0)]
+                }
+                ')' | ']' => break,
+                _ => return Err(format!("Unexpected '{}' in formula '{}'", c, self.formula)),
+            };
+            let digits = self.chars[self.position..].iter().take_while(|c| c.is_ascii_digit()).count();
+            let multiplier = if digits == 0 { 1.0 } else { count(&self.chars[self.position..self.position + digits], self.formula)? };
+            self.position += digits;
+            add_atoms(&mut atoms, group, multiplier);
+        }
+        Ok(atoms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn molar(formula: &str) -> Result<f64, String> {
+        molar_mass(&Value::Text(formula.to_string()))
+    }
+
+    #[test]
+    fn molar_masses() {
+        assert!((molar("H2O").unwrap() - 18.015).abs() < 1e-9);
+        assert!((molar("Ca(OH)2").unwrap() - 74.092).abs() < 1e-9);
+        assert!((molar("CuSO4·5H2O").unwrap() - 249.677).abs() < 1e-9);
+    }
+
+    #[test]
+    fn malformed_formulas_are_rejected() {
+        for formula in ["", "H0", "(OH)0", "0H2O", "CuSO4·0H2O", "()", "H2()", "Ca(OH", "OH)2", "Xy", "h2o", "H2O.", "H99999999999"] {
+            assert!(molar(formula).is_err(), "{}", formula);
+        }
+        assert_eq!(molar("H0"), Err("Zero count in formula 'H0'".to_string()));
+    }
+}
@@ -0,0 +1,139 @@
+use std::f64::consts::{E, PI};
+
+/// Tightest relative distance within which a candidate counts as the exact value, for
+/// results carrying the full precision of an `f64`.
+const MIN_TOLERANCE: f64 = 1e-12;
+
+/// Largest chance that a number this precise lies so close to some `p/q` by accident,
+/// roughly `q^2` times the tolerance. Coarse inputs only match small denominators.
+const MAX_CHANCE: f64 = 1e-3;
+
+/// Largest denominator tried for plain fractions.
+const MAX_DENOMINATOR: i64 = 1000;
+
+/// Largest denominator tried for multiples of π, e and square roots.
+const MAX_SYMBOLIC_DENOMINATOR: i64 = 12;
+
+/// Recognises a result that is very close to a simple exact value, e.g. `1/3`,
+/// `3π/4` or `√2/2`. A number typed with few digits, such as `0.3333333` or
+/// `1.4142135`, only has to match to the digits it has.
+pub fn recognize(x: f64) -> Option<String> {
+    if !x.is_finite() || x.fract() == 0.0 || x.abs() > 1e6 || x.abs() < 1e-6 {
+        return None;
+    }
+    let tolerance = tolerance(x);
+
+    if let Some((p, q)) = rational(x, MAX_DENOMINATOR, tolerance) {
+        // Terminating decimals such as 1/8 are already shown exactly
+        return (!is_terminating(q)).then(|| fraction(p, q, ""));
+    }
+    if let Some((p, q)) = rational(x / PI, MAX_SYMBOLIC_DENOMINATOR, tolerance) {
+        return Some(fraction(p, q, "π"));
+    }
+    for n in (2..=50).filter(|&n| is_squarefree(n)) {
+        if let Some((p, q)) = rational(x / (n as f64).sqrt(), MAX_SYMBOLIC_DENOMINATOR, tolerance) {
+            return Some(fraction(p, q, &format!("√{}", n)));
+        }
+    }
+    if let Some((p, q)) = rational(x / E, MAX_SYMBOLIC_DENOMINATOR, tolerance) {
+        return Some(fraction(p, q, "e"));
+    }
+    None
+}
+
+/// A unit in the last significant digit of `x`, relative to `x`, so that truncated
+/// input such as 1.4142135 still matches. Digits are counted in the shortest
+/// round-trip form: 0.3333333 has 7, a computed 1/3 has 16.
+fn tolerance(x: f64) -> f64 {
+    let scientific = format!("{:e}", x.abs());
+    let mantissa = scientific.split('e').next().unwrap_or_default();
+    let digits = mantissa.chars().filter(char::is_ascii_digit).count() as i32;
+    let leading = mantissa.parse::<f64>().unwrap_or(1.0);
+    (10f64.powi(1 - digits) / leading).max(MIN_TOLERANCE)
+}
+
+/// The first continued-fraction convergent `p/q` with `q <= max_denominator` that
+/// matches `x` within `tolerance`, relative to `x`.
+fn rational(x: f64, max_denominator: i64, tolerance: f64) -> Option<(i64, i64)> {
+    let (mut p0, mut q0, mut p1, mut q1) = (0i64, 1i64, 1i64, 0i64);
+    let mut remainder = x;
+
+    for _ in 0..64 {
+        let a = remainder.floor();
+        if a.abs() > 1e12 {
+            return None;
+        }
+        let a = a as i64;
+        let (p, q) = (a.checked_mul(p1)?.checked_add(p0)?, a.checked_mul(q1)?.checked_add(q0)?);
+        if q > max_denominator || (q * q) as f64 * tolerance > MAX_CHANCE {
+            return None;
+        }
+        if (x - p as f64 / q as f64).abs() <= tolerance * x.abs() {
+            return Some((p, q));
+        }
+        (p0, q0, p1, q1) = (p1, q1, p, q);
+
+        let fraction = remainder - a as f64;
+        if fraction == 0.0 {
+            return None;
+        }
+        remainder = 1.0 / fraction;
+    }
+    None
+}
+
+fn is_terminating(mut q: i64) -> bool {
+    for factor in [2, 5] {
+        while q % factor == 0 {
+            q /= factor;
+        }
+    }
+    q == 1
+}
+
+fn is_squarefree(n: i64) -> bool {
+    (2..).take_while(|d| d * d <= n).all(|d| n % (d * d) != 0)
+}
+
+/// Writes `p/q` times `symbol`, e.g. `-3π/4` or `√2/2`.
+fn fraction(p: i64, q: i64, symbol: &str) -> String {
+    let sign = if p < 0 { "-" } else { "" };
+    let numerator = match (p.abs(), symbol) {
+        (1, symbol) if !symbol.is_empty() => symbol.to_string(),
+        (p, symbol) => format!("{}{}", p, symbol),
+    };
+    if q == 1 {
+        format!("{}{}", sign, numerator)
+    } else {
+        format!("{}{}/{}", sign, numerator, q)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::approx_constant)] // Truncated constants are exactly what is being recognised
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_inputs_typed_to_seven_digits() {
+        assert_eq!(recognize(0.3333333).as_deref(), Some("1/3"));
+        assert_eq!(recognize(1.4142135).as_deref(), Some("√2"));
+        assert_eq!(recognize(0.7853982).as_deref(), Some("π/4"));
+    }
+
+    #[test]
+    fn recognises_computed_results() {
+        assert_eq!(recognize(1.0 / 3.0).as_deref(), Some("1/3"));
+        assert_eq!(recognize(1.0 / 2f64.sqrt()).as_deref(), Some("√2/2"));
+        assert_eq!(recognize(3.0 * PI / 2.0).as_deref(), Some("3π/2"));
+        assert_eq!(recognize(22.0 / 7.0).as_deref(), Some("22/7"));
+    }
+
+    #[test]
+    fn coarse_or_unrelated_numbers_are_left_alone() {
+        assert_eq!(recognize(0.33), None);
+        assert_eq!(recognize(3.14), None);
+        assert_eq!(recognize(1.234567), None);
+        assert_eq!(recognize(0.125), None);
+    }
+}
@@ -0,0 +1,97 @@
+use super::value::Value;
+
+/// Complex number `re + im·i`, produced by polynomial roots.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    pub fn real(re: f64) -> Self {
+        Complex { re, im: 0.0 }
+    }
+
+    pub fn from_polar(r: f64, theta: f64) -> Self {
+        Complex { re: r * theta.cos(), im: r * theta.sin() }
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Complex(z) => Ok(*z),
+            other => Ok(Complex::real(other.to_f64()?)),
+        }
+    }
+
+    /// A real number when the imaginary part is exactly zero.
+    pub fn into_value(self) -> Value {
+        if self.im == 0.0 { Value::Number(self.re) } else { Value::Complex(self) }
+    }
+
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn add(self, other: Self) -> Self {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+
+    pub fn sub(self, other: Self) -> Self {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+
+    pub fn mul(self, other: Self) -> Self {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+
+    pub fn div(self, other: Self) -> Self {
+        // Smith's algorithm avoids overflow in |other|²
+        if other.re.abs() >= other.im.abs() {
+            let ratio = other.im / other.re;
+            let denominator = other.re + other.im * ratio;
+            Complex::new((self.re + self.im * ratio) / denominator, (self.im - self.re * ratio) / denominator)
+        } else {
+            let ratio = other.re / other.im;
+            let denominator = other.re * ratio + other.im;
+            Complex::new((self.re * ratio + self.im) / denominator, (self.im * ratio - self.re) / denominator)
+        }
+    }
+
+    pub fn neg(self) -> Self {
+        Complex::new(-self.re, -self.im)
+    }
+
+    pub fn scale(self, k: f64) -> Self {
+        Complex::new(self.re * k, self.im * k)
+    }
+}
+
+/// Arithmetic where either operand is complex; `None` when neither is.
+pub fn binary(op: char, a: &Value, b: &Value) -> Result<Option<Value>, String> {
+    if !matches!(a, Value::Complex(_)) && !matches!(b, Value::Complex(_)) {
+        return Ok(None);
+    }
+    let (x, y) = (Complex::from_value(a)?, Complex::from_value(b)?);
+    let result = match op {
+        '+' => x.add(y),
+        '-' => x.sub(y),
+        '*' => x.mul(y),
+        '/' => x.div(y),
+        '^' => match b.to_integer() {
+            Some(n) if n.unsigned_abs() <= 1 << 20 => {
+                let power = (0..n.unsigned_abs()).fold(Complex::real(1.0), |acc, _| acc.mul(x));
+                if n < 0 { Complex::real(1.0).div(power) } else { power }
+            }
+            _ => return Err("Complex numbers can only be raised to integer powers".to_string()),
+        },
+        _ => return Err(format!("Cannot apply '{}' to a complex number", op)),
+    };
+    Ok(Some(result.into_value()))
+}
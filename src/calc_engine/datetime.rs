@@ -0,0 +1,412 @@
+use super::value::Value;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+const MAX_BUSINESS_DAYS: i64 = 1_000_000;
+
+/// Latest year a date may reach, before or after year 0. Far beyond any calendar use,
+/// and well inside the range where counts of seconds fit in `i64`.
+const MAX_YEAR: i64 = 1_000_000;
+
+/// Longest step `plus_seconds` converts to whole seconds: twice `MAX_YEAR` in average
+/// Gregorian years, which spans the whole range from either end.
+const MAX_STEP: i64 = 2 * MAX_YEAR * 31_556_952;
+
+const DAY_NAMES: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+/// Duration units accepted directly after a number, with their length in seconds.
+const DURATION_UNITS: [(&str, f64); 16] = [
+    ("w", 604_800.0), ("wk", 604_800.0), ("week", 604_800.0), ("weeks", 604_800.0),
+    ("d", 86_400.0), ("day", 86_400.0), ("days", 86_400.0),
+    ("h", 3_600.0), ("hr", 3_600.0), ("hour", 3_600.0), ("hours", 3_600.0),
+    ("min", 60.0), ("minutes", 60.0),
+    ("s", 1.0), ("sec", 1.0), ("seconds", 1.0),
+];
+
+/// A point in time stored as UTC seconds, displayed at a fixed UTC offset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateTime {
+    pub timestamp: i64,
+    pub offset: i32,
+}
+
+impl DateTime {
+    pub fn from_civil(year: i64, month: u32, day: u32, seconds_of_day: i64, offset: i32) -> Self {
+        let timestamp = days_from_civil(year, month, day) * SECONDS_PER_DAY + seconds_of_day - offset as i64;
+        DateTime { timestamp, offset }
+    }
+
+    pub fn now() -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        DateTime { timestamp, offset: 0 }
+    }
+
+    pub fn plus_seconds(&self, seconds: f64) -> Result<Self, String> {
+        let seconds = seconds.round();
+        // Checked before converting, since `as i64` saturates and NaN becomes 0
+        if seconds.is_nan() || seconds.abs() > MAX_STEP as f64 {
+            return Err(out_of_range());
+        }
+        let timestamp = self.timestamp.checked_add(seconds as i64).ok_or_else(out_of_range)?;
+        let date = DateTime { timestamp, offset: self.offset };
+        if date.civil().0.abs() > MAX_YEAR {
+            return Err(out_of_range());
+        }
+        Ok(date)
+    }
+
+    /// Days since 1970-01-01 of the calendar date at this value's offset.
+    fn local_day(&self) -> i64 {
+        (self.timestamp + self.offset as i64).div_euclid(SECONDS_PER_DAY)
+    }
+
+    fn local_seconds_of_day(&self) -> i64 {
+        (self.timestamp + self.offset as i64).rem_euclid(SECONDS_PER_DAY)
+    }
+
+    fn civil(&self) -> (i64, u32, u32) {
+        civil_from_days(self.local_day())
+    }
+
+    /// ISO weekday, Monday = 1 ... Sunday = 7.
+    pub fn weekday(&self) -> u32 {
+        weekday_of_day(self.local_day())
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = self.civil();
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)?;
+        let seconds = self.local_seconds_of_day();
+        if seconds == 0 && self.offset == 0 {
+            return Ok(());
+        }
+        write!(f, "T{:02}:{:02}", seconds / 3600, seconds % 3600 / 60)?;
+        if seconds % 60 != 0 {
+            write!(f, ":{:02}", seconds % 60)?;
+        }
+        if self.offset == 0 {
+            write!(f, "Z")
+        } else {
+            let sign = if self.offset < 0 { '-' } else { '+' };
+            let offset = self.offset.abs();
+            write!(f, "{}{:02}:{:02}", sign, offset / 3600, offset % 3600 / 60)
+        }
+    }
+}
+
+/// Formats a duration in seconds as e.g. `3d 4h 5min 6s`.
+pub fn format_duration(seconds: f64) -> String {
+    if seconds == 0.0 {
+        return "0s".to_string();
+    }
+    let sign = if seconds < 0.0 { "-" } else { "" };
+    let mut remaining = seconds.abs();
+    let mut parts = Vec::new();
+    for (unit, length) in [("d", 86_400.0), ("h", 3_600.0), ("min", 60.0)] {
+        let count = (remaining / length).floor();
+        if count > 0.0 {
+            parts.push(format!("{}{}", count, unit));
+            remaining -= count * length;
+        }
+    }
+    if remaining > 0.0 {
+        let rounded = (remaining * 1000.0).round() / 1000.0;
+        parts.push(format!("{}s", rounded));
+    }
+    format!("{}{}", sign, parts.join(" "))
+}
+
+/// Scans a date literal such as `2026-10-16`, `2026-10-16T14:30` or
+/// `2026-10-16T14:30:00+02:00` at the start of `chars`, consuming it on success.
+pub fn scan_date_literal(chars: &mut Peekable<Chars>) -> Result<Option<DateTime>, String> {
+    let mut lookahead = chars.clone();
+    let mut literal = String::new();
+    for expected in ["dddd", "-", "dd", "-", "dd"] {
+        for kind in expected.chars() {
+            match (kind, lookahead.peek()) {
+                ('d', Some(&c)) if c.is_ascii_digit() => literal.push(c),
+                ('-', Some('-')) => literal.push('-'),
+                _ => return Ok(None),
+            }
+            lookahead.next();
+        }
+    }
+    if matches!(lookahead.peek(), Some(c) if c.is_ascii_digit()) {
+        return Ok(None);
+    }
+
+    let year: i64 = literal[0..4].parse().unwrap_or(0);
+    let month: u32 = literal[5..7].parse().unwrap_or(0);
+    let day: u32 = literal[8..10].parse().unwrap_or(0);
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return Err(format!("Invalid date: '{}'", literal));
+    }
+
+    let mut seconds_of_day = 0;
+    let mut offset = 0;
+    if lookahead.peek() == Some(&'T') {
+        lookahead.next();
+        let time = take_while(&mut lookahead, |c| c.is_ascii_digit() || c == ':');
+        seconds_of_day = parse_clock(&time, true)
+            .ok_or_else(|| format!("Invalid time: '{}T{}'", literal, time))?;
+        match lookahead.peek() {
+            Some('Z') => {
+                lookahead.next();
+            }
+            Some(&sign) if sign == '+' || sign == '-' => {
+                let mut offset_lookahead = lookahead.clone();
+                offset_lookahead.next();
+                let text = take_while(&mut offset_lookahead, |c| c.is_ascii_digit() || c == ':');
+                // Only `+hh:mm` is an offset; `+ 3h` and `+3` stay arithmetic
+                if text.len() == 5 && text.as_bytes()[2] == b':' {
+                    let minutes = parse_clock(&text, false)
+                        .ok_or_else(|| format!("Invalid UTC offset: '{}'", text))?;
+                    offset = if sign == '-' { -minutes } else { minutes } as i32;
+                    lookahead = offset_lookahead;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    *chars = lookahead;
+    Ok(Some(DateTime::from_civil(year, month, day, seconds_of_day, offset)))
+}
+
+/// Scans a duration unit directly after a number (`3d`, `90min`), returning its length in seconds.
+pub fn scan_duration_unit(chars: &mut Peekable<Chars>) -> Option<f64> {
+    let mut lookahead = chars.clone();
+    let unit = take_while(&mut lookahead, |c| c.is_ascii_alphabetic());
+    if matches!(lookahead.peek(), Some(&c) if c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    let (_, length) = DURATION_UNITS.iter().find(|(name, _)| *name == unit)?;
+    *chars = lookahead;
+    Some(*length)
+}
+
+fn take_while(chars: &mut Peekable<Chars>, predicate: impl Fn(char) -> bool) -> String {
+    let mut text = String::new();
+    while let Some(&c) = chars.peek() {
+        if !predicate(c) {
+            break;
+        }
+        text.push(c);
+        chars.next();
+    }
+    text
+}
+
+/// Parses `hh:mm` or `hh:mm:ss` into seconds (or `hh:mm` into seconds for offsets).
+fn parse_clock(text: &str, allow_seconds: bool) -> Option<i64> {
+    let parts: Vec<&str> = text.split(':').collect();
+    if parts.len() < 2 || parts.len() > 3 || (parts.len() == 3 && !allow_seconds) {
+        return None;
+    }
+    if parts.iter().any(|p| p.len() != 2) {
+        return None;
+    }
+    let values: Vec<i64> = parts.iter().filter_map(|p| p.parse().ok()).collect();
+    let (hours, minutes, seconds) = (values[0], values[1], values.get(2).copied().unwrap_or(0));
+    if hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+    Some(hours * 3600 + minutes * 60 + seconds)
+}
+
+pub fn evaluate(name: &str, args: &[Value]) -> Result<Value, String> {
+    match name {
+        "today" => {
+            no_args(name, args)?;
+            let now = DateTime::now();
+            Ok(Value::Date(DateTime { timestamp: now.local_day() * SECONDS_PER_DAY, offset: 0 }))
+        }
+        "now" => {
+            no_args(name, args)?;
+            Ok(Value::Date(DateTime::now()))
+        }
+        "date" => {
+            if args.len() != 3 {
+                return Err("date requires three arguments: year, month and day".to_string());
+            }
+            let year = integer_arg(name, &args[0])?;
+            let month = integer_arg(name, &args[1])?;
+            let day = integer_arg(name, &args[2])?;
+            if year.abs() > MAX_YEAR {
+                return Err(out_of_range());
+            }
+            if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month as u32) as i64 {
+                return Err(format!("Invalid date: {}-{}-{}", year, month, day));
+            }
+            Ok(Value::Date(DateTime::from_civil(year, month as u32, day as u32, 0, 0)))
+        }
+        "weekday" => Ok(Value::Integer(date_arg(name, args)?.weekday() as i128)),
+        "dayname" => Ok(Value::Text(DAY_NAMES[date_arg(name, args)?.weekday() as usize - 1].to_string())),
+        "year" => Ok(Value::Integer(date_arg(name, args)?.civil().0 as i128)),
+        "month" => Ok(Value::Integer(date_arg(name, args)?.civil().1 as i128)),
+        "day" => Ok(Value::Integer(date_arg(name, args)?.civil().2 as i128)),
+        "tz" => {
+            if args.len() != 2 {
+                return Err("tz requires two arguments: date and UTC offset in hours".to_string());
+            }
+            let date = date_arg(name, &args[..1])?;
+            let hours = args[1].to_f64()?;
+            if hours.abs() > 18.0 {
+                return Err("tz domain: offset between -18 and 18 hours".to_string());
+            }
+            Ok(Value::Date(DateTime { timestamp: date.timestamp, offset: (hours * 3600.0).round() as i32 }))
+        }
+        "bizdays" => {
+            if args.len() != 2 {
+                return Err("bizdays requires two arguments: start and end date".to_string());
+            }
+            let start = date_arg(name, &args[..1])?.local_day();
+            let end = date_arg(name, &args[1..])?.local_day();
+            let count = if end >= start {
+                business_days_between(start, end)
+            } else {
+                -business_days_between(end, start)
+            };
+            Ok(Value::Integer(count as i128))
+        }
+        "addbizdays" => {
+            if args.len() != 2 {
+                return Err("addbizdays requires two arguments: date and number of business days".to_string());
+            }
+            let date = date_arg(name, &args[..1])?;
+            let count = integer_arg(name, &args[1])?;
+            if count.abs() > MAX_BUSINESS_DAYS {
+                return Err(format!("addbizdays supports at most {} business days", MAX_BUSINESS_DAYS));
+            }
+            let step = count.signum();
+            let mut day = date.local_day();
+            let mut remaining = count.abs();
+            while remaining > 0 {
+                day += step;
+                if weekday_of_day(day) <= 5 {
+                    remaining -= 1;
+                }
+            }
+            Ok(Value::Date(date.plus_seconds(((day - date.local_day()) * SECONDS_PER_DAY) as f64)?))
+        }
+        "days" | "hours" | "minutes" | "seconds" => {
+            if args.len() != 1 {
+                return Err(format!("{} requires one argument", name));
+            }
+            let seconds = match &args[0] {
+                Value::Duration(seconds) => *seconds,
+                _ => return Err(format!("{} requires a duration", name)),
+            };
+            let unit = match name {
+                "days" => 86_400.0,
+                "hours" => 3_600.0,
+                "minutes" => 60.0,
+                _ => 1.0,
+            };
+            Ok(Value::Number(seconds / unit))
+        }
+        _ => Err(format!("Unknown function: '{}'", name)),
+    }
+}
+
+fn no_args(name: &str, args: &[Value]) -> Result<(), String> {
+    if args.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("{} takes no arguments", name))
+    }
+}
+
+fn date_arg(name: &str, args: &[Value]) -> Result<DateTime, String> {
+    match args {
+        [Value::Date(date)] => Ok(*date),
+        [_] => Err(format!("{} requires a date", name)),
+        _ => Err(format!("{} requires one argument", name)),
+    }
+}
+
+fn integer_arg(name: &str, arg: &Value) -> Result<i64, String> {
+    arg.to_integer()
+        .and_then(|i| i64::try_from(i).ok())
+        .ok_or_else(|| format!("{} requires integer arguments", name))
+}
+
+/// Weekdays (Monday to Friday) in the half-open day range [start, end).
+fn out_of_range() -> String {
+    format!("Date out of range: years run from -{} to {}", MAX_YEAR, MAX_YEAR)
+}
+
+fn business_days_between(start: i64, end: i64) -> i64 {
+    let total = end - start;
+    let mut count = total / 7 * 5;
+    for day in start + total / 7 * 7..end {
+        if weekday_of_day(day) <= 5 {
+            count += 1;
+        }
+    }
+    count
+}
+
+fn weekday_of_day(day: i64) -> u32 {
+    // 1970-01-01 was a Thursday
+    ((day + 3).rem_euclid(7) + 1) as u32
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Proleptic Gregorian calendar conversions (H. Hinnant's algorithms)
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn far_away_dates_are_errors_not_overflows() {
+        let date = DateTime::from_civil(2026, 10, 16, 0, 0);
+        assert!(date.plus_seconds(1e15 * 86_400.0).is_err());
+        assert!(date.plus_seconds(-1e300 * 86_400.0).is_err());
+        assert!(date.plus_seconds(f64::NAN).is_err());
+        assert_eq!(date.plus_seconds(3.0 * 86_400.0), Ok(DateTime::from_civil(2026, 10, 19, 0, 0)));
+    }
+}
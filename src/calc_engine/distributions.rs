@@ -0,0 +1,388 @@
+use super::special::{beta_inc, erfc, gamma_p, gamma_q, inverse_normal_cdf, ln_gamma};
+use std::f64::consts::{PI, SQRT_2};
+
+const BISECTION_STEPS: usize = 200;
+
+pub fn evaluate(name: &str, args: &[f64]) -> Result<f64, String> {
+    match name {
+        // Normal
+        "normpdf" => {
+            let (x, mu, sigma) = normal_args(name, args)?;
+            let z = (x - mu) / sigma;
+            Ok((-0.5 * z * z).exp() / (sigma * (2.0 * PI).sqrt()))
+        }
+        "normcdf" => {
+            let (x, mu, sigma) = normal_args(name, args)?;
+            Ok(normal_cdf((x - mu) / sigma))
+        }
+        "invnorm" => {
+            let (p, mu, sigma) = normal_args(name, args)?;
+            check_probability(name, p)?;
+            finite_quantile(name, p, mu + sigma * inverse_normal_cdf(p))
+        }
+
+        // Student t
+        "tpdf" => {
+            let (x, df) = two_args(name, args, "x and df")?;
+            check_positive(name, "df", df)?;
+            let ln_norm = ln_gamma((df + 1.0) / 2.0) - ln_gamma(df / 2.0) - 0.5 * (df * PI).ln();
+            Ok((ln_norm - (df + 1.0) / 2.0 * (x * x / df).ln_1p()).exp())
+        }
+        "tcdf" => {
+            let (x, df) = two_args(name, args, "x and df")?;
+            check_positive(name, "df", df)?;
+            Ok(student_t_cdf(x, df))
+        }
+        "invt" => {
+            let (p, df) = two_args(name, args, "p and df")?;
+            check_positive(name, "df", df)?;
+            check_probability(name, p)?;
+            // Symmetric about 0, so the median is exactly 0 and the upper half mirrors
+            // the lower, where 1 - p is exact
+            if p == 0.5 {
+                return Ok(0.0);
+            }
+            let lower = p.min(1.0 - p);
+            let x = finite_quantile(name, p, invert_continuous(|x| student_t_cdf(x, df), lower, f64::NEG_INFINITY))?;
+            Ok(if p > 0.5 { -x } else { x })
+        }
+
+        // Chi-squared
+        "chi2pdf" => {
+            let (x, df) = two_args(name, args, "x and df")?;
+            check_positive(name, "df", df)?;
+            if x == 0.0 && df < 2.0 {
+                return Err(format!("{} domain: x > 0 when df < 2, where the density is infinite at 0", name));
+            }
+            Ok(chi_squared_pdf(x, df))
+        }
+        "chi2cdf" => {
+            let (x, df) = two_args(name, args, "x and df")?;
+            check_positive(name, "df", df)?;
+            Ok(gamma_p(df / 2.0, x / 2.0))
+        }
+        "invchi2" => {
+            let (p, df) = two_args(name, args, "p and df")?;
+            check_positive(name, "df", df)?;
+            check_probability(name, p)?;
+            finite_quantile(name, p, invert_continuous(|x| gamma_p(df / 2.0, x / 2.0), p, 0.0))
+        }
+
+        // Binomial
+        "binompdf" => {
+            let (k, n, p) = binomial_args(name, args, "k")?;
+            Ok(binomial_pdf(k, n, p))
+        }
+        "binomcdf" => {
+            let (k, n, p) = binomial_args(name, args, "k")?;
+            Ok(binomial_cdf(k, n, p))
+        }
+        "invbinom" => {
+            if args.len() != 3 {
+                return Err(format!("{} requires three arguments: q, n and p", name));
+            }
+            let (q, n, p) = (args[0], args[1], args[2]);
+            check_probability(name, q)?;
+            check_count(name, "n", n)?;
+            check_unit_interval(name, p)?;
+            Ok(invert_discrete(|k| binomial_cdf(k, n, p), q, Some(n)))
+        }
+
+        // Poisson
+        "poisspdf" => {
+            let (k, lambda) = two_args(name, args, "k and lambda")?;
+            check_count(name, "k", k)?;
+            check_positive(name, "lambda", lambda)?;
+            Ok((k * lambda.ln() - lambda - ln_gamma(k + 1.0)).exp())
+        }
+        "poisscdf" => {
+            let (k, lambda) = two_args(name, args, "k and lambda")?;
+            check_count(name, "k", k)?;
+            check_positive(name, "lambda", lambda)?;
+            Ok(gamma_q(k + 1.0, lambda))
+        }
+        "invpoiss" => {
+            let (q, lambda) = two_args(name, args, "q and lambda")?;
+            check_probability(name, q)?;
+            check_positive(name, "lambda", lambda)?;
+            if q == 1.0 {
+                return Err(unbounded_quantile(name, q));
+            }
+            Ok(invert_discrete(|k| gamma_q(k + 1.0, lambda), q, None))
+        }
+
+        // Exponential
+        "exppdf" => {
+            let (x, lambda) = two_args(name, args, "x and lambda")?;
+            check_positive(name, "lambda", lambda)?;
+            Ok(if x < 0.0 { 0.0 } else { lambda * (-lambda * x).exp() })
+        }
+        "expcdf" => {
+            let (x, lambda) = two_args(name, args, "x and lambda")?;
+            check_positive(name, "lambda", lambda)?;
+            Ok(if x < 0.0 { 0.0 } else { -(-lambda * x).exp_m1() })
+        }
+        "invexp" => {
+            let (p, lambda) = two_args(name, args, "p and lambda")?;
+            check_probability(name, p)?;
+            check_positive(name, "lambda", lambda)?;
+            finite_quantile(name, p, -(-p).ln_1p() / lambda)
+        }
+
+        // Uniform
+        "unifpdf" => {
+            let (x, a, b) = uniform_args(name, args, "x")?;
+            Ok(if x < a || x > b { 0.0 } else { 1.0 / (b - a) })
+        }
+        "unifcdf" => {
+            let (x, a, b) = uniform_args(name, args, "x")?;
+            Ok(((x - a) / (b - a)).clamp(0.0, 1.0))
+        }
+        "invunif" => {
+            let (p, a, b) = uniform_args(name, args, "p")?;
+            check_probability(name, p)?;
+            Ok(a + p * (b - a))
+        }
+
+        _ => Err(format!("Unknown function: '{}'", name)),
+    }
+}
+
+pub fn normal_cdf(z: f64) -> f64 {
+    0.5 * erfc(-z / SQRT_2)
+}
+
+fn student_t_cdf(x: f64, df: f64) -> f64 {
+    let t = x * x;
+    if t < df {
+        // Near the centre the tail is close to 1/2, so work with the part between 0 and x
+        let centre = 0.5 * beta_inc(0.5, df / 2.0, t / (df + t));
+        return if x >= 0.0 { 0.5 + centre } else { 0.5 - centre };
+    }
+    let tail = 0.5 * beta_inc(df / 2.0, 0.5, df / (df + t));
+    if x >= 0.0 { 1.0 - tail } else { tail }
+}
+
+fn chi_squared_pdf(x: f64, df: f64) -> f64 {
+    if x < 0.0 {
+        return 0.0;
+    }
+    if x == 0.0 {
+        return match df.partial_cmp(&2.0) {
+            Some(std::cmp::Ordering::Less) => f64::INFINITY,
+            Some(std::cmp::Ordering::Equal) => 0.5,
+            _ => 0.0,
+        };
+    }
+    let k = df / 2.0;
+    ((k - 1.0) * x.ln() - x / 2.0 - k * 2f64.ln() - ln_gamma(k)).exp()
+}
+
+fn binomial_pdf(k: f64, n: f64, p: f64) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    if p == 0.0 {
+        return if k == 0.0 { 1.0 } else { 0.0 };
+    }
+    if p == 1.0 {
+        return if k == n { 1.0 } else { 0.0 };
+    }
+    let ln_comb = ln_gamma(n + 1.0) - ln_gamma(k + 1.0) - ln_gamma(n - k + 1.0);
+    (ln_comb + k * p.ln() + (n - k) * (-p).ln_1p()).exp()
+}
+
+fn binomial_cdf(k: f64, n: f64, p: f64) -> f64 {
+    if k >= n || p == 0.0 {
+        1.0
+    } else if p == 1.0 {
+        0.0
+    } else {
+        beta_inc(n - k, k + 1.0, 1.0 - p)
+    }
+}
+
+/// Finds x with cdf(x) = p by bracketing and bisection.
+/// `lower` is the left end of the support (`-inf` for unbounded).
+fn invert_continuous(cdf: impl Fn(f64) -> f64, p: f64, lower: f64) -> f64 {
+    if p == 0.0 {
+        return lower;
+    }
+    if p == 1.0 {
+        return f64::INFINITY;
+    }
+    let mut hi = 1.0;
+    while cdf(hi) < p {
+        hi *= 2.0;
+    }
+    let mut lo = if lower.is_finite() { lower } else { -1.0 };
+    while cdf(lo) > p {
+        lo *= 2.0;
+    }
+    for _ in 0..BISECTION_STEPS {
+        let mid = 0.5 * (lo + hi);
+        if mid == lo || mid == hi {
+            break;
+        }
+        if cdf(mid) < p {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    0.5 * (lo + hi)
+}
+
+/// Smallest non-negative integer k with cdf(k) >= q.
+fn invert_discrete(cdf: impl Fn(f64) -> f64, q: f64, upper: Option<f64>) -> f64 {
+    let mut hi = match upper {
+        Some(n) => n,
+        None => {
+            let mut hi = 1.0;
+            while cdf(hi) < q {
+                hi *= 2.0;
+            }
+            hi
+        }
+    };
+    let mut lo = 0.0;
+    if cdf(lo) >= q {
+        return lo;
+    }
+    // Invariant: cdf(lo) < q <= cdf(hi)
+    while hi - lo > 1.0 {
+        let mid = ((lo + hi) / 2.0).floor();
+        if cdf(mid) < q {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    hi
+}
+
+fn normal_args(name: &str, args: &[f64]) -> Result<(f64, f64, f64), String> {
+    let (x, mu, sigma) = match args.len() {
+        1 => (args[0], 0.0, 1.0),
+        3 => (args[0], args[1], args[2]),
+        _ => return Err(format!("{} requires one or three arguments: x[, mu, sigma]", name)),
+    };
+    check_positive(name, "sigma", sigma)?;
+    Ok((x, mu, sigma))
+}
+
+fn binomial_args(name: &str, args: &[f64], first: &str) -> Result<(f64, f64, f64), String> {
+    if args.len() != 3 {
+        return Err(format!("{} requires three arguments: {}, n and p", name, first));
+    }
+    let (k, n, p) = (args[0], args[1], args[2]);
+    check_count(name, first, k)?;
+    check_count(name, "n", n)?;
+    check_unit_interval(name, p)?;
+    Ok((k, n, p))
+}
+
+fn uniform_args(name: &str, args: &[f64], first: &str) -> Result<(f64, f64, f64), String> {
+    if args.len() != 3 {
+        return Err(format!("{} requires three arguments: {}, a and b", name, first));
+    }
+    if args[1] >= args[2] {
+        return Err(format!("{} requires a < b", name));
+    }
+    Ok((args[0], args[1], args[2]))
+}
+
+fn two_args(name: &str, args: &[f64], names: &str) -> Result<(f64, f64), String> {
+    if args.len() != 2 {
+        return Err(format!("{} requires two arguments: {}", name, names));
+    }
+    Ok((args[0], args[1]))
+}
+
+fn check_positive(name: &str, param: &str, value: f64) -> Result<(), String> {
+    if value > 0.0 {
+        Ok(())
+    } else {
+        Err(format!("{} domain: {} > 0", name, param))
+    }
+}
+
+fn check_probability(name: &str, p: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&p) {
+        Ok(())
+    } else {
+        Err(format!("{} domain: 0 <= p <= 1", name))
+    }
+}
+
+/// A quantile, or an error where it is infinite, as at p = 0 or 1 for the normal.
+fn finite_quantile(name: &str, p: f64, x: f64) -> Result<f64, String> {
+    if x.is_infinite() {
+        return Err(unbounded_quantile(name, p));
+    }
+    Ok(x)
+}
+
+fn unbounded_quantile(name: &str, p: f64) -> String {
+    format!("{} domain: the quantile at p = {} is infinite", name, p)
+}
+
+fn check_unit_interval(name: &str, p: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&p) {
+        Ok(())
+    } else {
+        Err(format!("{} domain: success probability in [0, 1]", name))
+    }
+}
+
+fn check_count(name: &str, param: &str, value: f64) -> Result<(), String> {
+    if value >= 0.0 && value.fract() == 0.0 {
+        Ok(())
+    } else {
+        Err(format!("{} requires {} to be a non-negative integer", name, param))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infinite_edges_are_domain_errors() {
+        for (name, args) in [
+            ("invnorm", &[0.0][..]),
+            ("invnorm", &[1.0]),
+            ("invt", &[0.0, 3.0]),
+            ("invchi2", &[1.0, 3.0]),
+            ("invexp", &[1.0, 2.0]),
+            ("invpoiss", &[1.0, 3.0]),
+            ("chi2pdf", &[0.0, 1.0]),
+        ] {
+            let error = evaluate(name, args).unwrap_err();
+            assert!(error.starts_with(&format!("{} domain", name)), "{}", error);
+        }
+    }
+
+    #[test]
+    fn student_t_is_exact_at_the_centre() {
+        for df in [1.0, 2.5, 10.0, 1e3, 1e6] {
+            assert_eq!(evaluate("invt", &[0.5, df]), Ok(0.0), "df = {}", df);
+            assert_eq!(evaluate("tcdf", &[0.0, df]), Ok(0.5), "df = {}", df);
+            let upper = evaluate("invt", &[0.75, df]).unwrap();
+            assert_eq!(evaluate("invt", &[0.25, df]), Ok(-upper), "df = {}", df);
+        }
+        let x = evaluate("invt", &[0.975, 10.0]).unwrap();
+        assert!((x - 2.228_138_851_986_274).abs() < 1e-12, "{}", x);
+        let p = evaluate("tcdf", &[1e-9, 10.0]).unwrap();
+        assert!((p - 0.5 - 3.891_083_839e-10).abs() < 2e-16, "{}", p);
+    }
+
+    #[test]
+    fn finite_edges_are_values() {
+        assert_eq!(evaluate("invchi2", &[0.0, 3.0]), Ok(0.0));
+        assert_eq!(evaluate("invexp", &[0.0, 2.0]), Ok(0.0));
+        assert_eq!(evaluate("chi2pdf", &[0.0, 2.0]), Ok(0.5));
+        assert_eq!(evaluate("chi2pdf", &[0.0, 3.0]), Ok(0.0));
+        assert_eq!(evaluate("invnorm", &[0.5]), Ok(0.0));
+    }
+}
@@ -0,0 +1,405 @@
+use super::special;
+use super::value::Value;
+use std::f64::consts::{LN_2, LOG10_2};
+use std::fmt;
+
+/// Low part of ln 2, so `LN_2 + LN_2_LO` is accurate well beyond `f64` precision.
+const LN_2_LO: f64 = 2.319_046_813_846_299_6e-17;
+
+/// Low part of log10 2.
+const LOG10_2_LO: f64 = -2.803_728_127_785_170_3e-18;
+
+/// Largest binary exponent, about 10^(2.7e15). Beyond it the exponent no longer
+/// converts exactly to `f64`, which `decimal` relies on.
+const MAX_EXPONENT: i64 = 1 << 53;
+
+/// Longest product computed term by term; larger factorials and binomials go through
+/// the log-gamma function.
+const MAX_PRODUCT_TERMS: f64 = 1e6;
+
+/// Number with a wide exponent, `mantissa * 2^exponent`, used when a result leaves the
+/// range of `f64`, e.g. `fact(1000)` or `comb(10000, 5000)`.
+///
+/// The mantissa keeps the 53-bit precision of an `f64`; only the exponent is wider.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Extended {
+    /// Zero, or a magnitude in [0.5, 1).
+    mantissa: f64,
+    exponent: i64,
+}
+
+impl Extended {
+    pub fn new(mantissa: f64, exponent: i64) -> Self {
+        let (mantissa, shift) = frexp(mantissa);
+        Extended { mantissa, exponent: if mantissa == 0.0 { 0 } else { exponent.saturating_add(shift) } }
+    }
+
+    pub fn from_f64(x: f64) -> Self {
+        Extended::new(x, 0)
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Extended(x) => Ok(*x),
+            other => Ok(Extended::from_f64(other.to_f64()?)),
+        }
+    }
+
+    /// `e^x`, without overflowing for large `x`.
+    pub fn exp(x: f64) -> Result<Self, String> {
+        if x.is_nan() {
+            return Err("Invalid operation: result is not a number".to_string());
+        }
+        let k = (x / LN_2).floor();
+        if k.abs() > MAX_EXPONENT as f64 {
+            return Err(range_error(k > 0.0));
+        }
+        // x - k ln 2 with ln 2 carried to extra precision
+        let r = (-k).mul_add(LN_2, x) - k * LN_2_LO;
+        Ok(Extended::new(r.exp(), k as i64))
+    }
+
+    /// Back to a plain number when it fits, so results return to `f64` once they can.
+    /// Fails when the result is beyond even the wide exponent range.
+    pub fn into_value(self) -> Result<Value, String> {
+        let wide = self.checked()?;
+        let x = wide.to_f64();
+        if (x == 0.0 && wide.mantissa != 0.0) || x.is_infinite() || x.is_subnormal() {
+            Ok(Value::Extended(wide))
+        } else {
+            Ok(Value::Number(x))
+        }
+    }
+
+    /// Rejects a non-finite mantissa or an exponent beyond `MAX_EXPONENT`, which the
+    /// saturating arithmetic above can produce.
+    fn checked(self) -> Result<Self, String> {
+        if self.mantissa.is_nan() {
+            return Err("Invalid operation: result is not a number".to_string());
+        }
+        if self.mantissa.is_infinite() {
+            return Err(range_error(true));
+        }
+        if self.exponent.abs() > MAX_EXPONENT {
+            return Err(range_error(self.exponent > 0));
+        }
+        Ok(self)
+    }
+
+    /// The nearest `f64`, which may be infinite or zero.
+    pub fn to_f64(self) -> f64 {
+        ldexp(self.mantissa, self.exponent)
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.mantissa < 0.0
+    }
+
+    pub fn neg(self) -> Self {
+        Extended { mantissa: -self.mantissa, exponent: self.exponent }
+    }
+
+    pub fn abs(self) -> Self {
+        Extended { mantissa: self.mantissa.abs(), exponent: self.exponent }
+    }
+
+    pub fn mul(self, other: Self) -> Self {
+        Extended::new(self.mantissa * other.mantissa, self.exponent.saturating_add(other.exponent))
+    }
+
+    pub fn div(self, other: Self) -> Self {
+        Extended::new(self.mantissa / other.mantissa, self.exponent.saturating_sub(other.exponent))
+    }
+
+    pub fn add(self, other: Self) -> Self {
+        if self.mantissa == 0.0 {
+            return other;
+        }
+        if other.mantissa == 0.0 {
+            return self;
+        }
+        let (large, small) = if self.exponent >= other.exponent { (self, other) } else { (other, self) };
+        let gap = large.exponent.saturating_sub(small.exponent);
+        if gap > 64 {
+            return large;
+        }
+        Extended::new(large.mantissa + ldexp(small.mantissa, -gap), large.exponent)
+    }
+
+    pub fn sub(self, other: Self) -> Self {
+        self.add(other.neg())
+    }
+
+    /// `self^y`; integer powers multiply exactly by repeated squaring.
+    pub fn powf(self, y: f64) -> Result<Self, String> {
+        if self.mantissa == 0.0 {
+            if y < 0.0 {
+                return Err("Division by zero".to_string());
+            }
+            return Ok(if y == 0.0 { Extended::from_f64(1.0) } else { self });
+        }
+        if y.fract() == 0.0 && y.abs() <= 2f64.powi(53) {
+            let mut result = Extended::from_f64(1.0);
+            let mut base = self;
+            let mut n = y.abs() as u64;
+            while n > 0 {
+                if n & 1 == 1 {
+                    result = result.mul(base);
+                }
+                base = base.mul(base);
+                n >>= 1;
+            }
+            return Ok(if y < 0.0 { Extended::from_f64(1.0).div(result) } else { result });
+        }
+        if self.is_negative() {
+            return Err("Negative number raised to a fractional power".to_string());
+        }
+        Extended::exp(y * self.ln())
+    }
+
+    /// Ordering through the sign of the difference, which `add` keeps even when the
+    /// exponents are far apart.
+    fn compare(self, other: Self) -> std::cmp::Ordering {
+        let difference = self.sub(other);
+        if difference.mantissa == 0.0 {
+            std::cmp::Ordering::Equal
+        } else if difference.is_negative() {
+            std::cmp::Ordering::Less
+        } else {
+            std::cmp::Ordering::Greater
+        }
+    }
+
+    pub fn ln(self) -> f64 {
+        self.mantissa.ln() + self.exponent as f64 * LN_2
+    }
+
+    pub fn sqrt(self) -> Self {
+        let (mantissa, exponent) = if self.exponent % 2 == 0 {
+            (self.mantissa, self.exponent)
+        } else {
+            (self.mantissa * 2.0, self.exponent - 1)
+        };
+        Extended::new(mantissa.sqrt(), exponent / 2)
+    }
+
+    /// Decimal mantissa in [1, 10) (with the sign) and power of ten.
+    pub fn decimal(self) -> (f64, i64) {
+        if self.mantissa == 0.0 || !self.mantissa.is_finite() {
+            return (self.mantissa, 0);
+        }
+        // exponent * log10 2 split into an exactly rounded product and its error
+        let e = self.exponent as f64;
+        let product = e * LOG10_2;
+        let error = e.mul_add(LOG10_2, -product) + e * LOG10_2_LO + self.mantissa.abs().log10();
+        let power = product.floor();
+        let fraction = (product - power) + error;
+        // The error is below 1, so this moves the fraction back into [0, 1) in one step
+        let carry = fraction.floor();
+        (10f64.powf(fraction - carry).copysign(self.mantissa), (power + carry) as i64)
+    }
+}
+
+impl fmt::Display for Extended {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (mantissa, exponent) = self.decimal();
+        let digits = format!("{:.7}", mantissa);
+        // Rounding can carry the mantissa up to 10
+        match digits.trim_start_matches('-').starts_with("10") {
+            true => write!(f, "{:.7}e{}", mantissa / 10.0, exponent + 1),
+            false => write!(f, "{}e{}", digits, exponent),
+        }
+    }
+}
+
+/// Functions that have a wide-exponent version, for results too large or too small for
+/// `f64`. Returns `None` when `name` has none.
+pub fn evaluate(name: &str, args: &[Value]) -> Result<Option<Value>, String> {
+    let wide = |i: usize| Extended::from_value(&args[i]);
+    let result = match (name, args.len()) {
+        ("exp", 1) => Extended::exp(args[0].to_f64()?)?,
+        ("ln", 1) => return Ok(Some(Value::Number(wide(0)?.ln()))),
+        ("log", 1) => {
+            let (mantissa, exponent) = wide(0)?.decimal();
+            return Ok(Some(Value::Number(mantissa.log10() + exponent as f64)));
+        }
+        ("sqrt", 1) => wide(0)?.sqrt(),
+        ("abs", 1) => wide(0)?.abs(),
+        ("floor" | "ceil" | "round", 1) => rounded(name, wide(0)?),
+        ("min" | "max" | "mean" | "median" | "range", n) if n > 0 => {
+            let mut values = (0..n).map(wide).collect::<Result<Vec<_>, _>>()?;
+            values.sort_by(|a, b| a.compare(*b));
+            let (first, last) = (values[0], values[n - 1]);
+            match name {
+                "min" => first,
+                "max" => last,
+                "range" => last.sub(first),
+                "median" if n % 2 == 1 => values[n / 2],
+                "median" => values[n / 2 - 1].add(values[n / 2]).mul(Extended::from_f64(0.5)),
+                _ => values.iter().fold(Extended::from_f64(0.0), |sum, x| sum.add(*x)).div(Extended::from_f64(n as f64)),
+            }
+        }
+        ("fact" | "factorial", 1) => factorial(args[0].to_f64()?)?,
+        ("gamma", 1) => {
+            let x = args[0].to_f64()?;
+            if x < 0.5 {
+                return Ok(None);
+            }
+            factorial(x - 1.0)?
+        }
+        // Only reached once the f64 result overflows, where e^-|x| no longer matters
+        ("sinh" | "cosh", 1) => {
+            let x = args[0].to_f64()?;
+            let half = Extended::exp(x.abs())?.mul(Extended::from_f64(0.5));
+            if name == "sinh" && x < 0.0 { half.neg() } else { half }
+        }
+        ("perm" | "npr", 2) => {
+            let (n, k) = (args[0].to_f64()?, args[1].to_f64()?);
+            product(k, |i| n - i, || special::ln_gamma(n + 1.0) - special::ln_gamma(n - k + 1.0))?
+        }
+        ("comb" | "ncr", 2) => {
+            let (n, k) = (args[0].to_f64()?, args[1].to_f64()?);
+            let k = k.min(n - k);
+            product(k, |i| (n - i) / (i + 1.0), || {
+                special::ln_gamma(n + 1.0) - special::ln_gamma(k + 1.0) - special::ln_gamma(n - k + 1.0)
+            })?
+        }
+        _ => return Ok(None),
+    };
+    result.into_value().map(Some)
+}
+
+/// `base r degree` when either operand is beyond the range of `f64`.
+pub fn root(base: &Value, degree: &Value) -> Result<Value, String> {
+    let degree = degree.to_f64()?;
+    if degree == 0.0 {
+        return Err("Root degree cannot be zero".to_string());
+    }
+    let base = Extended::from_value(base)?;
+    if base.is_negative() && degree % 2.0 == 0.0 {
+        return Err("Even root of negative number".to_string());
+    }
+    base.powf(1.0 / degree)?.into_value()
+}
+
+/// `floor`, `ceil` or `round`. Beyond 2^53 every value is whole already, and values
+/// too small for `f64` round to 0 or to ±1 away from it.
+fn rounded(name: &str, x: Extended) -> Extended {
+    if x.exponent > 53 {
+        return x;
+    }
+    let f = x.to_f64();
+    let whole = match name {
+        _ if f == 0.0 && x.mantissa != 0.0 => match name {
+            "floor" if x.is_negative() => -1.0,
+            "ceil" if !x.is_negative() => 1.0,
+            _ => 0.0,
+        },
+        "floor" => f.floor(),
+        "ceil" => f.ceil(),
+        _ => f.round(),
+    };
+    Extended::from_f64(whole)
+}
+
+/// A literal with an exponent, such as `1e400`, that does not fit in `f64`.
+pub fn parse_literal(text: &str) -> Option<Extended> {
+    let (mantissa, exponent) = text.split_once(['e', 'E'])?;
+    let mantissa = Extended::from_f64(mantissa.parse().ok()?);
+    let power = Extended::from_f64(10.0).powf(exponent.parse::<i64>().ok()? as f64).ok()?;
+    mantissa.mul(power).checked().ok()
+}
+
+/// `x!`, multiplied out for whole numbers and from log-gamma otherwise.
+fn factorial(x: f64) -> Result<Extended, String> {
+    if x.fract() == 0.0 && x <= MAX_PRODUCT_TERMS {
+        Ok((2..=x as u64).fold(Extended::from_f64(1.0), |acc, i| acc.mul(Extended::from_f64(i as f64))))
+    } else {
+        Extended::exp(special::ln_gamma(x + 1.0))
+    }
+}
+
+/// The product of `term(i)` for `i` below `k`, or `exp(ln_value())` when there are
+/// too many terms.
+fn product(k: f64, term: impl Fn(f64) -> f64, ln_value: impl Fn() -> f64) -> Result<Extended, String> {
+    if k > MAX_PRODUCT_TERMS {
+        return Extended::exp(ln_value());
+    }
+    Ok((0..k as u64).fold(Extended::from_f64(1.0), |acc, i| acc.mul(Extended::from_f64(term(i as f64)))))
+}
+
+fn range_error(overflow: bool) -> String {
+    match overflow {
+        true => "Overflow: result is beyond 10^(2.7e15)".to_string(),
+        false => "Underflow: result is below 10^(-2.7e15)".to_string(),
+    }
+}
+
+/// Splits `x` into a mantissa in [0.5, 1) and a power of two.
+fn frexp(x: f64) -> (f64, i64) {
+    if x == 0.0 || !x.is_finite() {
+        return (x, 0);
+    }
+    if x.is_subnormal() {
+        let (mantissa, exponent) = frexp(x * 2f64.powi(64));
+        return (mantissa, exponent - 64);
+    }
+    let bits = x.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i64 - 1022;
+    let mantissa = f64::from_bits((bits & !(0x7ff << 52)) | (1022 << 52));
+    (mantissa, exponent)
+}
+
+/// `x * 2^exponent`, saturating to infinity or zero.
+fn ldexp(mut x: f64, exponent: i64) -> f64 {
+    let mut exponent = exponent.clamp(-2200, 2200) as i32;
+    // Scale in steps that 2^n can represent exactly
+    while exponent > 1000 {
+        x *= 2f64.powi(1000);
+        exponent -= 1000;
+    }
+    while exponent < -1000 {
+        x *= 2f64.powi(-1000);
+        exponent += 1000;
+    }
+    x * 2f64.powi(exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_to_a_negative_power_is_division_by_zero() {
+        let zero = Extended::from_f64(0.0);
+        assert_eq!(zero.powf(-1.0), Err("Division by zero".to_string()));
+        assert_eq!(zero.powf(-0.5), Err("Division by zero".to_string()));
+        assert_eq!(zero.powf(0.0), Ok(Extended::from_f64(1.0)));
+    }
+
+    #[test]
+    fn results_beyond_the_exponent_range_fail() {
+        assert!(Extended::exp(1e20).unwrap_err().starts_with("Overflow"));
+        assert!(Extended::exp(-1e300).unwrap_err().starts_with("Underflow"));
+        assert!(Extended::from_f64(2.0).powf(1e20).is_err());
+        assert!(evaluate("fact", &[Value::Number(1e20)]).is_err());
+        assert!(evaluate("gamma", &[Value::Number(1e300)]).is_err());
+    }
+
+    #[test]
+    fn non_finite_mantissas_are_rejected() {
+        assert!(Extended::from_f64(f64::INFINITY).into_value().is_err());
+        assert!(Extended::from_f64(f64::NAN).into_value().is_err());
+        assert_eq!(Extended::from_f64(f64::INFINITY).decimal().0, f64::INFINITY);
+    }
+
+    #[test]
+    fn hyperbolic_functions_extend_past_f64() {
+        let Ok(Some(Value::Extended(x))) = evaluate("sinh", &[Value::Number(-1000.0)]) else {
+            panic!("sinh(-1000) should be a wide number");
+        };
+        let (mantissa, exponent) = x.decimal();
+        assert_eq!(exponent, 433);
+        assert!((mantissa + 9.8503556).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,286 @@
+use super::value::{Table, Value};
+
+const SOLVER_ITERATIONS: usize = 200;
+const SOLVER_TOLERANCE: f64 = 1e-12;
+
+/// Time value of money uses the spreadsheet sign convention: money paid out is
+/// negative, money received is positive, and payments fall at the end of each period.
+pub fn evaluate(name: &str, args: &[f64]) -> Result<Value, String> {
+    let result = match name {
+        "pmt" => {
+            let (rate, n, pv, fv) = tvm_args(name, args, "rate, n, pv[, fv]")?;
+            check_periods(name, n)?;
+            payment(rate, n, pv, fv)
+        }
+        "fv" => {
+            let (rate, n, pmt, pv) = tvm_args(name, args, "rate, n, pmt[, pv]")?;
+            -(pv * growth(rate, n) + pmt * annuity_factor(rate, n))
+        }
+        "pv" => {
+            let (rate, n, pmt, fv) = tvm_args(name, args, "rate, n, pmt[, fv]")?;
+            -(fv + pmt * annuity_factor(rate, n)) / growth(rate, n)
+        }
+        "nper" => {
+            let (rate, pmt, pv, fv) = tvm_args(name, args, "rate, pmt, pv[, fv]")?;
+            if rate == 0.0 {
+                if pmt == 0.0 {
+                    return Err("nper requires a non-zero payment when rate is 0".to_string());
+                }
+                -(pv + fv) / pmt
+            } else {
+                let ratio = (pmt - fv * rate) / (pmt + pv * rate);
+                if ratio <= 0.0 {
+                    return Err("nper: the loan is never paid off with this payment".to_string());
+                }
+                ratio.ln() / (1.0 + rate).ln()
+            }
+        }
+        "rate" => {
+            let (n, pmt, pv, fv) = tvm_args(name, args, "n, pmt, pv[, fv]")?;
+            check_periods(name, n)?;
+            let balance = |r: f64| pv * growth(r, n) + pmt * annuity_factor(r, n) + fv;
+            solve_rate(name, balance)?
+        }
+        "npv" => {
+            if args.len() < 2 {
+                return Err("npv requires a rate and at least one cash flow".to_string());
+            }
+            check_rate(name, args[0])?;
+            discounted_sum(args[0], &args[1..], 1)
+        }
+        "irr" => {
+            if args.len() < 2 {
+                return Err("irr requires at least two cash flows".to_string());
+            }
+            if !args.iter().any(|&c| c > 0.0) || !args.iter().any(|&c| c < 0.0) {
+                return Err("irr requires at least one positive and one negative cash flow".to_string());
+            }
+            solve_rate(name, |r| discounted_sum(r, args, 0))?
+        }
+        "compound" => {
+            if args.len() != 4 {
+                return Err("compound requires four arguments: p, r, n and t".to_string());
+            }
+            let (principal, rate, n, t) = (args[0], args[1], args[2], args[3]);
+            if n <= 0.0 {
+                return Err("compound domain: n > 0 compounding periods per year".to_string());
+            }
+            principal * (1.0 + rate / n).powf(n * t)
+        }
+        "amort" => return amortization(args).map(Value::Table),
+        _ => return Err(format!("Unknown function: '{}'", name)),
+    };
+    Ok(Value::Number(result))
+}
+
+/// Payment schedule for a loan of `pv` repaid in `n` equal payments.
+fn amortization(args: &[f64]) -> Result<Table, String> {
+    if args.len() != 3 {
+        return Err("amort requires three arguments: rate, n and pv".to_string());
+    }
+    let (rate, n, principal) = (args[0], args[1], args[2]);
+    check_rate("amort", rate)?;
+    if n < 1.0 || n.fract() != 0.0 {
+        return Err("amort requires a positive whole number of periods".to_string());
+    }
+    if n > 1200.0 {
+        return Err("amort supports at most 1200 periods".to_string());
+    }
+    let payment = -payment(rate, n, principal, 0.0);
+
+    let mut rows = Vec::new();
+    let mut balance = principal;
+    for period in 1..=n as u32 {
+        let interest = balance * rate;
+        let principal_part = payment - interest;
+        balance -= principal_part;
+        if period == n as u32 {
+            balance = 0.0;
+        }
+        rows.push(vec![
+            Value::Integer(period as i128),
+            money(payment),
+            money(interest),
+            money(principal_part),
+            money(balance),
+        ]);
+    }
+
+    Ok(Table {
+        title: format!("Amortization schedule ({} payments of {:.2})", n, round_cents(payment)),
+        headers: ["Period", "Payment", "Interest", "Principal", "Balance"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect(),
+        rows,
+    })
+}
+
+fn tvm_args(name: &str, args: &[f64], usage: &str) -> Result<(f64, f64, f64, f64), String> {
+    match args.len() {
+        3 | 4 => {
+            if name != "rate" {
+                check_rate(name, args[0])?;
+            }
+            Ok((args[0], args[1], args[2], args.get(3).copied().unwrap_or(0.0)))
+        }
+        _ => Err(format!("{} requires arguments: {}", name, usage)),
+    }
+}
+
+fn check_rate(name: &str, rate: f64) -> Result<(), String> {
+    if rate <= -1.0 {
+        return Err(format!("{} domain: rate > -1", name));
+    }
+    Ok(())
+}
+
+fn check_periods(name: &str, n: f64) -> Result<(), String> {
+    if n <= 0.0 {
+        return Err(format!("{} domain: n > 0", name));
+    }
+    Ok(())
+}
+
+/// Written with the discount factor (1 + rate)^-n when the balance grows, so a
+/// long loan tends to rate * pv instead of inf / inf.
+fn payment(rate: f64, n: f64, pv: f64, fv: f64) -> f64 {
+    if rate == 0.0 {
+        return -(pv + fv) / n;
+    }
+    let exponent = n * rate.ln_1p();
+    if exponent > 0.0 {
+        -rate * (pv + fv * (-exponent).exp()) / -(-exponent).exp_m1()
+    } else {
+        -rate * (pv * exponent.exp() + fv) / exponent.exp_m1()
+    }
+}
+
+fn growth(rate: f64, n: f64) -> f64 {
+    (1.0 + rate).powf(n)
+}
+
+/// Future value of a unit payment made at the end of each of `n` periods.
+fn annuity_factor(rate: f64, n: f64) -> f64 {
+    if rate == 0.0 { n } else { ((1.0 + rate).powf(n) - 1.0) / rate }
+}
+
+fn discounted_sum(rate: f64, cash_flows: &[f64], first_period: i32) -> f64 {
+    cash_flows
+        .iter()
+        .enumerate()
+        .map(|(i, c)| c / (1.0 + rate).powi(first_period + i as i32))
+        .sum()
+}
+
+/// Finds a root of `f` in (-1, inf) with Newton's method, falling back to bisection.
+fn solve_rate(name: &str, f: impl Fn(f64) -> f64) -> Result<f64, String> {
+    let mut r = 0.1;
+    for _ in 0..SOLVER_ITERATIONS {
+        let value = f(r);
+        let h = 1e-7 * (1.0 + r.abs());
+        let slope = (f(r + h) - f(r - h)) / (2.0 * h);
+        if slope == 0.0 || !slope.is_finite() {
+            break;
+        }
+        let next = r - value / slope;
+        if !next.is_finite() || next <= -1.0 {
+            break;
+        }
+        if (next - r).abs() < SOLVER_TOLERANCE {
+            return Ok(settle(next));
+        }
+        r = next;
+    }
+
+    // Scan for a sign change, then bisect
+    let mut lo = -0.999_999;
+    let mut hi = lo;
+    let mut found = false;
+    for step in 1..=400 {
+        let candidate = -0.999_999 + step as f64 * 0.025;
+        if f(lo).signum() != f(candidate).signum() {
+            hi = candidate;
+            found = true;
+            break;
+        }
+        lo = candidate;
+    }
+    if !found {
+        return Err(format!("{} did not converge", name));
+    }
+    for _ in 0..SOLVER_ITERATIONS {
+        let mid = 0.5 * (lo + hi);
+        if f(lo).signum() == f(mid).signum() {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Ok(settle(0.5 * (lo + hi)))
+}
+
+/// A rate within the solver's tolerance of zero is zero; anything smaller is noise.
+fn settle(rate: f64) -> f64 {
+    if rate.abs() < SOLVER_TOLERANCE { 0.0 } else { rate }
+}
+
+fn round_cents(x: f64) -> f64 {
+    let rounded = (x * 100.0).round() / 100.0;
+    if rounded == 0.0 { 0.0 } else { rounded }
+}
+
+/// A table cell showing an amount with exactly two decimals, such as 6.70.
+fn money(x: f64) -> Value {
+    Value::Text(format!("{:.2}", round_cents(x)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(name: &str, args: &[f64]) -> f64 {
+        match evaluate(name, args) {
+            Ok(Value::Number(x)) => x,
+            other => panic!("{}{:?} gave {:?}", name, args, other),
+        }
+    }
+
+    #[test]
+    fn payments_match_reference_values() {
+        assert!((number("pmt", &[0.05 / 12.0, 360.0, 200000.0]) + 1073.6432460242797).abs() < 1e-8);
+        assert!((number("pmt", &[0.0, 10.0, 100.0]) + 10.0).abs() < 1e-12);
+        assert_eq!(number("pmt", &[0.1, 1e10, 100.0]), -10.0);
+        assert!((number("fv", &[0.05, 10.0, -100.0]) - 1257.789253554884).abs() < 1e-8);
+        assert!((number("pv", &[0.05, 10.0, -100.0]) - 772.1734929184818).abs() < 1e-8);
+        assert!((number("nper", &[0.01, -100.0, 1000.0]) - 10.58864445942323).abs() < 1e-9);
+        assert!((number("compound", &[1000.0, 0.05, 12.0, 10.0]) - 1647.00949769028).abs() < 1e-8);
+    }
+
+    #[test]
+    fn rates_are_solved_without_noise() {
+        assert!((number("rate", &[10.0, -100.0, 772.1734929184818]) - 0.05).abs() < 1e-10);
+        assert_eq!(number("rate", &[10.0, 0.0, 100.0, -100.0]), 0.0);
+        assert!((number("irr", &[-100.0, 60.0, 60.0]) - 0.1306623862918075).abs() < 1e-10);
+        assert!((number("npv", &[0.1, 110.0, 121.0]) - 200.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn bad_arguments_are_rejected() {
+        assert!(evaluate("irr", &[100.0, 50.0]).is_err());
+        assert!(evaluate("irr", &[-100.0, -50.0]).is_err());
+        assert!(evaluate("irr", &[-100.0]).is_err());
+        assert!(evaluate("pmt", &[-1.0, 10.0, 100.0]).is_err());
+        assert!(evaluate("pmt", &[0.1, 0.0, 100.0]).is_err());
+        assert!(evaluate("nper", &[0.1, -5.0, 100.0]).is_err());
+        assert!(evaluate("amort", &[0.1, 2.5, 100.0]).is_err());
+    }
+
+    #[test]
+    fn amortization_shows_cents() {
+        let table = amortization(&[0.1, 2.0, 100.0]).unwrap();
+        assert_eq!(table.title, "Amortization schedule (2 payments of 57.62)");
+        let cells: Vec<String> = table.rows[1].iter().map(|cell| cell.to_string()).collect();
+        assert_eq!(cells, ["2", "57.62", "5.24", "52.38", "0.00"]);
+    }
+}
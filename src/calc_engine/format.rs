@@ -0,0 +1,424 @@
+use super::closed_form;
+use super::extended::Extended;
+use super::interval::format_bound;
+use super::matrix::Matrix;
+use super::complex::Complex;
+use super::value::{format_complex, format_complex_vector, format_matrix, format_uncertain, format_vector, Value};
+
+/// SI prefixes from 10^-24 to 10^24 in steps of three.
+const SI_PREFIXES: [&str; 17] = ["y", "z", "a", "f", "p", "n", "µ", "m", "", "k", "M", "G", "T", "P", "E", "Z", "Y"];
+
+/// Lists longer than this are shortened to their first and last entries in a result.
+const COMPACT_LIST_LENGTH: usize = 8;
+
+/// Entries kept at each end of a shortened list.
+const COMPACT_LIST_ENDS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Precision {
+    /// Up to six decimals with trailing zeros trimmed.
+    #[default]
+    Auto,
+    /// Exactly this many digits after the decimal point.
+    Decimals(usize),
+    /// This many significant figures.
+    Significant(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Notation {
+    /// Plain numbers, switching to scientific notation above 1e10 and below 1e-5.
+    #[default]
+    Auto,
+    Scientific,
+    /// Scientific notation with the exponent a multiple of three.
+    Engineering,
+    /// Engineering notation written with SI prefixes, e.g. `4.7k` or `22µ`.
+    SiPrefix,
+}
+
+/// Magnitude below which results print as 0 after `set zerosnap on`. Off by default,
+/// since it would also hide genuinely small values such as Planck's constant.
+const DEFAULT_ZERO_SNAP: f64 = 1e-15;
+
+/// How results are printed, shared by the TUI history, trace steps and line mode.
+#[derive(Debug, Clone, Default)]
+pub struct DisplaySettings {
+    pub precision: Precision,
+    pub notation: Notation,
+    pub thousands_separator: bool,
+    pub decimal_comma: bool,
+    /// Follow results with a recognised exact form, e.g. `≈ 1/3` or `≈ √2`.
+    pub annotate: bool,
+    /// Numbers smaller in magnitude than this are shown as 0; off unless asked for.
+    pub zero_snap: Option<f64>,
+}
+
+impl DisplaySettings {
+    /// Changes one option by name, as typed in `set <name> <value>` or `--<name> <value>`.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let count = || {
+            value.parse::<usize>()
+                .ok()
+                .filter(|&n| n <= 17)
+                .ok_or_else(|| format!("{} expects a whole number from 0 to 17", name))
+        };
+        let switch = || match value {
+            "on" => Ok(true),
+            "off" => Ok(false),
+            _ => Err(format!("{} expects 'on' or 'off'", name)),
+        };
+
+        match name {
+            "decimals" => self.precision = Precision::Decimals(count()?),
+            "sig" | "sigfigs" => match count()? {
+                0 => return Err("sig expects at least one significant figure".to_string()),
+                n => self.precision = Precision::Significant(n),
+            },
+            "precision" if value == "auto" => self.precision = Precision::Auto,
+            "notation" => {
+                self.notation = match value {
+                    "auto" => Notation::Auto,
+                    "sci" | "scientific" => Notation::Scientific,
+                    "eng" | "engineering" => Notation::Engineering,
+                    "si" => Notation::SiPrefix,
+                    _ => return Err("notation expects auto, sci, eng or si".to_string()),
+                }
+            }
+            "thousands" => self.thousands_separator = switch()?,
+            "annotate" => self.annotate = switch()?,
+            "zerosnap" => {
+                self.zero_snap = match value {
+                    "off" => None,
+                    "on" => Some(DEFAULT_ZERO_SNAP),
+                    _ => match value.parse::<f64>() {
+                        Ok(threshold) if threshold > 0.0 && threshold < 1.0 => Some(threshold),
+                        _ => return Err("zerosnap expects off, on or a threshold such as 1e-12".to_string()),
+                    },
+                }
+            }
+            "decimal" => {
+                self.decimal_comma = match value {
+                    "comma" => true,
+                    "point" => false,
+                    _ => return Err("decimal expects 'point' or 'comma'".to_string()),
+                }
+            }
+            _ => return Err(format!("Unknown display setting '{} {}'", name, value)),
+        }
+        Ok(())
+    }
+
+    /// Whether `name` is an option accepted by `set`.
+    pub fn is_option(name: &str) -> bool {
+        matches!(
+            name,
+            "decimals" | "sig" | "sigfigs" | "precision" | "notation" | "thousands" | "decimal" | "annotate" | "zerosnap"
+        )
+    }
+
+    pub fn format_value(&self, value: &Value) -> String {
+        match value {
+            Value::Number(x) => self.format_number(*x),
+            // Exact integers keep every digit unless another notation or rounding is asked for
+            Value::Integer(i) => match (self.notation, self.precision) {
+                (Notation::Auto, Precision::Auto) => self.localize(&i.to_string()),
+                (Notation::Auto, Precision::Decimals(n)) if n > 0 => self.localize(&format!("{}.{}", i, "0".repeat(n))),
+                (Notation::Auto, Precision::Decimals(_)) => self.localize(&i.to_string()),
+                _ => self.format_number(*i as f64),
+            },
+            Value::Tuple(items) => {
+                let items = items.iter().map(|v| self.format_value(v)).collect::<Vec<_>>();
+                format!("({})", items.join(self.list_separator()))
+            }
+            Value::Table(table) => table.title.clone(),
+            Value::Uncertain(center, uncertainty) => self.localize_decimal(&format_uncertain(*center, *uncertainty)),
+            Value::Interval(interval) => format!(
+                "[{}{} {}]",
+                self.localize_decimal(&format_bound(interval.lo, false)),
+                self.list_separator().trim_end(),
+                self.localize_decimal(&format_bound(interval.hi, true)),
+            ),
+            Value::Extended(x) => self.format_extended(*x),
+            Value::Vector(v) => format_vector(v, self.list_separator(), &|x| self.format_number(x)),
+            Value::Matrix(m) => format_matrix(m, self.list_separator(), &|x| self.format_number(x)),
+            Value::Complex(z) => format_complex(*z, &|x| self.format_number(x)),
+            Value::ComplexVector(v) => format_complex_vector(v, self.list_separator(), &|x| self.format_number(x)),
+            Value::Expression(expr) => expr.render(&|x| self.format_number(x)),
+            Value::Fit(fit) => fit.render(&|x| self.format_number(x)),
+            _ => value.to_string(),
+        }
+    }
+
+    /// A recognised exact form of the result such as `≈ 1/3`, when annotations are on.
+    pub fn annotation(&self, value: &Value) -> Option<String> {
+        match value {
+            Value::Number(x) if self.annotate && !self.snaps_to_zero(*x) => {
+                closed_form::recognize(*x).map(|form| format!("≈ {}", form))
+            }
+            _ => None,
+        }
+    }
+
+    fn snaps_to_zero(&self, x: f64) -> bool {
+        self.zero_snap.is_some_and(|threshold| x.abs() < threshold)
+    }
+
+    /// What follows `=` for a final result. Matrices are summarised by their shape and
+    /// drawn underneath by `block_lines`, long lists show only their ends (see
+    /// `list_lines`), and everything else is `format_value`.
+    pub fn format_result(&self, value: &Value) -> String {
+        match value {
+            Value::Matrix(m) => format!("{} matrix", m.shape()),
+            Value::Vector(v) if v.len() > COMPACT_LIST_LENGTH => {
+                self.compact_list(v.iter().map(|&x| self.format_number(x)).collect())
+            }
+            Value::ComplexVector(v) if v.len() > COMPACT_LIST_LENGTH => {
+                self.compact_list(v.iter().map(|&z| self.format_complex_entry(z)).collect())
+            }
+            _ => self.format_value(value),
+        }
+    }
+
+    /// Whether `format_result` shortened the value, so `list_lines` has more to show.
+    pub fn is_compacted(&self, value: &Value) -> bool {
+        match value {
+            Value::Vector(v) => v.len() > COMPACT_LIST_LENGTH,
+            Value::ComplexVector(v) => v.len() > COMPACT_LIST_LENGTH,
+            _ => false,
+        }
+    }
+
+    /// Every entry of a list, one per line with its index, for an expanded result.
+    pub fn list_lines(&self, value: &Value) -> Vec<String> {
+        let entries: Vec<String> = match value {
+            Value::Vector(v) => v.iter().map(|&x| self.format_number(x)).collect(),
+            Value::ComplexVector(v) => v.iter().map(|&z| self.format_complex_entry(z)).collect(),
+            _ => return Vec::new(),
+        };
+        let width = (entries.len() - 1).to_string().len();
+        entries.iter().enumerate().map(|(i, entry)| format!("{:>width$}: {}", i, entry)).collect()
+    }
+
+    /// `[a, b, c, …, x, y, z] (n values)`.
+    fn compact_list(&self, entries: Vec<String>) -> String {
+        let separator = self.list_separator();
+        let head = entries[..COMPACT_LIST_ENDS].join(separator);
+        let tail = entries[entries.len() - COMPACT_LIST_ENDS..].join(separator);
+        format!("[{}{}…{}{}] ({} values)", head, separator, separator, tail, entries.len())
+    }
+
+    fn format_complex_entry(&self, z: Complex) -> String {
+        format_complex(z, &|x| self.format_number(x))
+    }
+
+    /// Lines drawn below a result: the header and rows of a table, or the rows of a
+    /// matrix with aligned columns.
+    pub fn block_lines(&self, value: &Value) -> Vec<String> {
+        match value {
+            Value::Table(table) => table.lines(|cell| self.format_value(cell)),
+            Value::Matrix(m) => self.matrix_lines(m),
+            _ => Vec::new(),
+        }
+    }
+
+    /// A matrix drawn with tall brackets and right-aligned columns:
+    ///
+    /// ```text
+    /// ⎡1  2⎤
+    /// ⎣3  4⎦
+    /// ```
+    fn matrix_lines(&self, m: &Matrix) -> Vec<String> {
+        let cells: Vec<Vec<String>> = (0..m.rows)
+            .map(|i| m.row(i).iter().map(|&x| self.format_number(x)).collect())
+            .collect();
+        let widths: Vec<usize> = (0..m.cols)
+            .map(|j| cells.iter().map(|row| row[j].chars().count()).max().unwrap_or(0))
+            .collect();
+
+        cells
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let (left, right) = match i {
+                    _ if m.rows == 1 => ('[', ']'),
+                    0 => ('⎡', '⎤'),
+                    _ if i == m.rows - 1 => ('⎣', '⎦'),
+                    _ => ('⎢', '⎥'),
+                };
+                let entries = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                format!("{}{}{}", left, entries, right)
+            })
+            .collect()
+    }
+
+    pub fn format_number(&self, x: f64) -> String {
+        if !x.is_finite() {
+            return x.to_string();
+        }
+        // Avoid printing a negative zero
+        let x = if x == 0.0 || self.snaps_to_zero(x) { 0.0 } else { x };
+        let x = match self.precision {
+            Precision::Significant(n) => round_significant(x, n),
+            _ => x,
+        };
+
+        match self.notation {
+            Notation::Auto if x.abs() > 1e10 || (x.abs() < 1e-5 && x != 0.0) => self.scientific(x),
+            Notation::Auto => self.localize(&self.plain(x)),
+            Notation::Scientific => self.scientific(x),
+            Notation::Engineering => self.engineering(x, false),
+            Notation::SiPrefix => self.engineering(x, true),
+        }
+    }
+
+    fn plain(&self, x: f64) -> String {
+        let s = match self.precision {
+            Precision::Auto => {
+                let s = format!("{:.6}", x);
+                s.trim_end_matches('0').trim_end_matches('.').to_string()
+            }
+            Precision::Decimals(n) => format!("{:.*}", n, x),
+            Precision::Significant(n) => {
+                let decimals = if x == 0.0 { n as i32 - 1 } else { n as i32 - 1 - decimal_exponent(x) };
+                format!("{:.*}", decimals.max(0) as usize, x)
+            }
+        };
+        // Small negatives that round to zero print without a sign
+        match s.strip_prefix('-') {
+            Some(rest) if rest.chars().all(|c| c == '0' || c == '.') => rest.to_string(),
+            _ => s,
+        }
+    }
+
+    fn scientific(&self, x: f64) -> String {
+        let s = match self.precision {
+            Precision::Auto => format!("{:.6e}", x),
+            Precision::Decimals(n) => format!("{:.*e}", n, x),
+            Precision::Significant(n) => format!("{:.*e}", n - 1, x),
+        };
+        self.localize(&s)
+    }
+
+    /// Engineering notation, optionally spelling the exponent as an SI prefix.
+    fn engineering(&self, x: f64, si_prefix: bool) -> String {
+        let mut exponent = if x == 0.0 { 0 } else { decimal_exponent(x).div_euclid(3) * 3 };
+        let mut mantissa = self.plain(x / 10f64.powi(exponent));
+        // Rounding can carry the mantissa up to 1000
+        if mantissa.trim_start_matches('-').parse::<f64>().is_ok_and(|m| m >= 1000.0) {
+            exponent += 3;
+            mantissa = self.plain(x / 10f64.powi(exponent));
+        }
+        let mantissa = self.localize(&mantissa);
+
+        let prefix = usize::try_from(exponent / 3 + 8).ok().and_then(|i| SI_PREFIXES.get(i));
+        match prefix {
+            Some(prefix) if si_prefix => format!("{}{}", mantissa, prefix),
+            _ if exponent == 0 => mantissa,
+            _ => format!("{}e{}", mantissa, exponent),
+        }
+    }
+
+    /// A number beyond the range of `f64`, always with an exponent. Auto precision shows
+    /// eight significant figures, e.g. `fact(1000) = 4.0238726e2567`.
+    fn format_extended(&self, x: Extended) -> String {
+        let figures = match self.precision {
+            Precision::Auto => 8,
+            Precision::Decimals(n) => n + 1,
+            Precision::Significant(n) => n,
+        };
+        // Round in scientific form first, since rounding can carry into the exponent
+        let (mantissa, exponent) = x.decimal();
+        let rounded = format!("{:.*e}", figures - 1, mantissa);
+        let (digits, carry) = rounded.split_once('e').unwrap_or((&rounded, "0"));
+        let mut exponent = exponent + carry.parse::<i64>().unwrap_or(0);
+
+        let mut mantissa = digits.to_string();
+        if matches!(self.notation, Notation::Engineering | Notation::SiPrefix) {
+            let shift = exponent.rem_euclid(3);
+            let shifted = digits.parse::<f64>().unwrap_or(0.0) * 10f64.powi(shift as i32);
+            let decimals = match self.precision {
+                Precision::Decimals(n) => n,
+                _ => figures.saturating_sub(1 + shift as usize),
+            };
+            mantissa = format!("{:.*}", decimals, shifted);
+            exponent -= shift;
+        }
+        self.localize(&format!("{}e{}", mantissa, exponent))
+    }
+
+    /// Separator between listed values; a semicolon when the comma is the decimal mark.
+    pub fn list_separator(&self) -> &'static str {
+        if self.decimal_comma { "; " } else { ", " }
+    }
+
+    /// Applies the thousands separator and decimal comma to a formatted number.
+    fn localize(&self, number: &str) -> String {
+        let (number, exponent) = match number.find('e') {
+            Some(i) => number.split_at(i),
+            None => (number, ""),
+        };
+        let (sign, number) = match number.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", number),
+        };
+        let (integer, fraction) = match number.find('.') {
+            Some(i) => number.split_at(i),
+            None => (number, ""),
+        };
+
+        let mut grouped = String::new();
+        for (i, digit) in integer.chars().enumerate() {
+            if self.thousands_separator && i > 0 && (integer.len() - i).is_multiple_of(3) {
+                grouped.push(if self.decimal_comma { '.' } else { ',' });
+            }
+            grouped.push(digit);
+        }
+        format!("{}{}{}{}", sign, grouped, self.localize_decimal(fraction), exponent)
+    }
+
+    fn localize_decimal(&self, text: &str) -> String {
+        if self.decimal_comma { text.replace('.', ",") } else { text.to_string() }
+    }
+}
+
+/// Power of ten of the leading digit, e.g. 2 for 123.4.
+fn decimal_exponent(x: f64) -> i32 {
+    // Read it from Rust's correctly rounded formatting rather than log10
+    let s = format!("{:e}", x);
+    s.split_once('e').and_then(|(_, e)| e.parse().ok()).unwrap_or(0)
+}
+
+fn round_significant(x: f64, figures: usize) -> f64 {
+    format!("{:.*e}", figures.saturating_sub(1), x).parse().unwrap_or(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_physical_constants_survive_by_default() {
+        let display = DisplaySettings::default();
+        assert_eq!(display.format_result(&Value::Number(6.626e-34)), "6.626000e-34");
+        assert_eq!(display.format_result(&Value::Number(1.602e-19)), "1.602000e-19");
+        assert_eq!(display.format_result(&Value::Number(1e-16)), "1.000000e-16");
+        assert_eq!(display.format_result(&Value::Vector(vec![1e-20, 1.0])), "[1.000000e-20, 1]");
+    }
+
+    #[test]
+    fn zero_snap_applies_only_when_set() {
+        let mut display = DisplaySettings::default();
+        display.set("zerosnap", "on").unwrap();
+        assert_eq!(display.format_result(&Value::Number(1.2e-16)), "0");
+        display.set("zerosnap", "1e-40").unwrap();
+        assert_eq!(display.format_result(&Value::Number(6.626e-34)), "6.626000e-34");
+        display.set("zerosnap", "off").unwrap();
+        assert_eq!(display.format_result(&Value::Number(1.2e-16)), "1.200000e-16");
+    }
+}
@@ -0,0 +1,522 @@
+use super::value::{Value, MAX_EXACT_F64_INTEGER};
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+use std::fmt;
+
+/// Significant digits shown for each bound.
+const DISPLAY_DIGITS: usize = 10;
+
+/// Closed interval `[lo, hi]` guaranteed to contain the true result.
+///
+/// Every operation rounds its bounds outward, so enclosures stay rigorous despite
+/// floating-point rounding. Library functions such as `sin` are not correctly rounded,
+/// so their results are widened by a few extra units in the last place.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+impl Interval {
+    pub fn new(lo: f64, hi: f64) -> Result<Self, String> {
+        if lo.is_nan() || hi.is_nan() {
+            return Err("Interval bounds must be numbers".to_string());
+        }
+        if lo > hi {
+            return Err("Interval lower bound exceeds upper bound".to_string());
+        }
+        Ok(Interval { lo, hi })
+    }
+
+    /// A single exactly representable value.
+    pub fn point(x: f64) -> Self {
+        Interval { lo: x, hi: x }
+    }
+
+    /// Encloses a value that may already carry a rounding error, such as a decimal literal.
+    pub fn enclosing(x: f64) -> Self {
+        if x.fract() == 0.0 && x.abs() <= MAX_EXACT_F64_INTEGER {
+            Interval::point(x)
+        } else {
+            Interval { lo: x.next_down(), hi: x.next_up() }
+        }
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Interval(interval) => Ok(*interval),
+            Value::Integer(i) | Value::Factors(i, _) => Ok(Interval::enclosing(*i as f64)),
+            other => Ok(Interval::enclosing(other.to_f64()?)),
+        }
+    }
+
+    pub fn contains_zero(&self) -> bool {
+        self.lo <= 0.0 && self.hi >= 0.0
+    }
+
+    fn is_point(&self) -> bool {
+        self.lo == self.hi
+    }
+
+    fn widened(mut lo: f64, mut hi: f64, ulps: u32) -> Self {
+        for _ in 0..ulps {
+            lo = lo.next_down();
+            hi = hi.next_up();
+        }
+        Interval { lo, hi }
+    }
+
+    /// Encloses a function that is non-decreasing on this interval.
+    fn increasing(&self, f: impl Fn(f64) -> f64, ulps: u32) -> Self {
+        Interval::widened(f(self.lo), f(self.hi), ulps)
+    }
+
+    /// Encloses a function that is non-increasing on this interval.
+    fn decreasing(&self, f: impl Fn(f64) -> f64, ulps: u32) -> Self {
+        Interval::widened(f(self.hi), f(self.lo), ulps)
+    }
+
+    fn clamp_to(self, lo: f64, hi: f64) -> Self {
+        Interval { lo: self.lo.max(lo), hi: self.hi.min(hi) }
+    }
+
+    pub fn add(self, other: Interval) -> Self {
+        Interval {
+            lo: add_rounded(self.lo, other.lo, false),
+            hi: add_rounded(self.hi, other.hi, true),
+        }
+    }
+
+    pub fn sub(self, other: Interval) -> Self {
+        self.add(other.neg())
+    }
+
+    pub fn neg(self) -> Self {
+        Interval { lo: -self.hi, hi: -self.lo }
+    }
+
+    pub fn mul(self, other: Interval) -> Self {
+        let corners = [(self.lo, other.lo), (self.lo, other.hi), (self.hi, other.lo), (self.hi, other.hi)];
+        Interval {
+            lo: corners.iter().map(|&(x, y)| mul_rounded(x, y, false)).fold(f64::INFINITY, f64::min),
+            hi: corners.iter().map(|&(x, y)| mul_rounded(x, y, true)).fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+
+    pub fn div(self, other: Interval) -> Result<Self, String> {
+        if other.contains_zero() {
+            return Err(format!("Division by an interval containing zero: {}", other));
+        }
+        let corners = [(self.lo, other.lo), (self.lo, other.hi), (self.hi, other.lo), (self.hi, other.hi)];
+        Ok(Interval {
+            lo: corners.iter().map(|&(x, y)| div_rounded(x, y, false)).fold(f64::INFINITY, f64::min),
+            hi: corners.iter().map(|&(x, y)| div_rounded(x, y, true)).fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+
+    pub fn abs(self) -> Self {
+        if self.lo >= 0.0 {
+            self
+        } else if self.hi <= 0.0 {
+            self.neg()
+        } else {
+            Interval { lo: 0.0, hi: self.hi.max(-self.lo) }
+        }
+    }
+
+    pub fn pow(self, exponent: Interval) -> Result<Self, String> {
+        if exponent.is_point() && exponent.lo.fract() == 0.0 && exponent.lo.abs() <= i32::MAX as f64 {
+            return self.powi(exponent.lo as i32);
+        }
+        if self.lo <= 0.0 {
+            return Err("Non-integer powers need a positive base in interval mode".to_string());
+        }
+        // x^y is monotone in each argument for x > 0, so the corners bound it
+        let candidates = [
+            self.lo.powf(exponent.lo),
+            self.lo.powf(exponent.hi),
+            self.hi.powf(exponent.lo),
+            self.hi.powf(exponent.hi),
+        ];
+        let lo = candidates.iter().copied().fold(f64::INFINITY, f64::min);
+        let hi = candidates.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        Ok(Interval::widened(lo, hi, 4).clamp_to(0.0, f64::INFINITY))
+    }
+
+    fn powi(self, n: i32) -> Result<Self, String> {
+        if n == 0 {
+            return Ok(Interval::point(1.0));
+        }
+        if n < 0 {
+            return Interval::point(1.0).div(self.powi(-n)?);
+        }
+        if n % 2 == 0 {
+            let magnitude = self.abs();
+            return Ok(Interval { lo: powi_rounded(magnitude.lo, n, false), hi: powi_rounded(magnitude.hi, n, true) });
+        }
+        // Odd powers keep the sign, so round the magnitude of negative bounds the other way
+        let bound = |x: f64, up: bool| {
+            if x < 0.0 { -powi_rounded(-x, n, !up) } else { powi_rounded(x, n, up) }
+        };
+        Ok(Interval { lo: bound(self.lo, false), hi: bound(self.hi, true) })
+    }
+
+    /// The `degree`-th root, for an integer degree.
+    pub fn root(self, degree: Interval) -> Result<Self, String> {
+        if !degree.is_point() || degree.lo.fract() != 0.0 {
+            return Err("Root degree must be an exact integer in interval mode".to_string());
+        }
+        let n = degree.lo;
+        if n == 0.0 {
+            return Err("Root degree cannot be zero".to_string());
+        }
+        if n < 0.0 {
+            return Interval::point(1.0).div(self.root(Interval::point(-n))?);
+        }
+        let even = n % 2.0 == 0.0;
+        if even && self.lo < 0.0 {
+            return Err("Even root of negative number".to_string());
+        }
+        if n == 2.0 {
+            return Ok(self.sqrt());
+        }
+        let bound = |x: f64, up: bool| {
+            let root = x.signum() * x.abs().powf(1.0 / n);
+            // Exact roots such as 8 r 3 stay exact
+            if root.fract() == 0.0 && x.abs() <= MAX_EXACT_F64_INTEGER && root.powi(n as i32) == x {
+                return root;
+            }
+            // 1/n is itself rounded, so allow a relative error well above its effect
+            let margin = root.abs() * 1e-13;
+            if up { (root + margin).next_up() } else { (root - margin).next_down() }
+        };
+        let result = Interval { lo: bound(self.lo, false), hi: bound(self.hi, true) };
+        Ok(if even { result.clamp_to(0.0, f64::INFINITY) } else { result })
+    }
+
+    fn sqrt(self) -> Self {
+        Interval { lo: sqrt_rounded(self.lo, false), hi: sqrt_rounded(self.hi, true) }
+    }
+
+    fn sin(self) -> Self {
+        self.periodic_extrema(f64::sin, FRAC_PI_2)
+    }
+
+    fn cos(self) -> Self {
+        self.periodic_extrema(f64::cos, 0.0)
+    }
+
+    /// Encloses `sin` or `cos`, whose maxima lie at `peak + 2kπ` and minima at `peak + π + 2kπ`.
+    fn periodic_extrema(self, f: impl Fn(f64) -> f64, peak: f64) -> Self {
+        // Far from the origin the extremum test below cannot locate multiples of π reliably
+        if self.hi - self.lo >= TAU || self.lo.abs().max(self.hi.abs()) > 1e8 {
+            return Interval { lo: -1.0, hi: 1.0 };
+        }
+        let a = f(self.lo);
+        let b = f(self.hi);
+        let mut result = Interval::widened(a.min(b), a.max(b), 2);
+        // The extremum test uses a rounded π, so err towards including extrema
+        let contains = |offset: f64| {
+            let first = ((self.lo - offset) / TAU - 1e-9).ceil();
+            let last = ((self.hi - offset) / TAU + 1e-9).floor();
+            first <= last
+        };
+        if contains(peak) {
+            result.hi = 1.0;
+        }
+        if contains(peak + PI) {
+            result.lo = -1.0;
+        }
+        result.clamp_to(-1.0, 1.0)
+    }
+
+    fn tan(self) -> Result<Self, String> {
+        let first = ((self.lo - FRAC_PI_2) / PI - 1e-9).ceil();
+        let last = ((self.hi - FRAC_PI_2) / PI + 1e-9).floor();
+        if first <= last || self.hi - self.lo >= PI || self.lo.abs().max(self.hi.abs()) > 1e8 {
+            return Err("tan is unbounded on this interval".to_string());
+        }
+        Ok(self.increasing(f64::tan, 2))
+    }
+}
+
+/// Replaces an overflow to infinity by the largest finite value when rounding towards zero.
+fn finite_overflow(result: f64, up: bool) -> f64 {
+    match (result, up) {
+        (f64::INFINITY, false) => f64::MAX,
+        (f64::NEG_INFINITY, true) => f64::MIN,
+        _ => result,
+    }
+}
+
+/// Steps a round-to-nearest result one ulp towards the requested direction when the
+/// exact result lies on that side (`error` has the sign of exact minus rounded).
+fn directed(result: f64, error: f64, up: bool) -> f64 {
+    if up && error > 0.0 {
+        result.next_up()
+    } else if !up && error < 0.0 {
+        result.next_down()
+    } else {
+        result
+    }
+}
+
+/// `a + b` rounded down or up, using the exact rounding error from TwoSum.
+fn add_rounded(a: f64, b: f64, up: bool) -> f64 {
+    let sum = a + b;
+    if !sum.is_finite() {
+        return if a.is_finite() && b.is_finite() { finite_overflow(sum, up) } else { sum };
+    }
+    let b_part = sum - a;
+    let error = (a - (sum - b_part)) + (b - b_part);
+    directed(sum, error, up)
+}
+
+/// `a * b` rounded down or up, using a fused multiply-add for the rounding error.
+fn mul_rounded(a: f64, b: f64, up: bool) -> f64 {
+    // 0 * inf is taken as 0: the zero bound is exact, the infinite one is not reached
+    if a == 0.0 || b == 0.0 {
+        return 0.0;
+    }
+    let product = a * b;
+    if !product.is_finite() {
+        return if a.is_finite() && b.is_finite() { finite_overflow(product, up) } else { product };
+    }
+    if product.abs() < f64::MIN_POSITIVE {
+        // The fused error term is unreliable for subnormal results
+        return if up { product.next_up() } else { product.next_down() };
+    }
+    directed(product, a.mul_add(b, -product), up)
+}
+
+/// `a / b` rounded down or up; the remainder `a - q * b` is exact under a fused multiply-add.
+fn div_rounded(a: f64, b: f64, up: bool) -> f64 {
+    let quotient = a / b;
+    if !quotient.is_finite() {
+        return if a.is_finite() { finite_overflow(quotient, up) } else { quotient };
+    }
+    if quotient != 0.0 && quotient.abs() < f64::MIN_POSITIVE || quotient == 0.0 && a != 0.0 {
+        return if up { quotient.next_up() } else { quotient.next_down() };
+    }
+    let remainder = (-quotient).mul_add(b, a);
+    directed(quotient, remainder * b.signum(), up)
+}
+
+fn sqrt_rounded(x: f64, up: bool) -> f64 {
+    let root = x.sqrt();
+    if root == 0.0 || root.is_infinite() {
+        return root;
+    }
+    // root² - x has the opposite sign of the rounding error
+    directed(root, -root.mul_add(root, -x), up)
+}
+
+/// `x^n` for `x >= 0` by repeated squaring, each step rounded in the same direction.
+fn powi_rounded(x: f64, mut n: i32, up: bool) -> f64 {
+    let mut result = 1.0;
+    let mut base = x;
+    while n > 0 {
+        if n & 1 == 1 {
+            result = mul_rounded(result, base, up);
+        }
+        n >>= 1;
+        if n > 0 {
+            base = mul_rounded(base, base, up);
+        }
+    }
+    result
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}, {}]", format_bound(self.lo, false), format_bound(self.hi, true))
+    }
+}
+
+/// Prints a bound with `DISPLAY_DIGITS` significant digits, rounding away from the
+/// interior so the printed interval still encloses the stored one.
+pub(super) fn format_bound(x: f64, round_up: bool) -> String {
+    if x.is_infinite() {
+        return if x > 0.0 { "inf".to_string() } else { "-inf".to_string() };
+    }
+    if x == 0.0 {
+        return "0".to_string();
+    }
+
+    // Rust prints the exact binary value when asked for enough digits
+    let exact = format!("{:.800e}", x.abs());
+    let (mantissa, exponent) = exact.split_once('e').unwrap_or((&exact, "0"));
+    let mut exponent: i32 = exponent.parse().unwrap_or(0);
+    let digits: String = mantissa.chars().filter(|c| c.is_ascii_digit()).collect();
+    let (kept, dropped) = digits.split_at(DISPLAY_DIGITS);
+    let mut kept: u64 = kept.parse().unwrap_or(0);
+
+    // Truncation moved the bound towards zero; step away from zero when that is outward
+    let truncated = dropped.chars().any(|c| c != '0');
+    if truncated && round_up == (x > 0.0) {
+        kept += 1;
+        if kept == 10u64.pow(DISPLAY_DIGITS as u32) {
+            kept /= 10;
+            exponent += 1;
+        }
+    }
+
+    let digits = kept.to_string();
+    let digits = digits.trim_end_matches('0');
+    let digits = if digits.is_empty() { "0" } else { digits };
+    let sign = if x < 0.0 { "-" } else { "" };
+
+    if (-5..10).contains(&exponent) {
+        let point = exponent + 1;
+        let text = if point <= 0 {
+            format!("0.{}{}", "0".repeat(-point as usize), digits)
+        } else if point as usize >= digits.len() {
+            format!("{}{}", digits, "0".repeat(point as usize - digits.len()))
+        } else {
+            format!("{}.{}", &digits[..point as usize], &digits[point as usize..])
+        };
+        format!("{}{}", sign, text)
+    } else if digits.len() == 1 {
+        format!("{}{}e{}", sign, digits, exponent)
+    } else {
+        format!("{}{}.{}e{}", sign, &digits[..1], &digits[1..], exponent)
+    }
+}
+
+/// Evaluates a built-in function over intervals.
+pub fn evaluate(name: &str, args: &[Value]) -> Result<Interval, String> {
+    if name == "interval" {
+        if args.len() != 2 {
+            return Err("interval requires two arguments: lo and hi".to_string());
+        }
+        let (lo, hi) = (Interval::from_value(&args[0])?, Interval::from_value(&args[1])?);
+        return Interval::new(lo.lo, hi.hi);
+    }
+
+    let args: Vec<Interval> = args.iter().map(Interval::from_value).collect::<Result<_, _>>()?;
+    if name == "min" || name == "max" {
+        let first = *args.first().ok_or_else(|| format!("{} requires at least one argument", name))?;
+        return Ok(args.iter().skip(1).fold(first, |acc, x| {
+            if name == "min" {
+                Interval { lo: acc.lo.min(x.lo), hi: acc.hi.min(x.hi) }
+            } else {
+                Interval { lo: acc.lo.max(x.lo), hi: acc.hi.max(x.hi) }
+            }
+        }));
+    }
+
+    if args.len() != 1 {
+        return Err(format!("{} requires one argument in interval mode", name));
+    }
+    let x = args[0];
+
+    let result = match name {
+        // Trigonometric; inverse functions return degrees
+        "sin" => x.sin(),
+        "cos" => x.cos(),
+        "tan" => x.tan()?,
+        "asin" => {
+            if x.lo < -1.0 || x.hi > 1.0 {
+                return Err("asin domain: [-1, 1]".to_string());
+            }
+            x.increasing(|v| v.asin().to_degrees(), 4)
+        }
+        "acos" => {
+            if x.lo < -1.0 || x.hi > 1.0 {
+                return Err("acos domain: [-1, 1]".to_string());
+            }
+            x.decreasing(|v| v.acos().to_degrees(), 4).clamp_to(0.0, 180.0)
+        }
+        "atan" => x.increasing(|v| v.atan().to_degrees(), 4),
+
+        // Exponential
+        "ln" => {
+            if x.lo <= 0.0 {
+                return Err("ln domain: positive numbers".to_string());
+            }
+            x.increasing(f64::ln, 2)
+        }
+        "log" => {
+            if x.lo <= 0.0 {
+                return Err("log domain: positive numbers".to_string());
+            }
+            x.increasing(f64::log10, 2)
+        }
+        "exp" => x.increasing(f64::exp, 2).clamp_to(0.0, f64::INFINITY),
+
+        // Basic
+        "abs" => x.abs(),
+        "floor" => x.increasing(f64::floor, 0),
+        "ceil" => x.increasing(f64::ceil, 0),
+        "round" => x.increasing(f64::round, 0),
+        "sqrt" => {
+            if x.lo < 0.0 {
+                return Err("sqrt domain: non-negative numbers".to_string());
+            }
+            x.sqrt()
+        }
+
+        // Hyperbolic
+        "sinh" => x.increasing(f64::sinh, 2),
+        "cosh" => x.abs().increasing(f64::cosh, 2).clamp_to(1.0, f64::INFINITY),
+        "tanh" => x.increasing(f64::tanh, 2).clamp_to(-1.0, 1.0),
+        "asinh" => x.increasing(f64::asinh, 2),
+        "acosh" => {
+            if x.lo < 1.0 {
+                return Err("acosh domain: x >= 1".to_string());
+            }
+            x.increasing(f64::acosh, 2).clamp_to(0.0, f64::INFINITY)
+        }
+        "atanh" => {
+            if x.lo <= -1.0 || x.hi >= 1.0 {
+                return Err("atanh domain: |x| < 1".to_string());
+            }
+            x.increasing(f64::atanh, 2)
+        }
+
+        _ => return Err(format!("{} is not available in interval mode", name)),
+    };
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(lo: f64, hi: f64) -> Interval {
+        evaluate("interval", &[Value::Number(lo), Value::Number(hi)]).unwrap()
+    }
+
+    #[test]
+    fn sums_round_outward() {
+        let sum = interval(0.1, 0.2).add(interval(0.1, 0.2));
+        assert!(sum.lo < 0.2 && sum.hi > 0.4, "{:?}", sum);
+        assert!(sum.hi - sum.lo < 0.2 + 1e-15);
+        // 0.1 + 0.2 rounds up to 0.30000000000000004, so the lower bound steps down
+        let exact = Interval::point(0.1).add(Interval::point(0.2));
+        assert_eq!(exact, Interval { lo: 0.3, hi: 0.30000000000000004 });
+        assert_eq!(Interval::point(1.0).add(Interval::point(2.0)), Interval::point(3.0));
+    }
+
+    #[test]
+    fn products_and_quotients_enclose_the_result() {
+        let third = Interval::point(1.0).div(Interval::point(3.0)).unwrap();
+        assert!(third.lo < third.hi && third.lo.next_up() == third.hi);
+        assert_eq!(interval(-2.0, 3.0).mul(interval(-1.0, 4.0)), Interval { lo: -8.0, hi: 12.0 });
+        assert_eq!(interval(-2.0, 3.0).pow(Interval::point(2.0)), Ok(Interval { lo: 0.0, hi: 9.0 }));
+        assert!(Interval::point(1.0).div(interval(-1.0, 1.0)).is_err());
+        assert_eq!(Interval::point(8.0).root(Interval::point(3.0)), Ok(Interval::point(2.0)));
+        assert!(interval(-4.0, 1.0).root(Interval::point(2.0)).is_err());
+    }
+
+    #[test]
+    fn functions_cover_their_extrema() {
+        let sine = evaluate("sin", &[Value::Interval(Interval::new(0.0, 3.0).unwrap())]).unwrap();
+        assert!(sine.lo <= 0.0 && sine.hi == 1.0);
+        let root = evaluate("sqrt", &[Value::Number(2.0)]).unwrap();
+        // The rounded √2 lies above the true value, so it is the upper bound
+        assert_eq!(root, Interval { lo: std::f64::consts::SQRT_2.next_down(), hi: std::f64::consts::SQRT_2 });
+        assert!(evaluate("ln", &[Value::Interval(interval(-1.0, 1.0))]).is_err());
+        assert!(evaluate("tan", &[Value::Interval(interval(1.0, 2.0))]).is_err());
+        assert!(evaluate("interval", &[Value::Number(2.0), Value::Number(1.0)]).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+use super::value::{Table, Value};
+use super::Token;
+use std::fmt;
+
+/// Most variables `sat` and `equiv` check, trying every assignment.
+const MAX_VARIABLES: usize = 20;
+
+/// Most variables in a truth table, which has a row per assignment.
+const MAX_TABLE_VARIABLES: usize = 10;
+
+/// A Boolean formula such as `a and not b`, read by `truthtable`, `sat` and `equiv`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Formula {
+    Constant(bool),
+    Variable(String),
+    Not(Box<Formula>),
+    And(Box<Formula>, Box<Formula>),
+    Or(Box<Formula>, Box<Formula>),
+    Xor(Box<Formula>, Box<Formula>),
+    Implies(Box<Formula>, Box<Formula>),
+    Iff(Box<Formula>, Box<Formula>),
+}
+
+impl Formula {
+    fn evaluate(&self, assignment: &dyn Fn(&str) -> bool) -> bool {
+        match self {
+            Formula::Constant(value) => *value,
+            Formula::Variable(name) => assignment(name),
+            Formula::Not(a) => !a.evaluate(assignment),
+            Formula::And(a, b) => a.evaluate(assignment) && b.evaluate(assignment),
+            Formula::Or(a, b) => a.evaluate(assignment) || b.evaluate(assignment),
+            Formula::Xor(a, b) => a.evaluate(assignment) != b.evaluate(assignment),
+            Formula::Implies(a, b) => !a.evaluate(assignment) || b.evaluate(assignment),
+            Formula::Iff(a, b) => a.evaluate(assignment) == b.evaluate(assignment),
+        }
+    }
+
+    fn collect_variables(&self, variables: &mut Vec<String>) {
+        match self {
+            Formula::Constant(_) => {}
+            Formula::Variable(name) => {
+                if !variables.contains(name) {
+                    variables.push(name.clone());
+                }
+            }
+            Formula::Not(a) => a.collect_variables(variables),
+            Formula::And(a, b) | Formula::Or(a, b) | Formula::Xor(a, b) | Formula::Implies(a, b) | Formula::Iff(a, b) => {
+                a.collect_variables(variables);
+                b.collect_variables(variables);
+            }
+        }
+    }
+
+    /// Binding strength, loosest first: iff, implies, or, xor, and, not.
+    fn precedence(&self) -> u8 {
+        match self {
+            Formula::Iff(..) => 1,
+            Formula::Implies(..) => 2,
+            Formula::Or(..) => 3,
+            Formula::Xor(..) => 4,
+            Formula::And(..) => 5,
+            Formula::Not(_) => 6,
+            Formula::Constant(_) | Formula::Variable(_) => 7,
+        }
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Parenthesise an operand that binds more loosely than its operator; `right`
+        // also covers equal strength, except for `implies`, which groups to the right
+        let operand = |child: &Formula, right: bool| {
+            let parent = self.precedence();
+            let loose = child.precedence() < parent
+                || (child.precedence() == parent && right != matches!(self, Formula::Implies(..)));
+            if loose { format!("({})", child) } else { child.to_string() }
+        };
+        let binary = |word: &str, a: &Formula, b: &Formula| format!("{} {} {}", operand(a, false), word, operand(b, true));
+        let text = match self {
+            Formula::Constant(value) => (*value as u8).to_string(),
+            Formula::Variable(name) => name.clone(),
+            Formula::Not(a) => format!("not {}", operand(a, false)),
+            Formula::And(a, b) => binary("and", a, b),
+            Formula::Or(a, b) => binary("or", a, b),
+            Formula::Xor(a, b) => binary("xor", a, b),
+            Formula::Implies(a, b) => binary("implies", a, b),
+            Formula::Iff(a, b) => binary("iff", a, b),
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Reads a formula from `tokens` starting at `start` and returns it with the index of
+/// the first token after it.
+pub fn parse(tokens: &[Token], start: usize) -> Result<(Formula, usize), String> {
+    let mut parser = FormulaParser { tokens, current: start };
+    let formula = parser.iff()?;
+    match tokens.get(parser.current) {
+        None | Some(Token::Comma | Token::RParen) => Ok((formula, parser.current)),
+        Some(Token::Ident(word)) => Err(format!("Expected and, or, xor, implies or iff in a formula, found '{}'", word)),
+        Some(_) => Err("Formulas use the words and, or, xor, not, implies and iff".to_string()),
+    }
+}
+
+const KEYWORDS: [&str; 6] = ["and", "or", "xor", "not", "implies", "iff"];
+
+struct FormulaParser<'a> {
+    tokens: &'a [Token],
+    current: usize,
+}
+
+impl FormulaParser<'_> {
+    fn iff(&mut self) -> Result<Formula, String> {
+        let mut left = self.implies()?;
+        while self.keyword("iff") {
+            left = Formula::Iff(Box::new(left), Box::new(self.implies()?));
+        }
+        Ok(left)
+    }
+
+    fn implies(&mut self) -> Result<Formula, String> {
+        let left = self.or()?;
+        if self.keyword("implies") {
+            return Ok(Formula::Implies(Box::new(left), Box::new(self.implies()?)));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Formula, String> {
+        let mut left = self.xor()?;
+        while self.keyword("or") {
+            left = Formula::Or(Box::new(left), Box::new(self.xor()?));
+        }
+        Ok(left)
+    }
+
+    fn xor(&mut self) -> Result<Formula, String> {
+        let mut left = self.and()?;
+        while self.keyword("xor") {
+            left = Formula::Xor(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Formula, String> {
+        let mut left = self.not()?;
+        while self.keyword("and") {
+            left = Formula::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Formula, String> {
+        if self.keyword("not") {
+            return Ok(Formula::Not(Box::new(self.not()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Formula, String> {
+        let formula = match self.tokens.get(self.current) {
+            Some(Token::LParen) => {
+                self.current += 1;
+                let inner = self.iff()?;
+                if self.tokens.get(self.current) != Some(&Token::RParen) {
+                    return Err("Missing closing parenthesis in formula".to_string());
+                }
+                inner
+            }
+            Some(Token::Integer(0)) => Formula::Constant(false),
+            Some(Token::Integer(1)) => Formula::Constant(true),
+            Some(Token::Ident(word)) if word.eq_ignore_ascii_case("true") => Formula::Constant(true),
+            Some(Token::Ident(word)) if word.eq_ignore_ascii_case("false") => Formula::Constant(false),
+            Some(Token::Ident(word)) if !KEYWORDS.iter().any(|k| word.eq_ignore_ascii_case(k)) => {
+                Formula::Variable(word.clone())
+            }
+            // A lone `r` is tokenized as the root operator
+            Some(Token::Op('r')) => Formula::Variable("r".to_string()),
+            Some(Token::Integer(_) | Token::Number(_)) => return Err("The only constants in formulas are 0 and 1".to_string()),
+            _ => return Err("Expected a variable, 0, 1 or '(' in formula".to_string()),
+        };
+        self.current += 1;
+        Ok(formula)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.tokens.get(self.current) {
+            Some(Token::Ident(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.current += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// `truthtable(f, ...)`, `sat(f)` and `equiv(f, g)`, checked over every assignment of
+/// the variables in alphabetical order, with numbered names such as x2 before x10.
+pub fn evaluate(name: &str, formulas: &[Formula]) -> Result<Value, String> {
+    let mut variables = Vec::new();
+    for formula in formulas {
+        formula.collect_variables(&mut variables);
+    }
+    variables.sort_by_key(|name| {
+        let stem = name.trim_end_matches(|c: char| c.is_ascii_digit());
+        (stem.to_string(), name[stem.len()..].parse::<u128>().ok(), name.clone())
+    });
+    let limit = if name == "truthtable" { MAX_TABLE_VARIABLES } else { MAX_VARIABLES };
+    if variables.len() > limit {
+        return Err(format!("{} supports up to {} variables, found {}", name, limit, variables.len()));
+    }
+
+    // Row `mask` gives the first variable the highest bit, so rows count up in binary
+    let bit = |mask: usize, i: usize| mask >> (variables.len() - 1 - i) & 1 == 1;
+    let at = |formula: &Formula, mask: usize| {
+        formula.evaluate(&|name| bit(mask, variables.iter().position(|v| v == name).unwrap_or(0)))
+    };
+    let assignment = |mask: usize| {
+        variables.iter().enumerate().map(|(i, v)| format!("{} = {}", v, bit(mask, i) as u8)).collect::<Vec<_>>().join(", ")
+    };
+    let rows = 1usize << variables.len();
+
+    match (name, formulas) {
+        ("truthtable", [_, ..]) => Ok(Value::Table(Table {
+            title: format!("Truth table of {}", formulas.iter().map(|f| f.to_string()).collect::<Vec<_>>().join("; ")),
+            headers: variables.iter().cloned().chain(formulas.iter().map(|f| f.to_string())).collect(),
+            rows: (0..rows)
+                .map(|mask| {
+                    (0..variables.len())
+                        .map(|i| bit(mask, i))
+                        .chain(formulas.iter().map(|f| at(f, mask)))
+                        .map(|value| Value::Integer(value as i128))
+                        .collect()
+                })
+                .collect(),
+        })),
+        ("sat", [formula]) => {
+            let satisfying = (0..rows).filter(|&mask| at(formula, mask)).collect::<Vec<_>>();
+            Ok(Value::Text(match satisfying.first() {
+                None => "Unsatisfiable".to_string(),
+                Some(_) if variables.is_empty() => "Satisfiable (always true)".to_string(),
+                Some(&mask) => format!("Satisfiable: {} ({} of {} assignments)", assignment(mask), satisfying.len(), rows),
+            }))
+        }
+        ("equiv", [f, g]) => Ok(Value::Text(match (0..rows).find(|&mask| at(f, mask) != at(g, mask)) {
+            None => "Equivalent".to_string(),
+            Some(mask) => format!(
+                "Not equivalent: {}the first is {} and the second is {}",
+                if variables.is_empty() { String::new() } else { format!("at {} ", assignment(mask)) },
+                at(f, mask) as u8,
+                at(g, mask) as u8
+            ),
+        })),
+        ("truthtable", _) => Err("truthtable requires a formula, e.g. truthtable(a and not b)".to_string()),
+        ("sat", _) => Err("sat requires one formula, e.g. sat(a and not a)".to_string()),
+        _ => Err("equiv requires two formulas, e.g. equiv(not (a and b), not a or not b)".to_string()),
+    }
+}
@@ -0,0 +1,688 @@
+use super::value::Value;
+
+/// Iterations allowed per eigenvalue before the QR algorithm gives up.
+const MAX_EIGEN_ITERATIONS: usize = 500;
+
+/// Dense matrix of plain numbers stored row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    pub rows: usize,
+    pub cols: usize,
+    data: Vec<f64>,
+}
+
+/// `P * A = L * U` with partial pivoting.
+struct Lu {
+    l: Matrix,
+    u: Matrix,
+    /// Row of `A` that ends up in each row of `P * A`.
+    permutation: Vec<usize>,
+    /// +1 or -1, the determinant of `P`.
+    sign: f64,
+}
+
+impl Matrix {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Matrix { rows, cols, data: vec![0.0; rows * cols] }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut m = Matrix::zeros(n, n);
+        for i in 0..n {
+            m.set(i, i, 1.0);
+        }
+        m
+    }
+
+    /// Builds a matrix from equally long rows, e.g. the literal `[[1, 2], [3, 4]]`.
+    pub fn from_rows(rows: &[Vec<f64>]) -> Result<Self, String> {
+        let cols = rows.first().map_or(0, Vec::len);
+        if cols == 0 {
+            return Err("A matrix needs at least one row and one column".to_string());
+        }
+        if rows.iter().any(|row| row.len() != cols) {
+            return Err("Matrix rows must all have the same length".to_string());
+        }
+        Ok(Matrix { rows: rows.len(), cols, data: rows.concat() })
+    }
+
+    /// A vector as a single column.
+    pub fn column(values: &[f64]) -> Self {
+        Matrix { rows: values.len(), cols: 1, data: values.to_vec() }
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> f64 {
+        self.data[i * self.cols + j]
+    }
+
+    fn set(&mut self, i: usize, j: usize, value: f64) {
+        self.data[i * self.cols + j] = value;
+    }
+
+    pub fn row(&self, i: usize) -> &[f64] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn entries(&self) -> &[f64] {
+        &self.data
+    }
+
+    /// Dimensions as written in messages, e.g. `2×3`.
+    pub fn shape(&self) -> String {
+        format!("{}×{}", self.rows, self.cols)
+    }
+
+    fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    fn require_square(&self, name: &str) -> Result<(), String> {
+        if self.is_square() {
+            Ok(())
+        } else {
+            Err(format!("{} requires a square matrix, found {}", name, self.shape()))
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut t = Matrix::zeros(self.cols, self.rows);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                t.set(j, i, self.get(i, j));
+            }
+        }
+        t
+    }
+
+    fn map(&self, f: impl Fn(f64) -> f64) -> Self {
+        Matrix { rows: self.rows, cols: self.cols, data: self.data.iter().map(|&x| f(x)).collect() }
+    }
+
+    fn zip(&self, other: &Matrix, verb: &str, f: impl Fn(f64, f64) -> f64) -> Result<Self, String> {
+        if (self.rows, self.cols) != (other.rows, other.cols) {
+            return Err(format!("Cannot {} a {} matrix and a {} matrix", verb, self.shape(), other.shape()));
+        }
+        let data = self.data.iter().zip(&other.data).map(|(&x, &y)| f(x, y)).collect();
+        Ok(Matrix { rows: self.rows, cols: self.cols, data })
+    }
+
+    pub fn mul(&self, other: &Matrix) -> Result<Self, String> {
+        if self.cols != other.rows {
+            return Err(format!("Cannot multiply a {} matrix by a {} matrix", self.shape(), other.shape()));
+        }
+        let mut product = Matrix::zeros(self.rows, other.cols);
+        for i in 0..self.rows {
+            for j in 0..other.cols {
+                let sum = (0..self.cols).map(|k| self.get(i, k) * other.get(k, j)).sum();
+                product.set(i, j, sum);
+            }
+        }
+        Ok(product)
+    }
+
+    /// `A^n` by repeated squaring; negative powers invert first.
+    fn powi(&self, n: i64) -> Result<Self, String> {
+        self.require_square("A matrix power")?;
+        let mut base = if n < 0 { self.inverse()? } else { self.clone() };
+        let mut result = Matrix::identity(self.rows);
+        let mut n = n.unsigned_abs();
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result.mul(&base)?;
+            }
+            base = base.mul(&base)?;
+            n >>= 1;
+        }
+        Ok(result)
+    }
+
+    /// Largest entry in magnitude, the scale for singularity tests.
+    fn scale(&self) -> f64 {
+        self.data.iter().fold(0.0, |acc: f64, x| acc.max(x.abs()))
+    }
+
+    /// Pivots below this count as zero.
+    fn tolerance(&self) -> f64 {
+        self.rows.max(self.cols) as f64 * f64::EPSILON * self.scale()
+    }
+
+    fn lu(&self) -> Result<Lu, String> {
+        self.require_square("lu")?;
+        let n = self.rows;
+        let mut u = self.clone();
+        let mut l = Matrix::zeros(n, n);
+        let mut permutation: Vec<usize> = (0..n).collect();
+        let mut sign = 1.0;
+
+        for k in 0..n {
+            let pivot = (k..n)
+                .max_by(|&a, &b| u.get(a, k).abs().total_cmp(&u.get(b, k).abs()))
+                .unwrap_or(k);
+            if pivot != k {
+                for j in 0..n {
+                    let (a, b) = (u.get(k, j), u.get(pivot, j));
+                    u.set(k, j, b);
+                    u.set(pivot, j, a);
+                    let (a, b) = (l.get(k, j), l.get(pivot, j));
+                    l.set(k, j, b);
+                    l.set(pivot, j, a);
+                }
+                permutation.swap(k, pivot);
+                sign = -sign;
+            }
+            l.set(k, k, 1.0);
+            if u.get(k, k) == 0.0 {
+                continue;
+            }
+            for i in k + 1..n {
+                let factor = u.get(i, k) / u.get(k, k);
+                l.set(i, k, factor);
+                for j in k..n {
+                    u.set(i, j, u.get(i, j) - factor * u.get(k, j));
+                }
+            }
+        }
+        Ok(Lu { l, u, permutation, sign })
+    }
+
+    pub fn determinant(&self) -> Result<f64, String> {
+        self.require_square("det")?;
+        let lu = self.lu()?;
+        Ok((0..self.rows).fold(lu.sign, |acc, i| acc * lu.u.get(i, i)))
+    }
+
+    pub fn inverse(&self) -> Result<Self, String> {
+        self.require_square("inv")?;
+        self.solve(&Matrix::identity(self.rows))
+    }
+
+    /// Solves `A X = B`; least squares when `A` has more rows than columns.
+    pub fn solve(&self, b: &Matrix) -> Result<Self, String> {
+        if b.rows != self.rows {
+            return Err(format!("solve: the right-hand side has {} rows but the matrix has {}", b.rows, self.rows));
+        }
+        if self.rows > self.cols {
+            return self.least_squares(b);
+        }
+        if !self.is_square() {
+            return Err(format!("solve: a {} system has infinitely many solutions", self.shape()));
+        }
+
+        let n = self.rows;
+        let lu = self.lu()?;
+        let tolerance = self.tolerance();
+        if (0..n).any(|i| lu.u.get(i, i).abs() <= tolerance) {
+            return Err("Matrix is singular".to_string());
+        }
+
+        let mut x = Matrix::zeros(n, b.cols);
+        for col in 0..b.cols {
+            // Forward substitution with L, then back substitution with U
+            let mut y = vec![0.0; n];
+            for i in 0..n {
+                let sum: f64 = (0..i).map(|j| lu.l.get(i, j) * y[j]).sum();
+                y[i] = b.get(lu.permutation[i], col) - sum;
+            }
+            for i in (0..n).rev() {
+                let sum: f64 = (i + 1..n).map(|j| lu.u.get(i, j) * x.get(j, col)).sum();
+                x.set(i, col, (y[i] - sum) / lu.u.get(i, i));
+            }
+        }
+        Ok(x)
+    }
+
+    fn least_squares(&self, b: &Matrix) -> Result<Self, String> {
+        let (q, r) = self.qr();
+        let n = self.cols;
+        if (0..n).any(|i| r.get(i, i).abs() <= self.tolerance()) {
+            return Err("solve: the columns of the matrix are linearly dependent".to_string());
+        }
+        let qtb = q.transpose().mul(b)?;
+        let mut x = Matrix::zeros(n, b.cols);
+        for col in 0..b.cols {
+            for i in (0..n).rev() {
+                let sum: f64 = (i + 1..n).map(|j| r.get(i, j) * x.get(j, col)).sum();
+                x.set(i, col, (qtb.get(i, col) - sum) / r.get(i, i));
+            }
+        }
+        Ok(x)
+    }
+
+    /// `A = Q R` by Householder reflections, with `Q` orthogonal and `R` upper triangular.
+    pub fn qr(&self) -> (Matrix, Matrix) {
+        let (m, n) = (self.rows, self.cols);
+        let mut q = Matrix::identity(m);
+        let mut r = self.clone();
+
+        for k in 0..n.min(m.saturating_sub(1)) {
+            let norm = (k..m).map(|i| r.get(i, k).powi(2)).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                continue;
+            }
+            let alpha = if r.get(k, k) > 0.0 { -norm } else { norm };
+            let mut v: Vec<f64> = (k..m).map(|i| r.get(i, k)).collect();
+            v[0] -= alpha;
+            let v_norm_sq: f64 = v.iter().map(|x| x * x).sum();
+            if v_norm_sq == 0.0 {
+                continue;
+            }
+            // Apply H = I - 2 v vᵀ / (vᵀ v) to R from the left and to Q from the right
+            for j in 0..n {
+                let dot: f64 = (k..m).map(|i| v[i - k] * r.get(i, j)).sum();
+                let factor = 2.0 * dot / v_norm_sq;
+                for i in k..m {
+                    r.set(i, j, r.get(i, j) - factor * v[i - k]);
+                }
+            }
+            for i in 0..m {
+                let dot: f64 = (k..m).map(|j| q.get(i, j) * v[j - k]).sum();
+                let factor = 2.0 * dot / v_norm_sq;
+                for j in k..m {
+                    q.set(i, j, q.get(i, j) - factor * v[j - k]);
+                }
+            }
+        }
+        // Clear the rounding noise left below the diagonal
+        for i in 0..m {
+            for j in 0..i.min(n) {
+                r.set(i, j, 0.0);
+            }
+        }
+        (q, r)
+    }
+
+    /// Number of linearly independent rows, by elimination with partial pivoting.
+    pub fn rank(&self) -> usize {
+        let mut a = self.clone();
+        let tolerance = self.tolerance();
+        let mut rank = 0;
+        for col in 0..a.cols {
+            if rank == a.rows {
+                break;
+            }
+            let pivot = (rank..a.rows)
+                .max_by(|&x, &y| a.get(x, col).abs().total_cmp(&a.get(y, col).abs()))
+                .unwrap_or(rank);
+            if a.get(pivot, col).abs() <= tolerance {
+                continue;
+            }
+            for j in 0..a.cols {
+                let (x, y) = (a.get(rank, j), a.get(pivot, j));
+                a.set(rank, j, y);
+                a.set(pivot, j, x);
+            }
+            for i in rank + 1..a.rows {
+                let factor = a.get(i, col) / a.get(rank, col);
+                for j in col..a.cols {
+                    a.set(i, j, a.get(i, j) - factor * a.get(rank, j));
+                }
+            }
+            rank += 1;
+        }
+        rank
+    }
+
+    pub fn trace(&self) -> Result<f64, String> {
+        self.require_square("trace")?;
+        Ok((0..self.rows).map(|i| self.get(i, i)).sum())
+    }
+
+    /// Real eigenvalues in ascending order, by the shifted QR algorithm.
+    pub fn eigenvalues(&self) -> Result<Vec<f64>, String> {
+        self.require_square("eig")?;
+        let mut a = self.clone();
+        let mut values = Vec::new();
+        let mut n = a.rows;
+        let mut iterations = 0;
+
+        while n > 0 {
+            if n == 1 {
+                values.push(a.get(0, 0));
+                break;
+            }
+            let small = |x: f64, i: usize| x.abs() <= f64::EPSILON * (a.get(i, i).abs() + a.get(i - 1, i - 1).abs());
+            if small(a.get(n - 1, n - 2), n - 1) {
+                values.push(a.get(n - 1, n - 1));
+                n -= 1;
+                iterations = 0;
+                continue;
+            }
+            // A trailing 2×2 block split off from the rest is solved directly
+            if n == 2 || small(a.get(n - 2, n - 3), n - 2) {
+                let (first, second) = block_eigenvalues(&a, n - 2)?;
+                values.extend([first, second]);
+                n -= 2;
+                iterations = 0;
+                continue;
+            }
+
+            iterations += 1;
+            if iterations > MAX_EIGEN_ITERATIONS {
+                return Err("eig: the eigenvalues did not converge".to_string());
+            }
+            // Wilkinson shift, with an occasional exceptional shift to break cycles
+            let mut shift = wilkinson_shift(&a, n);
+            if iterations % 11 == 0 {
+                shift += a.get(n - 1, n - 2).abs();
+            }
+
+            let mut block = Matrix::zeros(n, n);
+            for i in 0..n {
+                for j in 0..n {
+                    block.set(i, j, a.get(i, j) - if i == j { shift } else { 0.0 });
+                }
+            }
+            let (q, r) = block.qr();
+            let next = r.mul(&q)?;
+            for i in 0..n {
+                for j in 0..n {
+                    a.set(i, j, next.get(i, j) + if i == j { shift } else { 0.0 });
+                }
+            }
+        }
+        values.sort_by(f64::total_cmp);
+        Ok(values)
+    }
+}
+
+/// Eigenvalues of the 2×2 block starting at row and column `k`.
+fn block_eigenvalues(a: &Matrix, k: usize) -> Result<(f64, f64), String> {
+    let (p, q, r, s) = (a.get(k, k), a.get(k, k + 1), a.get(k + 1, k), a.get(k + 1, k + 1));
+    let half_trace = (p + s) / 2.0;
+    let discriminant = ((p - s) / 2.0).powi(2) + q * r;
+    if discriminant < 0.0 {
+        return Err(format!(
+            "eig: complex eigenvalues {} ± {}i are not supported",
+            round_for_message(half_trace),
+            round_for_message((-discriminant).sqrt())
+        ));
+    }
+    let root = discriminant.sqrt();
+    Ok((half_trace - root, half_trace + root))
+}
+
+/// Eigenvalue of the trailing 2×2 block closest to the last diagonal entry.
+fn wilkinson_shift(a: &Matrix, n: usize) -> f64 {
+    let (p, q, r, s) = (a.get(n - 2, n - 2), a.get(n - 2, n - 1), a.get(n - 1, n - 2), a.get(n - 1, n - 1));
+    let half_difference = (p - s) / 2.0;
+    let discriminant = half_difference.powi(2) + q * r;
+    if discriminant < 0.0 {
+        return s;
+    }
+    let root = discriminant.sqrt();
+    let candidates = [(p + s) / 2.0 - root, (p + s) / 2.0 + root];
+    if (candidates[0] - s).abs() < (candidates[1] - s).abs() { candidates[0] } else { candidates[1] }
+}
+
+fn round_for_message(x: f64) -> String {
+    let s = format!("{:.6}", x);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Arithmetic where either operand is a matrix or vector; `None` when neither is.
+pub fn binary(op: char, a: &Value, b: &Value) -> Result<Option<Value>, String> {
+    if !is_array(a) && !is_array(b) {
+        return Ok(None);
+    }
+    let result = match (op, a, b) {
+        ('+', Value::Matrix(x), Value::Matrix(y)) => Value::Matrix(x.zip(y, "add", |p, q| p + q)?),
+        ('-', Value::Matrix(x), Value::Matrix(y)) => Value::Matrix(x.zip(y, "subtract", |p, q| p - q)?),
+        ('+' | '-', Value::Vector(x), Value::Vector(y)) => {
+            if x.len() != y.len() {
+                return Err(format!("Cannot combine vectors of length {} and {}", x.len(), y.len()));
+            }
+            let sign = if op == '+' { 1.0 } else { -1.0 };
+            Value::Vector(x.iter().zip(y).map(|(p, q)| p + sign * q).collect())
+        }
+        ('+' | '-', _, _) => {
+            return Err(format!("Cannot {} {} and {}", if op == '+' { "add" } else { "subtract" }, describe(a), describe(b)));
+        }
+
+        ('*', Value::Matrix(x), Value::Matrix(y)) => Value::Matrix(x.mul(y)?),
+        ('*', Value::Matrix(x), Value::Vector(v)) => Value::Vector(x.mul(&Matrix::column(v))?.data),
+        ('*', Value::Vector(v), Value::Matrix(x)) => Value::Vector(Matrix::column(v).transpose().mul(x)?.data),
+        ('*', Value::Vector(_), Value::Vector(_)) => {
+            return Err("Use dot(u, v) or cross(u, v) to multiply two vectors".to_string());
+        }
+        ('*', array, scalar) | ('*', scalar, array) if !is_array(scalar) => {
+            let k = scalar.to_f64()?;
+            scaled(array, |x| x * k)
+        }
+        ('/', array, scalar) if !is_array(scalar) => {
+            let k = scalar.to_f64()?;
+            scaled(array, |x| x / k)
+        }
+        ('/', _, _) => return Err("Cannot divide by a matrix; use inv(A) or solve(A, b)".to_string()),
+
+        ('^', Value::Matrix(x), exponent) if !is_array(exponent) => match exponent.to_integer() {
+            Some(n) => Value::Matrix(x.powi(n as i64)?),
+            None => return Err("Matrix powers need an integer exponent".to_string()),
+        },
+        _ => return Err(format!("Cannot apply '{}' to {} and {}", op, describe(a), describe(b))),
+    };
+    Ok(Some(result))
+}
+
+/// Negates every entry of a matrix or vector.
+pub fn neg(a: &Value) -> Value {
+    scaled(a, |x| -x)
+}
+
+fn is_array(value: &Value) -> bool {
+    matches!(value, Value::Matrix(_) | Value::Vector(_))
+}
+
+fn scaled(array: &Value, f: impl Fn(f64) -> f64) -> Value {
+    match array {
+        Value::Matrix(m) => Value::Matrix(m.map(f)),
+        Value::Vector(v) => Value::Vector(v.iter().map(|&x| f(x)).collect()),
+        other => other.clone(),
+    }
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Matrix(m) => format!("a {} matrix", m.shape()),
+        Value::Vector(v) => format!("a vector of length {}", v.len()),
+        _ => "a number".to_string(),
+    }
+}
+
+/// A matrix argument; vectors count as a single column.
+fn matrix_arg(name: &str, value: &Value) -> Result<Matrix, String> {
+    match value {
+        Value::Matrix(m) => Ok(m.clone()),
+        Value::Vector(v) => Ok(Matrix::column(v)),
+        _ => Err(format!("{} expects a matrix, e.g. {}([[1, 2], [3, 4]])", name, name)),
+    }
+}
+
+fn vector_arg<'a>(name: &str, value: &'a Value) -> Result<&'a [f64], String> {
+    match value {
+        Value::Vector(v) => Ok(v),
+        _ => Err(format!("{} expects vectors, e.g. {}([1, 2, 3], [4, 5, 6])", name, name)),
+    }
+}
+
+/// A single column (or row) comes back as a vector, anything else as a matrix.
+fn from_matrix(m: Matrix, as_vector: bool) -> Value {
+    if as_vector && m.cols == 1 { Value::Vector(m.data) } else { Value::Matrix(m) }
+}
+
+fn arity(name: &str, args: &[Value], count: usize) -> Result<(), String> {
+    if args.len() == count {
+        Ok(())
+    } else {
+        let plural = if count == 1 { "" } else { "s" };
+        Err(format!("{} requires {} argument{}", name, count, plural))
+    }
+}
+
+/// Matrix and vector functions.
+pub fn evaluate(name: &str, args: &[Value]) -> Result<Value, String> {
+    match name {
+        "transpose" => {
+            arity(name, args, 1)?;
+            Ok(Value::Matrix(matrix_arg(name, &args[0])?.transpose()))
+        }
+        "det" => {
+            arity(name, args, 1)?;
+            Ok(Value::Number(matrix_arg(name, &args[0])?.determinant()?))
+        }
+        "inv" => {
+            arity(name, args, 1)?;
+            Ok(Value::Matrix(matrix_arg(name, &args[0])?.inverse()?))
+        }
+        "rank" => {
+            arity(name, args, 1)?;
+            Ok(Value::Integer(matrix_arg(name, &args[0])?.rank() as i128))
+        }
+        "trace" => {
+            arity(name, args, 1)?;
+            Ok(Value::Number(matrix_arg(name, &args[0])?.trace()?))
+        }
+        "solve" => {
+            arity(name, args, 2)?;
+            let a = matrix_arg(name, &args[0])?;
+            let b = matrix_arg(name, &args[1])?;
+            Ok(from_matrix(a.solve(&b)?, matches!(args[1], Value::Vector(_))))
+        }
+        "lu" => {
+            arity(name, args, 1)?;
+            let lu = matrix_arg(name, &args[0])?.lu()?;
+            let n = lu.permutation.len();
+            let mut p = Matrix::zeros(n, n);
+            for (i, &row) in lu.permutation.iter().enumerate() {
+                p.set(i, row, 1.0);
+            }
+            Ok(Value::Tuple(vec![Value::Matrix(lu.l), Value::Matrix(lu.u), Value::Matrix(p)]))
+        }
+        "qr" => {
+            arity(name, args, 1)?;
+            let (q, r) = matrix_arg(name, &args[0])?.qr();
+            Ok(Value::Tuple(vec![Value::Matrix(q), Value::Matrix(r)]))
+        }
+        "eig" | "eigenvalues" => {
+            arity(name, args, 1)?;
+            Ok(Value::Vector(matrix_arg(name, &args[0])?.eigenvalues()?))
+        }
+        "dot" => {
+            arity(name, args, 2)?;
+            let (u, v) = (vector_arg(name, &args[0])?, vector_arg(name, &args[1])?);
+            if u.len() != v.len() {
+                return Err(format!("dot: vectors of length {} and {}", u.len(), v.len()));
+            }
+            Ok(Value::Number(u.iter().zip(v).map(|(x, y)| x * y).sum()))
+        }
+        "cross" => {
+            arity(name, args, 2)?;
+            let (u, v) = (vector_arg(name, &args[0])?, vector_arg(name, &args[1])?);
+            if u.len() != 3 || v.len() != 3 {
+                return Err("cross requires two vectors of length 3".to_string());
+            }
+            Ok(Value::Vector(vec![
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ]))
+        }
+        "norm" => {
+            // Euclidean length of a vector, Frobenius norm of a matrix
+            arity(name, args, 1)?;
+            let entries = match &args[0] {
+                Value::Vector(v) => v.as_slice(),
+                Value::Matrix(m) => m.entries(),
+                _ => return Err("norm expects a vector or matrix, e.g. norm([3, 4])".to_string()),
+            };
+            Ok(Value::Number(entries.iter().map(|x| x * x).sum::<f64>().sqrt()))
+        }
+        "identity" => {
+            arity(name, args, 1)?;
+            match args[0].to_integer() {
+                Some(n) if (1..=1000).contains(&n) => Ok(Value::Matrix(Matrix::identity(n as usize))),
+                _ => Err("identity requires a size from 1 to 1000".to_string()),
+            }
+        }
+        _ => Err(format!("Unknown matrix function: {}", name)),
+    }
+}
+
+/// Builds a literal from its bracketed items: numbers make a vector, vectors of equal
+/// length make the rows of a matrix.
+pub fn literal(items: Vec<Value>) -> Result<Value, String> {
+    if items.iter().all(|item| matches!(item, Value::Vector(_))) && !items.is_empty() {
+        let rows: Vec<Vec<f64>> = items
+            .into_iter()
+            .map(|item| match item {
+                Value::Vector(v) => v,
+                _ => unreachable!("checked above"),
+            })
+            .collect();
+        return Ok(Value::Matrix(Matrix::from_rows(&rows)?));
+    }
+    if items.is_empty() {
+        return Err("Empty brackets; write a vector such as [1, 2, 3]".to_string());
+    }
+    let entries = items
+        .iter()
+        .map(|item| match item {
+            Value::Number(_) | Value::Integer(_) | Value::Factors(..) | Value::Extended(_) => item.to_f64(),
+            Value::Matrix(_) | Value::Vector(_) => Err("Matrix rows must all be vectors of the same length".to_string()),
+            _ => Err("Matrix and vector entries must be plain numbers".to_string()),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Value::Vector(entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix(rows: &[&[f64]]) -> Value {
+        Value::Matrix(Matrix::from_rows(&rows.iter().map(|row| row.to_vec()).collect::<Vec<_>>()).unwrap())
+    }
+
+    fn close(value: Value, expected: &[f64]) {
+        let actual = match &value {
+            Value::Matrix(m) => m.entries().to_vec(),
+            Value::Vector(v) => v.clone(),
+            other => vec![other.to_f64().unwrap()],
+        };
+        assert_eq!(actual.len(), expected.len(), "{:?}", value);
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-12, "{:?} is not {:?}", value, expected);
+        }
+    }
+
+    #[test]
+    fn inverse_determinant_and_solve() {
+        let a = matrix(&[&[1.0, 2.0], &[3.0, 4.0]]);
+        close(evaluate("inv", std::slice::from_ref(&a)).unwrap(), &[-2.0, 1.0, 1.5, -0.5]);
+        close(evaluate("det", std::slice::from_ref(&a)).unwrap(), &[-2.0]);
+        close(binary('^', &a, &Value::Integer(-1)).unwrap().unwrap(), &[-2.0, 1.0, 1.5, -0.5]);
+        let b = matrix(&[&[2.0, 1.0], &[1.0, 3.0]]);
+        close(evaluate("solve", &[b, Value::Vector(vec![3.0, 5.0])]).unwrap(), &[0.8, 1.4]);
+    }
+
+    #[test]
+    fn singular_matrices_are_rejected() {
+        let singular = matrix(&[&[1.0, 2.0], &[2.0, 4.0]]);
+        assert_eq!(evaluate("inv", std::slice::from_ref(&singular)), Err("Matrix is singular".to_string()));
+        assert!(evaluate("solve", &[singular.clone(), Value::Vector(vec![1.0, 2.0])]).is_err());
+        assert_eq!(evaluate("rank", std::slice::from_ref(&singular)), Ok(Value::Integer(1)));
+        close(evaluate("det", &[singular]).unwrap(), &[0.0]);
+    }
+
+    #[test]
+    fn eigenvalues_and_products() {
+        close(evaluate("eig", &[matrix(&[&[2.0, 1.0], &[1.0, 2.0]])]).unwrap(), &[1.0, 3.0]);
+        assert!(evaluate("eig", &[matrix(&[&[0.0, -1.0], &[1.0, 0.0]])]).is_err());
+        let u = Value::Vector(vec![1.0, 0.0, 0.0]);
+        let v = Value::Vector(vec![0.0, 1.0, 0.0]);
+        close(evaluate("cross", &[u.clone(), v.clone()]).unwrap(), &[0.0, 0.0, 1.0]);
+        close(evaluate("dot", &[u, v]).unwrap(), &[0.0]);
+        let row = matrix(&[&[1.0, 2.0]]);
+        assert!(binary('*', &row, &row).is_err());
+        assert!(literal(vec![Value::Vector(vec![1.0, 2.0]), Value::Vector(vec![3.0])]).is_err());
+    }
+}
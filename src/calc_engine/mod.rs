@@ -0,0 +1,1950 @@
+mod chemistry;
+mod closed_form;
+mod complex;
+mod datetime;
+mod distributions;
+mod extended;
+mod finance;
+mod format;
+mod interval;
+mod logic;
+mod matrix;
+mod modular;
+mod number_theory;
+mod ode;
+mod optimize;
+mod polynomial;
+mod random;
+mod regression;
+mod signal;
+mod special;
+mod symbolic;
+mod value;
+
+pub use format::DisplaySettings;
+pub use random::Random;
+pub use value::{ModuloKind, Value};
+
+use datetime::DateTime;
+use extended::Extended;
+use interval::Interval;
+use std::f64::consts::{PI, E};
+
+/// Most trials `simulate` runs.
+const MAX_TRIALS: usize = 10_000_000;
+
+/// Trials whose draws appear in a detailed `simulate` trace.
+const TRACED_TRIALS: usize = 3;
+
+#[derive(Debug, PartialEq)]
+pub enum Token {
+    Number(f64),
+    Integer(i128),
+    /// A literal beyond the range of `f64`, e.g. `1e400`.
+    Extended(Extended),
+    Date(DateTime),
+    /// Length of time in seconds, e.g. `3d 4h`.
+    Duration(f64),
+    /// A dice roll: how many dice and how many sides each, e.g. `3d6`. A lone `d20` is
+    /// an identifier, so that names such as `d1` stay free for formulas and variables.
+    Dice(i128, i128),
+    /// A string in double quotes, e.g. a chemical formula in `molar("H2O")`.
+    Text(String),
+    Op(char),
+    Ident(String),
+    LParen,
+    RParen,
+    /// `[` and `]` around vector and matrix literals.
+    LBracket,
+    RBracket,
+    /// `{` and `}` around the body of a `mod p { ... }` block.
+    LBrace,
+    RBrace,
+    Comma,
+}
+
+/// Session-wide evaluation options.
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    /// Evaluate every number as an interval with outward rounding.
+    pub interval: bool,
+    /// Treat overflow, underflow to zero and NaN as errors instead of printing them.
+    pub strict: bool,
+    pub percent_sign: PercentSign,
+    pub display: DisplaySettings,
+    /// Generator for `rand()`, dice and `simulate`, which carries on across a session.
+    pub random: Random,
+    /// Evaluate `+ - * / ^` in the integers mod p, as inside `mod p { ... }`.
+    pub modulus: Option<u64>,
+}
+
+/// What the `%` sign means.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PercentSign {
+    /// Postfix percent: `15%` is 0.15 and `200 + 15%` is 230.
+    #[default]
+    Percent,
+    /// Binary remainder operator, as in many programming languages.
+    Modulo(ModuloKind),
+}
+
+impl Settings {
+    /// Changes one option by name, as typed in `set <name> <value>` or `--<name> <value>`.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "percent" => {
+                self.percent_sign = match value {
+                    "percent" => PercentSign::Percent,
+                    "mod" => PercentSign::Modulo(ModuloKind::Floored),
+                    "rem" | "fmod" => PercentSign::Modulo(ModuloKind::Truncated),
+                    "emod" => PercentSign::Modulo(ModuloKind::Euclidean),
+                    _ => return Err("percent expects percent, mod, rem, emod or fmod".to_string()),
+                };
+                Ok(())
+            }
+            "strict" => {
+                self.strict = match value {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err("strict expects 'on' or 'off'".to_string()),
+                };
+                Ok(())
+            }
+            "modulus" => {
+                self.modulus = match value {
+                    "off" => None,
+                    _ => Some(modular::modulus(&Value::Integer(
+                        value.parse().map_err(|_| "modulus expects a whole number of at least 2 or 'off'")?,
+                    ))?),
+                };
+                Ok(())
+            }
+            "seed" => {
+                self.random = match value {
+                    "off" => Random::default(),
+                    _ => Random::seeded(value.parse().map_err(|_| "seed expects a whole number from 0 or 'off'")?),
+                };
+                Ok(())
+            }
+            _ => self.display.set(name, value),
+        }
+    }
+
+    /// Whether `name` is an option accepted by `set`.
+    pub fn is_option(name: &str) -> bool {
+        matches!(name, "percent" | "strict" | "seed" | "modulus") || DisplaySettings::is_option(name)
+    }
+}
+
+pub struct Step {
+    pub operation: String,
+    pub result: Value,
+}
+
+pub struct EvaluationTrace {
+    pub steps: Vec<Step>,
+    pub detailed_mode: bool,
+    /// The first step that overflowed, underflowed to zero or produced NaN, recorded
+    /// whether or not detailed mode is on.
+    pub first_non_finite: Option<NumericError>,
+}
+
+/// What went wrong with a floating-point result.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumericIssue {
+    /// Finite operands gave an infinite result.
+    Overflow,
+    /// Nonzero operands gave a result too small to represent, which rounded to zero.
+    Underflow,
+    NotANumber,
+}
+
+/// A step whose result is not a trustworthy number, e.g. `2 ^ 10000`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericError {
+    pub issue: NumericIssue,
+    pub operation: String,
+    /// Index into `EvaluationTrace::steps` in detailed mode.
+    pub step: Option<usize>,
+}
+
+impl std::fmt::Display for NumericError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.issue {
+            NumericIssue::Overflow => write!(f, "Overflow in {}: result is too large to represent", self.operation),
+            NumericIssue::Underflow => write!(f, "Underflow in {}: result rounds to zero", self.operation),
+            NumericIssue::NotANumber => write!(f, "Invalid operation in {}: result is not a number", self.operation),
+        }
+    }
+}
+
+impl EvaluationTrace {
+    pub fn new(detailed_mode: bool) -> Self {
+        EvaluationTrace {
+            steps: Vec::new(),
+            detailed_mode,
+            first_non_finite: None,
+        }
+    }
+
+    pub fn add_step(&mut self, operation: String, result: Value) {
+        if self.detailed_mode {
+            self.steps.push(Step { operation, result });
+        }
+    }
+}
+
+pub fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' => {
+                chars.next();
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                chars.next();
+            }
+            '[' => {
+                tokens.push(Token::LBracket);
+                chars.next();
+            }
+            ']' => {
+                tokens.push(Token::RBracket);
+                chars.next();
+            }
+            '{' => {
+                tokens.push(Token::LBrace);
+                chars.next();
+            }
+            '}' => {
+                tokens.push(Token::RBrace);
+                chars.next();
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                chars.next();
+            }
+            '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => text.push(c),
+                        None => return Err("Missing closing quote".to_string()),
+                    }
+                }
+                tokens.push(Token::Text(text));
+            }
+            // `+/-` is the ASCII spelling of `±`; `+-` stays a sum and a negation, as in `5+-3`
+            '+' if starts_plus_minus(&chars) => {
+                tokens.push(Token::Op('±'));
+                chars.nth(2);
+            }
+            '+' | '-' | '*' | '/' | '^' | '%' | '±' => {
+                tokens.push(Token::Op(c));
+                chars.next();
+            }
+            // `r` is the root operator unless it starts an identifier such as `round` or
+            // `r1`; after a number or a closing bracket, `r3` as in `8 r3` is the operator
+            // and a degree, while words such as `rem` stay whole
+            'r' if !starts_identifier(&chars) || (ends_operand(tokens.last()) && starts_digit(&chars)) => {
+                tokens.push(Token::Op(c));
+                chars.next();
+            }
+            '0'..='9' | '.' => {
+                if let Some(date) = datetime::scan_date_literal(&mut chars)? {
+                    tokens.push(Token::Date(date));
+                    continue;
+                }
+
+                let mut num_str = String::new();
+                let mut has_dot = false;
+                let mut has_exp = false;
+
+                while let Some(&ch) = chars.peek() {
+                    match ch {
+                        '.' if has_dot => break,
+                        '.' => {
+                            has_dot = true;
+                            num_str.push(ch);
+                            chars.next();
+                        }
+                        'e' | 'E' if !has_exp => {
+                            has_exp = true;
+                            num_str.push(ch);
+                            chars.next();
+
+                            if let Some(&next_ch) = chars.peek() {
+                                if next_ch == '+' || next_ch == '-' {
+                                    num_str.push(next_ch);
+                                    chars.next();
+                                }
+                            }
+                        }
+                        '0'..='9' => {
+                            num_str.push(ch);
+                            chars.next();
+                        }
+                        _ => break,
+                    }
+                }
+
+                // `3d6` is a dice roll, while `3d` alone is still three days
+                if !has_dot && !has_exp {
+                    if let Some(sides) = scan_dice_sides(&mut chars) {
+                        let count = num_str.parse::<i128>()
+                            .map_err(|_| format!("Invalid number of dice: '{}'", num_str))?;
+                        tokens.push(Token::Dice(count, sides));
+                        continue;
+                    }
+                }
+
+                if let Some(unit) = datetime::scan_duration_unit(&mut chars) {
+                    let number = num_str.parse::<f64>()
+                        .map_err(|_| format!("Invalid number: '{}'", num_str))?;
+                    // Adjacent durations such as `3d 4h` form a single duration
+                    match tokens.last_mut() {
+                        Some(Token::Duration(seconds)) => *seconds += number * unit,
+                        _ => tokens.push(Token::Duration(number * unit)),
+                    }
+                    continue;
+                }
+
+                // Integer literals stay exact as long as they fit
+                if let (false, false, Ok(integer)) = (has_dot, has_exp, num_str.parse::<i128>()) {
+                    tokens.push(Token::Integer(integer));
+                    continue;
+                }
+                let number = num_str.parse::<f64>()
+                    .map_err(|_| format!("Invalid number: '{}'", num_str))?;
+                // Literals such as 1e400 keep their exponent instead of becoming inf or 0
+                if number.is_infinite() || ((number == 0.0 || number.is_subnormal()) && has_exp) {
+                    if let Some(wide) = extended::parse_literal(&num_str) {
+                        tokens.push(Token::Extended(wide));
+                        continue;
+                    }
+                }
+                tokens.push(Token::Number(number));
+            }
+            'a'..='z' | 'A'..='Z' | '_' => {
+                let mut ident = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_alphanumeric() || ch == '_' {
+                        ident.push(ch);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            _ => return Err(format!("Unknown character: '{}'", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+fn starts_identifier(chars: &std::iter::Peekable<std::str::Chars>) -> bool {
+    let mut lookahead = chars.clone();
+    lookahead.next();
+    matches!(lookahead.peek(), Some(&ch) if ch.is_alphanumeric() || ch == '_')
+}
+
+fn starts_digit(chars: &std::iter::Peekable<std::str::Chars>) -> bool {
+    let mut lookahead = chars.clone();
+    lookahead.next();
+    matches!(lookahead.peek(), Some(ch) if ch.is_ascii_digit())
+}
+
+/// Whether `token` ends an operand, so that an operator rather than a name follows.
+fn ends_operand(token: Option<&Token>) -> bool {
+    matches!(
+        token,
+        Some(Token::Number(_) | Token::Integer(_) | Token::Extended(_) | Token::RParen | Token::RBracket)
+    )
+}
+
+/// The number of sides of a single die written as a name, such as `d20`.
+fn single_die(name: &str) -> Option<i128> {
+    let sides = name.strip_prefix('d')?;
+    if sides.is_empty() || !sides.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    sides.parse().ok()
+}
+
+/// The number of sides after the `d` of a dice roll such as `3d6`, consuming both.
+fn scan_dice_sides(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<i128> {
+    let mut lookahead = chars.clone();
+    if lookahead.next() != Some('d') {
+        return None;
+    }
+    let mut digits = String::new();
+    while let Some(&ch) = lookahead.peek() {
+        if !ch.is_ascii_digit() {
+            break;
+        }
+        digits.push(ch);
+        lookahead.next();
+    }
+    if digits.is_empty() || matches!(lookahead.peek(), Some(&ch) if ch.is_alphanumeric() || ch == '_') {
+        return None;
+    }
+    *chars = lookahead;
+    digits.parse().ok()
+}
+
+fn starts_plus_minus(chars: &std::iter::Peekable<std::str::Chars>) -> bool {
+    let mut lookahead = chars.clone();
+    lookahead.next();
+    lookahead.next() == Some('/') && lookahead.next() == Some('-')
+}
+
+/// Result of a multiplicative term; `percent` holds the number written before a
+/// trailing `%`, so `200 + 15%` can add 15% of 200.
+struct Term {
+    value: Value,
+    percent: Option<Value>,
+}
+
+pub struct Parser {
+    tokens: Vec<Token>,
+    current: usize,
+    settings: Settings,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<Token>, settings: Settings) -> Self {
+        Parser { tokens, current: 0, settings }
+    }
+
+    /// The generator after evaluation, so the next input draws new numbers.
+    pub fn random(&self) -> &Random {
+        &self.settings.random
+    }
+
+    pub fn parse(&mut self, trace: &mut EvaluationTrace) -> Result<Value, String> {
+        let result = self.expr(trace)?;
+        if self.current < self.tokens.len() {
+            return Err("Unexpected tokens at end of expression".to_string());
+        }
+        match self.settings.modulus {
+            Some(p) => Ok(Value::Integer(modular::residue(&result, p)? as i128)),
+            None => Ok(result),
+        }
+    }
+
+    fn expr(&mut self, trace: &mut EvaluationTrace) -> Result<Value, String> {
+        let first = self.term(trace)?;
+        let mut left_is_percent = first.percent.is_some();
+        let mut left = first.value;
+
+        while self.current < self.tokens.len() {
+            match &self.tokens[self.current] {
+                Token::Op(op @ ('+' | '-')) => {
+                    let op = *op;
+                    self.current += 1;
+                    let right = self.term(trace)?;
+                    let operation = format!("{} {} {}", self.show(&left), op, self.show_term(&right));
+                    // Like a desk calculator, `200 + 15%` adds 15% of 200
+                    let amount = match (right.percent, self.settings.modulus) {
+                        (Some(_), Some(p)) if !left_is_percent => modular::binary('*', &left, &right.value, p)?,
+                        (Some(_), None) if !left_is_percent => value::mul(&left, &right.value)?,
+                        _ => right.value,
+                    };
+                    let left_before = left;
+                    left = match self.settings.modulus {
+                        Some(p) => modular::binary(op, &left_before, &amount, p)?,
+                        None if op == '+' => value::add(&left_before, &amount)?,
+                        None => value::sub(&left_before, &amount)?,
+                    };
+                    left_is_percent = false;
+                    left = self.record(trace, operation, &[&left_before, &amount], left, false)?;
+                }
+                Token::Ident(word) if word.eq_ignore_ascii_case("as") => {
+                    // `x as % of y` has the lowest precedence of all
+                    self.current += 1;
+                    if !self.next_is(&Token::Op('%')) || !self.next_is_keyword_at(self.current + 1, "of") {
+                        return Err("Expected '% of' after 'as', e.g. 30 as % of 120".to_string());
+                    }
+                    self.current += 2;
+                    let whole = self.expr(trace)?;
+                    let operation = format!("{} as % of {}", self.show(&left), self.show(&whole));
+                    let share = value::mul(&value::div(&left, &whole)?, &Value::Integer(100))?;
+                    left = self.record(trace, operation, &[&left, &whole], share, true)?;
+                    break;
+                }
+                _ => break,
+            }
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self, trace: &mut EvaluationTrace) -> Result<Term, String> {
+        let mut left = self.percent(trace)?;
+
+        while self.current < self.tokens.len() {
+            let op = match &self.tokens[self.current] {
+                Token::Op(op @ ('*' | '/')) => op.to_string(),
+                Token::Op('%') if self.settings.percent_sign != PercentSign::Percent => "%".to_string(),
+                Token::Ident(word) if word.eq_ignore_ascii_case("mod") => "mod".to_string(),
+                Token::Ident(word) if word.eq_ignore_ascii_case("rem") => "rem".to_string(),
+                Token::Ident(word) if word.eq_ignore_ascii_case("of") => {
+                    if left.percent.is_none() {
+                        return Err("'of' must follow a percentage, e.g. 50% of 80".to_string());
+                    }
+                    "of".to_string()
+                }
+                _ => break,
+            };
+            self.current += 1;
+            let right = self.percent(trace)?;
+            let operation = format!("{} {} {}", self.show_term(&left), op, self.show_term(&right));
+            let result = match (op.as_str(), self.settings.modulus) {
+                ("*", Some(p)) => modular::binary('*', &left.value, &right.value, p)?,
+                ("/", Some(p)) => modular::binary('/', &left.value, &right.value, p)?,
+                ("*" | "of", _) => value::mul(&left.value, &right.value)?,
+                ("/", _) => value::div(&left.value, &right.value)?,
+                ("mod", _) => value::modulo(&left.value, &right.value, ModuloKind::Floored)?,
+                ("rem", _) => value::modulo(&left.value, &right.value, ModuloKind::Truncated)?,
+                _ => match self.settings.percent_sign {
+                    PercentSign::Modulo(kind) => value::modulo(&left.value, &right.value, kind)?,
+                    PercentSign::Percent => unreachable!("percent signs are handled in Parser::percent"),
+                },
+            };
+            let may_underflow = matches!(op.as_str(), "*" | "/" | "of");
+            let result = self.record(trace, operation, &[&left.value, &right.value], result, may_underflow)?;
+            left = Term { value: result, percent: None };
+        }
+        Ok(left)
+    }
+
+    /// A factor with an optional trailing `%`, which divides it by 100.
+    fn percent(&mut self, trace: &mut EvaluationTrace) -> Result<Term, String> {
+        let value = self.factor(trace)?;
+        if self.settings.percent_sign != PercentSign::Percent || !self.next_is(&Token::Op('%')) {
+            return Ok(Term { value, percent: None });
+        }
+        self.current += 1;
+
+        // `10 % 3` used to mean modulo; point users to the keyword instead of guessing
+        let starts_operand = match self.tokens.get(self.current) {
+            Some(Token::Ident(word)) => !["mod", "rem", "of", "as"].iter().any(|k| word.eq_ignore_ascii_case(k)),
+            Some(Token::Op(_) | Token::RParen | Token::RBracket | Token::RBrace | Token::Comma) | None => false,
+            Some(_) => true,
+        };
+        if starts_operand {
+            return Err("'%' means percent; use 'mod' for remainders (e.g. 10 mod 3) or 'set percent mod'".to_string());
+        }
+
+        let result = match self.settings.modulus {
+            Some(p) => modular::binary('/', &value, &Value::Integer(100), p)?,
+            None => value::div(&value, &Value::Integer(100))?,
+        };
+        let result = self.record(trace, format!("{}%", self.show(&value)), &[&value], result, true)?;
+        Ok(Term { value: result, percent: Some(value) })
+    }
+
+    fn factor(&mut self, trace: &mut EvaluationTrace) -> Result<Value, String> {
+        let base = self.uncertain(trace)?;
+
+        if self.current < self.tokens.len() && self.tokens[self.current] == Token::Op('r') {
+            self.current += 1;
+            let exponent = self.power(trace)?;
+            let result = if self.settings.interval {
+                let base = Interval::from_value(&base)?;
+                Value::Interval(base.root(Interval::from_value(&exponent)?)?)
+            } else if matches!(base, Value::Extended(_)) || matches!(exponent, Value::Extended(_)) {
+                extended::root(&base, &exponent)?
+            } else {
+                propagate_uncertainty(&[base.clone(), exponent.clone()], |args| {
+                    let (base, exponent) = (args[0], args[1]);
+                    if exponent == 0.0 {
+                        return Err("Root degree cannot be zero".to_string());
+                    }
+                    if base < 0.0 && exponent % 2.0 == 0.0 {
+                        return Err("Even root of negative number".to_string());
+                    }
+                    Ok(base.powf(1.0 / exponent))
+                })?
+            };
+            let operation = format!("{} r {}", self.show(&base), self.show(&exponent));
+            self.record(trace, operation, &[&base, &exponent], result, false)
+        } else {
+            Ok(base)
+        }
+    }
+
+    /// A measurement such as `9.81 ± 0.02`; binds tighter than `*` so `2 * 9.81 ± 0.02`
+    /// scales the whole measurement.
+    fn uncertain(&mut self, trace: &mut EvaluationTrace) -> Result<Value, String> {
+        let center = self.power(trace)?;
+
+        if self.current < self.tokens.len() && self.tokens[self.current] == Token::Op('±') {
+            self.current += 1;
+            let uncertainty = self.power(trace)?;
+            let result = value::plus_minus(&center, &uncertainty)?;
+            let operation = format!("{} ± {}", self.show(&center), self.show(&uncertainty));
+            self.record(trace, operation, &[&center, &uncertainty], result, false)
+        } else {
+            Ok(center)
+        }
+    }
+
+    fn power(&mut self, trace: &mut EvaluationTrace) -> Result<Value, String> {
+        let left = self.unary(trace)?;
+
+        if self.current < self.tokens.len() && self.tokens[self.current] == Token::Op('^') {
+            self.current += 1;
+            // Exponents are ordinary integers even in modular arithmetic
+            let modulus = self.settings.modulus.take();
+            let right = self.power(trace);
+            self.settings.modulus = modulus;
+            let right = right?;
+            let result = match modulus {
+                Some(p) => modular::binary('^', &left, &right, p)?,
+                None => value::pow(&left, &right)?,
+            };
+            let operation = format!("{} ^ {}", self.show(&left), self.show(&right));
+            self.record(trace, operation, &[&left, &right], result, true)
+        } else {
+            Ok(left)
+        }
+    }
+
+    fn unary(&mut self, trace: &mut EvaluationTrace) -> Result<Value, String> {
+        let mut negative = false;
+        let mut sign_changes = 0;
+
+        while self.current < self.tokens.len() {
+            match self.tokens[self.current] {
+                Token::Op('+') => {
+                    self.current += 1;
+                }
+                Token::Op('-') => {
+                    negative = !negative;
+                    sign_changes += 1;
+                    self.current += 1;
+                }
+                _ => break,
+            }
+        }
+
+        let operand = self.primary(trace)?;
+        let result = if negative { value::neg(&operand)? } else { operand.clone() };
+
+        if sign_changes > 0 {
+            let sign_str = if negative { "-" } else { "+" };
+            let operation = format!("{} {}", sign_str, self.show(&value::abs(&result)?));
+            return self.record(trace, operation, &[&operand], result, false);
+        }
+
+        Ok(result)
+    }
+
+    fn primary(&mut self, trace: &mut EvaluationTrace) -> Result<Value, String> {
+        if self.current >= self.tokens.len() {
+            return Err("Unexpected end of input".to_string());
+        }
+
+        match &self.tokens[self.current] {
+            Token::Number(n) => {
+                self.current += 1;
+                if !n.is_finite() {
+                    return self.record(trace, "number literal".to_string(), &[], Value::Number(*n), false);
+                }
+                Ok(self.number(*n))
+            }
+            Token::Extended(x) => {
+                self.current += 1;
+                x.into_value()
+            }
+            Token::Integer(i) => {
+                self.current += 1;
+                if self.settings.interval {
+                    return Ok(Value::Interval(Interval::enclosing(*i as f64)));
+                }
+                Ok(Value::Integer(*i))
+            }
+            Token::Date(date) => {
+                self.current += 1;
+                Ok(Value::Date(*date))
+            }
+            Token::Duration(seconds) => {
+                self.current += 1;
+                Ok(Value::Duration(*seconds))
+            }
+            Token::Text(text) => {
+                self.current += 1;
+                Ok(Value::Text(text.clone()))
+            }
+            &Token::Dice(count, sides) => {
+                self.current += 1;
+                self.roll(count, sides, trace)
+            }
+            Token::LParen => {
+                self.current += 1;
+                let expr = self.expr(trace)?;
+                if self.current < self.tokens.len() && self.tokens[self.current] == Token::RParen {
+                    self.current += 1;
+                    Ok(expr)
+                } else {
+                    Err("Missing closing parenthesis".to_string())
+                }
+            }
+            Token::LBracket => {
+                self.current += 1;
+                let mut items = Vec::new();
+                while !self.next_is(&Token::RBracket) {
+                    items.push(self.expr(trace)?);
+                    match self.tokens.get(self.current) {
+                        Some(Token::Comma) => self.current += 1,
+                        Some(Token::RBracket) => {}
+                        _ => return Err("Expected comma or closing bracket".to_string()),
+                    }
+                }
+                self.current += 1;
+                if self.settings.interval {
+                    return Err("Matrices are not available in interval mode".to_string());
+                }
+                matrix::literal(items)
+            }
+            Token::Ident(ident) => {
+                let name = ident.to_lowercase();
+                self.current += 1;
+
+                if name == "pi" {
+                    trace.add_step("pi".to_string(), self.number(PI));
+                    return Ok(self.number(PI));
+                }
+                if name == "e" {
+                    trace.add_step("e".to_string(), self.number(E));
+                    return Ok(self.number(E));
+                }
+
+                if name == "mod" && !self.next_is(&Token::LParen) {
+                    return self.modular_block(trace);
+                }
+                // A single die such as `d20`, where a name is not expected
+                if let Some(sides) = single_die(&name).filter(|_| !self.next_is(&Token::LParen)) {
+                    return self.roll(1, sides, trace);
+                }
+
+                if self.current >= self.tokens.len() || self.tokens[self.current] != Token::LParen {
+                    return Err(format!("Function '{}' requires parentheses", name));
+                }
+                self.current += 1;
+
+                if name == "diff" || name == "simplify" {
+                    return self.symbolic(&name, trace);
+                }
+                if name == "ode" || name == "rk4" {
+                    return self.ode(&name, trace);
+                }
+                if name == "minimize" || name == "maximize" {
+                    return self.optimize(&name, trace);
+                }
+                if name == "simulate" {
+                    return self.simulate(trace);
+                }
+                if name == "truthtable" || name == "sat" || name == "equiv" {
+                    return self.logic(&name, trace);
+                }
+
+                // Parse arguments
+                let mut args = Vec::new();
+                while self.current < self.tokens.len() && self.tokens[self.current] != Token::RParen {
+                    args.push(self.expr(trace)?);
+
+                    if self.current < self.tokens.len() {
+                        match self.tokens[self.current] {
+                            Token::Comma => {
+                                self.current += 1;
+                            }
+                            Token::RParen => break,
+                            _ => return Err("Expected comma or closing parenthesis".to_string()),
+                        }
+                    }
+                }
+
+                if self.current >= self.tokens.len() || self.tokens[self.current] != Token::RParen {
+                    return Err("Missing closing parenthesis for function".to_string());
+                }
+                self.current += 1;
+
+                let result = match name.as_str() {
+                    _ if self.settings.interval => Value::Interval(interval::evaluate(&name, &args)?),
+
+                    // Number theory works on exact integers
+                    "gcd" | "lcm" | "isprime" | "nextprime" | "factor" | "totient" | "phi" |
+                    "modpow" | "modinv" | "egcd" | "idiv" | "divmod" => number_theory::evaluate(&name, &args)?,
+
+                    // Dates and durations
+                    "today" | "now" | "date" | "weekday" | "dayname" | "year" | "month" | "day" | "tz" |
+                    "bizdays" | "addbizdays" | "days" | "hours" | "minutes" | "seconds" => datetime::evaluate(&name, &args)?,
+
+                    // Matrices and vectors
+                    "transpose" | "det" | "inv" | "rank" | "trace" | "solve" | "lu" | "qr" | "eig" |
+                    "eigenvalues" | "dot" | "cross" | "norm" | "identity" => matrix::evaluate(&name, &args)?,
+
+                    // Polynomials
+                    "polyroots" | "quadratic" | "cubic" | "polyval" | "polyfit" | "polyexpand" | "polymul" => {
+                        polynomial::evaluate(&name, &args)?
+                    }
+
+                    // Regression on data lists
+                    "linreg" | "expfit" | "logfit" | "powfit" | "corr" | "cov" | "predict" => {
+                        regression::evaluate(&name, &args)?
+                    }
+
+                    // Signal processing on lists
+                    "fft" | "ifft" | "magnitude" | "phase" | "convolve" | "movavg" | "hann" | "hamming"
+                    | "blackman" => signal::evaluate(&name, &args)?,
+
+                    // The inverse in the current modulus
+                    "inverse" => match (self.settings.modulus, args.as_slice()) {
+                        (Some(p), [x]) => modular::inverse(x, p)?,
+                        (Some(_), _) => return Err("inverse requires one argument".to_string()),
+                        (None, _) => {
+                            return Err("inverse works inside mod p { ... }; elsewhere use modinv(x, m)".to_string())
+                        }
+                    },
+
+                    // Molar masses from chemical formulas and the conversions built on them
+                    "molar" | "composition" | "moles" | "mass" | "molarity" | "solute" | "dilute" => {
+                        chemistry::evaluate(&name, &args)?
+                    }
+
+                    // Random draws, recorded like any other step so a trace shows what was drawn
+                    "rand" | "randint" | "randn" | "choice" => random::evaluate(&name, &args, &mut self.settings.random)?,
+
+                    // Finance
+                    "amort" => finance::evaluate(&name, &numeric_args(&args)?)?,
+
+                    // Remainders keep integers exact, so they work on values directly
+                    "emod" | "fmod" => {
+                        if args.len() != 2 {
+                            return Err(format!("{} requires two arguments: x and y", name));
+                        }
+                        let kind = if name == "emod" { ModuloKind::Euclidean } else { ModuloKind::Truncated };
+                        value::modulo(&args[0], &args[1], kind)?
+                    }
+                    "pmt" | "fv" | "pv" | "nper" | "rate" | "npv" | "irr" | "compound" => {
+                        propagate_uncertainty(&args, |a| finance::evaluate(&name, a)?.to_f64())?
+                    }
+
+                    _ => {
+                        // Statistics take their data as separate values or as lists, like regression
+                        let spread;
+                        let args = if STATISTICS.contains(&name.as_str()) {
+                            spread = spread_lists(&name, &args)?;
+                            &spread
+                        } else {
+                            &args
+                        };
+                        match propagate_uncertainty(args, |a| apply_function(&name, a))? {
+                            // Results beyond the range of f64 carry on with a wider exponent
+                            // Exact zeros stay put, except from exp, which only reaches 0 by underflowing
+                            Value::Number(x)
+                                if x.is_infinite()
+                                    || x.is_subnormal()
+                                    || (x == 0.0 && name == "exp")
+                                    || args.iter().any(|a| matches!(a, Value::Extended(_))) =>
+                            {
+                                let wide_input = args.iter().any(|a| matches!(a, Value::Extended(_)));
+                                match extended::evaluate(&name, args)? {
+                                    Some(result) => result,
+                                    // An infinity from a wide input is not an overflow of this function
+                                    None if x.is_nan() || (x.is_infinite() && wide_input) => {
+                                        return Err(format!(
+                                            "{} is not available beyond the range of f64 (about 1.8e308)",
+                                            name
+                                        ))
+                                    }
+                                    None => Value::Number(x),
+                                }
+                            }
+                            result => result,
+                        }
+                    }
+                };
+
+                let args_str = args.iter()
+                    .map(|a| self.show(a))
+                    .collect::<Vec<_>>()
+                    .join(self.settings.display.list_separator());
+                let operand_refs = args.iter().collect::<Vec<_>>();
+                self.record(trace, format!("{}({})", name, args_str), &operand_refs, result, name == "exp")
+            }
+            _ => Err("Unexpected token".to_string()),
+        }
+    }
+
+    /// `diff(f, x[, n])` and `simplify(f)`, whose first argument is read as an expression
+    /// in its variables instead of being evaluated.
+    fn symbolic(&mut self, name: &str, trace: &mut EvaluationTrace) -> Result<Value, String> {
+        let (expr, end) = symbolic::parse(&self.tokens, self.current)?;
+        self.current = end;
+
+        let mut args = vec![Value::Expression(expr.clone())];
+        let result = if name == "simplify" {
+            symbolic::simplify(&expr)?
+        } else {
+            let variable = match (self.tokens.get(self.current), self.tokens.get(self.current + 1)) {
+                (Some(Token::Comma), Some(Token::Ident(variable))) => variable.clone(),
+                _ => return Err("diff requires an expression and a variable, e.g. diff(x^2, x)".to_string()),
+            };
+            self.current += 2;
+            args.push(Value::Text(variable.clone()));
+            let order = if self.next_is(&Token::Comma) {
+                self.current += 1;
+                let order = self.expr(trace)?;
+                args.push(order.clone());
+                order.to_integer().ok_or("diff: the order must be a whole number")?
+            } else {
+                1
+            };
+            symbolic::derivative(&expr, &variable, order)?
+        };
+
+        if !self.next_is(&Token::RParen) {
+            return Err("Missing closing parenthesis for function".to_string());
+        }
+        self.current += 1;
+
+        let args_str = args.iter()
+            .map(|a| self.show(a))
+            .collect::<Vec<_>>()
+            .join(self.settings.display.list_separator());
+        self.record(trace, format!("{}({})", name, args_str), &[], Value::Expression(result), false)
+    }
+
+    /// `ode(f, y0, t0, t1[, samples])` and `rk4(f, y0, t0, t1[, steps])`, where `f` is an
+    /// expression in `t` and `y`, or a list such as `[y2, -y1]` for a system.
+    fn ode(&mut self, name: &str, trace: &mut EvaluationTrace) -> Result<Value, String> {
+        let (equations, end) = symbolic::parse_list(&self.tokens, self.current)?;
+        self.current = end;
+
+        let args = self.remaining_arguments(trace)?;
+        if self.settings.interval {
+            return Err(format!("{} is not available in interval mode", name));
+        }
+
+        let result = ode::evaluate(name, &equations, &args)?;
+        let format_number = |x: f64| self.show(&Value::Number(x));
+        let equations = match equations.as_slice() {
+            [single] => single.render(&format_number),
+            _ => format!("[{}]", equations.iter().map(|f| f.render(&format_number)).collect::<Vec<_>>().join(", ")),
+        };
+        let args_str = std::iter::once(equations)
+            .chain(args.iter().map(|a| self.show(a)))
+            .collect::<Vec<_>>()
+            .join(self.settings.display.list_separator());
+        let operands = args.iter().collect::<Vec<_>>();
+        self.record(trace, format!("{}({})", name, args_str), &operands, result, false)
+    }
+
+    /// `minimize(f, x, lo, hi)` over an interval, or `minimize(f, [x, y], [x0, y0])` from
+    /// a starting point; likewise `maximize`.
+    fn optimize(&mut self, name: &str, trace: &mut EvaluationTrace) -> Result<Value, String> {
+        let (expr, end) = symbolic::parse(&self.tokens, self.current)?;
+        self.current = end;
+        if !self.next_is(&Token::Comma) {
+            return Err(format!("{} requires an expression and its variables, e.g. {}(x^2 - 4x, x, -10, 10)", name, name));
+        }
+        self.current += 1;
+
+        // One variable, or a bracketed list of them
+        let bracketed = self.next_is(&Token::LBracket);
+        if bracketed {
+            self.current += 1;
+        }
+        let mut variables = Vec::new();
+        loop {
+            match self.tokens.get(self.current) {
+                Some(Token::Ident(variable)) if variable != "pi" && variable != "e" => variables.push(variable.clone()),
+                _ => return Err(format!("{}: expected a variable name such as x", name)),
+            }
+            self.current += 1;
+            if !bracketed || !self.next_is(&Token::Comma) {
+                break;
+            }
+            self.current += 1;
+        }
+        if bracketed {
+            if !self.next_is(&Token::RBracket) {
+                return Err("Expected comma or closing bracket".to_string());
+            }
+            self.current += 1;
+        }
+
+        let args = self.remaining_arguments(trace)?;
+        if self.settings.interval {
+            return Err(format!("{} is not available in interval mode", name));
+        }
+        let result = optimize::evaluate(name, &expr, &variables, &args)?;
+
+        let variables = if bracketed { format!("[{}]", variables.join(", ")) } else { variables.join(", ") };
+        let args_str = [expr.render(&|x| self.show(&Value::Number(x))), variables]
+            .into_iter()
+            .chain(args.iter().map(|a| self.show(a)))
+            .collect::<Vec<_>>()
+            .join(self.settings.display.list_separator());
+        let operands = args.iter().collect::<Vec<_>>();
+        self.record(trace, format!("{}({})", name, args_str), &operands, result, false)
+    }
+
+    /// `truthtable(f, ...)`, `sat(f)` and `equiv(f, g)`, whose arguments are Boolean
+    /// formulas such as `a and not b` rather than values.
+    fn logic(&mut self, name: &str, trace: &mut EvaluationTrace) -> Result<Value, String> {
+        let mut formulas = Vec::new();
+        loop {
+            let (formula, end) = logic::parse(&self.tokens, self.current)?;
+            formulas.push(formula);
+            self.current = end;
+            if !self.next_is(&Token::Comma) {
+                break;
+            }
+            self.current += 1;
+        }
+        if !self.next_is(&Token::RParen) {
+            return Err("Missing closing parenthesis for function".to_string());
+        }
+        self.current += 1;
+
+        let result = logic::evaluate(name, &formulas)?;
+        let args_str = formulas.iter()
+            .map(|f| f.to_string())
+            .collect::<Vec<_>>()
+            .join(self.settings.display.list_separator());
+        self.record(trace, format!("{}({})", name, args_str), &[], result, false)
+    }
+
+    /// Rolls `count` dice with `sides` sides each and records the individual rolls.
+    fn roll(&mut self, count: i128, sides: i128, trace: &mut EvaluationTrace) -> Result<Value, String> {
+        if self.settings.interval {
+            return Err("Random numbers are not available in interval mode".to_string());
+        }
+        let rolls = random::roll(count, sides, &mut self.settings.random)?;
+        let total = Value::Integer(rolls.iter().sum());
+        let dice = if count == 1 { format!("d{}", sides) } else { format!("{}d{}", count, sides) };
+        self.record(trace, format!("{}: {}", dice, random::describe_roll(&rolls)), &[], total, false)
+    }
+
+    /// `mod p { expr }`: evaluates `expr` with `+ - * / ^` in the integers mod p and
+    /// gives its residue. Blocks may nest; the modulus itself is an ordinary integer.
+    fn modular_block(&mut self, trace: &mut EvaluationTrace) -> Result<Value, String> {
+        let outer = self.settings.modulus.take();
+        let modulus = self.expr(trace);
+        self.settings.modulus = outer;
+        let p = modular::modulus(&modulus?)?;
+        if !self.next_is(&Token::LBrace) {
+            return Err("Expected '{' after the modulus, e.g. mod 7 { 3 * 5 }".to_string());
+        }
+        self.current += 1;
+        if self.settings.interval {
+            return Err("mod p { ... } is not available in interval mode".to_string());
+        }
+
+        self.settings.modulus = Some(p);
+        let body = self.expr(trace);
+        self.settings.modulus = outer;
+        let result = modular::residue(&body?, p)?;
+        if !self.next_is(&Token::RBrace) {
+            return Err("Missing closing '}' for mod block".to_string());
+        }
+        self.current += 1;
+        self.record(trace, format!("mod {} {{ … }}", p), &[], Value::Integer(result as i128), false)
+    }
+
+    /// `simulate(n, expr)`: evaluates `expr` n times, each with fresh random draws, and
+    /// gives the mean with its standard error. Detailed mode traces the first few trials.
+    fn simulate(&mut self, trace: &mut EvaluationTrace) -> Result<Value, String> {
+        let trials = match self.expr(trace)?.to_integer() {
+            Some(n) if (2..=MAX_TRIALS as i128).contains(&n) => n as usize,
+            _ => return Err(format!("simulate: the number of trials must be a whole number from 2 to {}", MAX_TRIALS)),
+        };
+        if !self.next_is(&Token::Comma) {
+            return Err("simulate requires a number of trials and an expression, e.g. simulate(10000, 2d6)".to_string());
+        }
+        self.current += 1;
+        if self.settings.interval {
+            return Err("simulate is not available in interval mode".to_string());
+        }
+
+        // Welford's running mean and sum of squared deviations
+        let start = self.current;
+        let (mut mean, mut squares) = (0.0, 0.0);
+        for i in 0..trials {
+            self.current = start;
+            let traced = trace.detailed_mode && i < TRACED_TRIALS;
+            let mut trial = EvaluationTrace::new(traced);
+            let x = self.expr(&mut trial)?.to_f64()?;
+            if !x.is_finite() {
+                return Err(format!("simulate: trial {} gave {}", i + 1, x));
+            }
+            if traced {
+                trace.steps.append(&mut trial.steps);
+                trace.add_step(format!("trial {}", i + 1), Value::Number(x));
+            }
+            let delta = x - mean;
+            mean += delta / (i + 1) as f64;
+            squares += delta * (x - mean);
+        }
+        if !self.next_is(&Token::RParen) {
+            return Err("Missing closing parenthesis for function".to_string());
+        }
+        self.current += 1;
+
+        let standard_error = (squares / (trials - 1) as f64 / trials as f64).sqrt();
+        let result = if standard_error == 0.0 { Value::Number(mean) } else { Value::Uncertain(mean, standard_error) };
+        self.record(trace, format!("simulate: mean of {} trials", trials), &[], result, false)
+    }
+
+    /// The evaluated arguments after a lazily read one, up to and including the closing
+    /// parenthesis.
+    fn remaining_arguments(&mut self, trace: &mut EvaluationTrace) -> Result<Vec<Value>, String> {
+        let mut args = Vec::new();
+        while self.next_is(&Token::Comma) {
+            self.current += 1;
+            args.push(self.expr(trace)?);
+        }
+        if !self.next_is(&Token::RParen) {
+            return Err("Missing closing parenthesis for function".to_string());
+        }
+        self.current += 1;
+        Ok(args)
+    }
+
+    /// Adds a trace step and notes the first result that overflowed, underflowed or
+    /// became NaN; in strict mode that result is an error naming the operation.
+    /// `may_underflow` marks operations where a zero from nonzero operands means the
+    /// true value was too small to represent.
+    fn record(
+        &self,
+        trace: &mut EvaluationTrace,
+        operation: String,
+        operands: &[&Value],
+        result: Value,
+        may_underflow: bool,
+    ) -> Result<Value, String> {
+        let issue = numeric_issue(operands, &result, may_underflow);
+        trace.add_step(operation.clone(), result.clone());
+
+        if let Some(issue) = issue {
+            let step = trace.detailed_mode.then(|| trace.steps.len() - 1);
+            let error = NumericError { issue, operation, step };
+            if self.settings.strict {
+                return Err(error.to_string());
+            }
+            trace.first_non_finite.get_or_insert(error);
+        }
+        Ok(result)
+    }
+
+    /// An operand as it appears in trace steps. Tiny operands are not snapped to zero,
+    /// so an underflowing step such as `1e-200 * 1e-200` reads correctly.
+    fn show(&self, value: &Value) -> String {
+        let display = DisplaySettings { zero_snap: None, ..self.settings.display.clone() };
+        display.format_value(value)
+    }
+
+    fn show_term(&self, term: &Term) -> String {
+        match &term.percent {
+            Some(percent) => format!("{}%", self.show(percent)),
+            None => self.show(&term.value),
+        }
+    }
+
+    fn next_is(&self, token: &Token) -> bool {
+        self.tokens.get(self.current) == Some(token)
+    }
+
+    fn next_is_keyword_at(&self, index: usize, keyword: &str) -> bool {
+        matches!(self.tokens.get(index), Some(Token::Ident(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    /// A floating-point literal or constant; in interval mode it becomes an enclosure.
+    fn number(&self, n: f64) -> Value {
+        if self.settings.interval {
+            Value::Interval(Interval::enclosing(n))
+        } else {
+            Value::Number(n)
+        }
+    }
+}
+
+/// Whether `result` went wrong even though its operands were well-behaved; a NaN or
+/// infinity that was passed in is not reported again.
+fn numeric_issue(operands: &[&Value], result: &Value, may_underflow: bool) -> Option<NumericIssue> {
+    let inputs = operands.iter().flat_map(|v| float_parts(v)).collect::<Vec<_>>();
+    let outputs = float_parts(result);
+
+    if outputs.iter().any(|x| x.is_nan()) && !inputs.iter().any(|x| x.is_nan()) {
+        Some(NumericIssue::NotANumber)
+    } else if outputs.iter().any(|x| x.is_infinite()) && inputs.iter().all(|x| x.is_finite()) {
+        Some(NumericIssue::Overflow)
+    } else if may_underflow
+        && *result == Value::Number(0.0)
+        && operands.iter().all(|v| v.to_f64().is_ok_and(|x| x != 0.0 && x.is_finite()))
+    {
+        Some(NumericIssue::Underflow)
+    } else {
+        None
+    }
+}
+
+/// The floating-point numbers held in a value; intervals carry their own bounds.
+fn float_parts(value: &Value) -> Vec<f64> {
+    match value {
+        Value::Number(x) | Value::Duration(x) => vec![*x],
+        Value::Uncertain(center, uncertainty) => vec![*center, *uncertainty],
+        Value::Tuple(items) => items.iter().flat_map(float_parts).collect(),
+        Value::Vector(entries) => entries.clone(),
+        Value::Matrix(m) => m.entries().to_vec(),
+        Value::Complex(z) => vec![z.re, z.im],
+        Value::ComplexVector(entries) => entries.iter().flat_map(|z| [z.re, z.im]).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn numeric_args(args: &[Value]) -> Result<Vec<f64>, String> {
+    args.iter().map(Value::to_f64).collect()
+}
+
+/// The statistics functions, whose data may be given as lists.
+const STATISTICS: &[&str] = &[
+    "mean", "median", "stdev", "stddev", "stdevs", "stdevp", "var", "vars", "varp", "mode", "min", "max",
+    "range", "percentile", "quartile", "iqr", "geomean", "harmean", "harmmean", "wmean", "skew", "skewness",
+    "kurt", "kurtosis", "zscore",
+];
+
+/// The arguments of a statistics function with each list spread into its entries, so
+/// that `mean([1, 2, 3])` is `mean(1, 2, 3)` and `percentile(90, [...])` works too.
+/// `wmean([x1, x2], [w1, w2])` pairs a list of values with a list of weights.
+fn spread_lists(name: &str, args: &[Value]) -> Result<Vec<Value>, String> {
+    if let ("wmean", [Value::Vector(values), Value::Vector(weights)]) = (name, args) {
+        if values.len() != weights.len() {
+            return Err(format!("wmean: {} values but {} weights", values.len(), weights.len()));
+        }
+        return Ok(values.iter().zip(weights).flat_map(|(&x, &w)| [Value::Number(x), Value::Number(w)]).collect());
+    }
+    Ok(args
+        .iter()
+        .flat_map(|arg| match arg {
+            Value::Vector(entries) => entries.iter().map(|&x| Value::Number(x)).collect(),
+            other => vec![other.clone()],
+        })
+        .collect())
+}
+
+/// Evaluates `f` at the central values and, when any argument is uncertain, propagates
+/// the uncertainties to first order using numerical partial derivatives.
+fn propagate_uncertainty(
+    args: &[Value],
+    f: impl Fn(&[f64]) -> Result<f64, String>,
+) -> Result<Value, String> {
+    if !args.iter().any(|arg| matches!(arg, Value::Uncertain(..))) {
+        return Ok(Value::Number(f(&numeric_args(args)?)?));
+    }
+
+    let (centers, uncertainties): (Vec<f64>, Vec<f64>) = args
+        .iter()
+        .map(Value::center_and_uncertainty)
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unzip();
+    let center = f(&centers)?;
+
+    let mut variance = 0.0;
+    for (i, &uncertainty) in uncertainties.iter().enumerate() {
+        if uncertainty == 0.0 {
+            continue;
+        }
+        let step = 1e-6 * centers[i].abs().max(uncertainty);
+        let mut shifted = centers.clone();
+        let mut evaluate_at = |x: f64| {
+            shifted[i] = x;
+            f(&shifted).ok().filter(|y| y.is_finite())
+        };
+        // Central difference, falling back to one side at the edge of the domain
+        let derivative = match (evaluate_at(centers[i] + step), evaluate_at(centers[i] - step)) {
+            (Some(above), Some(below)) => (above - below) / (2.0 * step),
+            (Some(above), None) => (above - center) / step,
+            (None, Some(below)) => (center - below) / step,
+            (None, None) => return Err("Cannot propagate uncertainty: function is not differentiable here".to_string()),
+        };
+        variance += (derivative * uncertainty).powi(2);
+    }
+
+    Ok(Value::Uncertain(center, variance.sqrt()))
+}
+
+/// Evaluates a built-in function over plain numeric arguments.
+fn apply_function(name: &str, args: &[f64]) -> Result<f64, String> {
+    let result = match name {
+        // Trigonometric
+        // A multiple of π is only as exact as its rounding, so what that leaves is noise
+        "sin" => value::clean_noise(args[0].sin(), args[0]),
+        "cos" => value::clean_noise(args[0].cos(), args[0]),
+        "tan" => value::clean_noise(args[0].tan(), args[0]),
+        "asin" => {
+            if args[0] < -1.0 || args[0] > 1.0 {
+                return Err("asin domain: [-1, 1]".to_string());
+            }
+            args[0].asin().to_degrees()
+        }
+        "acos" => {
+            if args[0] < -1.0 || args[0] > 1.0 {
+                return Err("acos domain: [-1, 1]".to_string());
+            }
+            args[0].acos().to_degrees()
+        }
+        "atan" => args[0].atan().to_degrees(),
+
+        // Exponential
+        "ln" => {
+            if args[0] <= 0.0 {
+                return Err("ln domain: positive numbers".to_string());
+            }
+            args[0].ln()
+        }
+        "log" => {
+            if args[0] <= 0.0 {
+                return Err("log domain: positive numbers".to_string());
+            }
+            args[0].log10()
+        }
+        "exp" => args[0].exp(),
+
+        // Basic
+        "abs" => args[0].abs(),
+        "floor" => args[0].floor(),
+        "ceil" => args[0].ceil(),
+        "round" => args[0].round(),
+        "sqrt" => {
+            if args[0] < 0.0 {
+                return Err("sqrt domain: non-negative numbers".to_string());
+            }
+            args[0].sqrt()
+        }
+
+        // Percentages
+        "pctchange" | "percentchange" => {
+            if args.len() != 2 {
+                return Err(format!("{} requires two arguments: old and new", name));
+            }
+            if args[0] == 0.0 {
+                return Err(format!("{}: the old value cannot be zero", name));
+            }
+            (args[1] - args[0]) / args[0].abs() * 100.0
+        }
+
+        // Hyperbolic
+        "sinh" => args[0].sinh(),
+        "cosh" => args[0].cosh(),
+        "tanh" => args[0].tanh(),
+        "asinh" => args[0].asinh(),
+        "acosh" => {
+            if args[0] < 1.0 {
+                return Err("acosh domain: x >= 1".to_string());
+            }
+            args[0].acosh()
+        }
+        "atanh" => {
+            if args[0] <= -1.0 || args[0] >= 1.0 {
+                return Err("atanh domain: |x| < 1".to_string());
+            }
+            args[0].atanh()
+        }
+
+        // Combinatorics
+        "fact" | "factorial" => {
+            if args[0] < 0.0 && args[0].fract() == 0.0 {
+                return Err("Factorial not defined for negative integers".to_string());
+            }
+            if args[0].fract() != 0.0 {
+                // Real arguments go through x! = Γ(x + 1)
+                return Ok(special::gamma(args[0] + 1.0));
+            }
+            let n = args[0] as u64;
+            let mut result = 1.0;
+            for i in 1..=n {
+                result *= i as f64;
+                if result == f64::INFINITY {
+                    break;
+                }
+            }
+            result
+        }
+        "perm" | "npr" => {
+            if args.len() != 2 {
+                return Err("perm requires two arguments: n and k".to_string());
+            }
+            if args[0] < 0.0 || args[1] < 0.0 {
+                return Err("perm requires non-negative integers".to_string());
+            }
+            if args[0].fract() != 0.0 || args[1].fract() != 0.0 {
+                return Err("perm requires integer arguments".to_string());
+            }
+            let n = args[0] as u64;
+            let k = args[1] as u64;
+            if k > n {
+                return Err("k cannot be greater than n in perm".to_string());
+            }
+            let mut result = 1.0;
+            for i in 0..k {
+                result *= (n - i) as f64;
+                if result == f64::INFINITY {
+                    break;
+                }
+            }
+            result
+        }
+        "comb" | "ncr" => {
+            if args.len() != 2 {
+                return Err("comb requires two arguments: n and k".to_string());
+            }
+            if args[0] < 0.0 || args[1] < 0.0 {
+                return Err("comb requires non-negative integers".to_string());
+            }
+            if args[0].fract() != 0.0 || args[1].fract() != 0.0 {
+                return Err("comb requires integer arguments".to_string());
+            }
+            let n = args[0] as u64;
+            let k = args[1] as u64;
+            if k > n {
+                return Err("k cannot be greater than n in comb".to_string());
+            }
+            let k = k.min(n - k);
+            let mut result = 1.0;
+            for i in 0..k {
+                result *= (n - i) as f64 / (i + 1) as f64;
+                if result == f64::INFINITY {
+                    break;
+                }
+            }
+            result
+        }
+
+        // Special functions
+        "gamma" => {
+            if args[0] <= 0.0 && args[0].fract() == 0.0 {
+                return Err("gamma domain: x not zero or a negative integer".to_string());
+            }
+            special::gamma(args[0])
+        }
+        "lgamma" => {
+            if args[0] <= 0.0 && args[0].fract() == 0.0 {
+                return Err("lgamma domain: x not zero or a negative integer".to_string());
+            }
+            special::ln_gamma(args[0])
+        }
+        "beta" => {
+            if args.len() != 2 {
+                return Err("beta requires two arguments: a and b".to_string());
+            }
+            if args[0] <= 0.0 || args[1] <= 0.0 {
+                return Err("beta domain: a > 0, b > 0".to_string());
+            }
+            special::beta(args[0], args[1])
+        }
+        "erf" => special::erf(args[0]),
+        "erfc" => special::erfc(args[0]),
+        "digamma" | "psi" => {
+            if args[0] <= 0.0 && args[0].fract() == 0.0 {
+                return Err("digamma domain: x not zero or a negative integer".to_string());
+            }
+            special::digamma(args[0])
+        }
+        "besselj" | "bessely" => {
+            if args.len() != 2 {
+                return Err(format!("{} requires two arguments: n and x", name));
+            }
+            if args[0].fract() != 0.0 || args[0].abs() > 1000.0 {
+                return Err(format!("{} domain: integer order |n| <= 1000", name));
+            }
+            if args[1].abs() > 1e6 {
+                return Err(format!("{} domain: |x| <= 1e6", name));
+            }
+            if name == "besselj" {
+                special::bessel_j(args[0] as i32, args[1])
+            } else {
+                if args[1] <= 0.0 {
+                    return Err("bessely domain: x > 0".to_string());
+                }
+                special::bessel_y(args[0] as i32, args[1])
+            }
+        }
+        "zeta" => {
+            if args[0] == 1.0 {
+                return Err("zeta domain: s != 1".to_string());
+            }
+            special::zeta(args[0])
+        }
+        "lambertw" => {
+            let branch = match args.len() {
+                1 => 0.0,
+                2 => args[1],
+                _ => return Err("lambertw requires x and an optional branch (0 or -1)".to_string()),
+            };
+            if args[0] < -(-1.0f64).exp() {
+                return Err("lambertw domain: x >= -1/e".to_string());
+            }
+            if branch == -1.0 {
+                if args[0] >= 0.0 {
+                    return Err("lambertw branch -1 domain: -1/e <= x < 0".to_string());
+                }
+                special::lambert_w(args[0], -1)
+            } else if branch == 0.0 {
+                special::lambert_w(args[0], 0)
+            } else {
+                return Err("lambertw branch must be 0 or -1".to_string());
+            }
+        }
+
+        // Statistical
+        "mean" => {
+            if args.is_empty() {
+                return Err("mean requires at least one argument".to_string());
+            }
+            mean(args)
+        }
+        "median" => {
+            if args.is_empty() {
+                return Err("median requires at least one argument".to_string());
+            }
+            let sorted = sorted_copy(args);
+            let mid = sorted.len() / 2;
+            if sorted.len().is_multiple_of(2) {
+                (sorted[mid - 1] + sorted[mid]) / 2.0
+            } else {
+                sorted[mid]
+            }
+        }
+        "stdev" | "stddev" | "stdevs" => {
+            if args.len() < 2 {
+                return Err("stdev requires at least two arguments".to_string());
+            }
+            variance(args, true).sqrt()
+        }
+        "stdevp" => {
+            if args.is_empty() {
+                return Err("stdevp requires at least one argument".to_string());
+            }
+            variance(args, false).sqrt()
+        }
+        "var" | "vars" => {
+            if args.len() < 2 {
+                return Err("var requires at least two arguments".to_string());
+            }
+            variance(args, true)
+        }
+        "varp" => {
+            if args.is_empty() {
+                return Err("varp requires at least one argument".to_string());
+            }
+            variance(args, false)
+        }
+        "mode" => {
+            if args.is_empty() {
+                return Err("mode requires at least one argument".to_string());
+            }
+            let sorted = sorted_copy(args);
+            let mut best = sorted[0];
+            let mut best_count = 0;
+            let mut i = 0;
+            while i < sorted.len() {
+                let mut j = i;
+                while j < sorted.len() && sorted[j] == sorted[i] {
+                    j += 1;
+                }
+                if j - i > best_count {
+                    best = sorted[i];
+                    best_count = j - i;
+                }
+                i = j;
+            }
+            best
+        }
+        "min" => {
+            if args.is_empty() {
+                return Err("min requires at least one argument".to_string());
+            }
+            args.iter().cloned().fold(f64::INFINITY, f64::min)
+        }
+        "max" => {
+            if args.is_empty() {
+                return Err("max requires at least one argument".to_string());
+            }
+            args.iter().cloned().fold(f64::NEG_INFINITY, f64::max)
+        }
+        "range" => {
+            if args.is_empty() {
+                return Err("range requires at least one argument".to_string());
+            }
+            let sorted = sorted_copy(args);
+            sorted[sorted.len() - 1] - sorted[0]
+        }
+        "percentile" => {
+            if args.len() < 2 {
+                return Err("percentile requires p and at least one value".to_string());
+            }
+            if args[0] < 0.0 || args[0] > 100.0 {
+                return Err("percentile domain: 0 <= p <= 100".to_string());
+            }
+            percentile(&sorted_copy(&args[1..]), args[0])
+        }
+        "quartile" => {
+            if args.len() < 2 {
+                return Err("quartile requires q and at least one value".to_string());
+            }
+            if args[0].fract() != 0.0 || args[0] < 0.0 || args[0] > 4.0 {
+                return Err("quartile domain: q in 0, 1, 2, 3, 4".to_string());
+            }
+            percentile(&sorted_copy(&args[1..]), args[0] * 25.0)
+        }
+        "iqr" => {
+            if args.is_empty() {
+                return Err("iqr requires at least one argument".to_string());
+            }
+            let sorted = sorted_copy(args);
+            percentile(&sorted, 75.0) - percentile(&sorted, 25.0)
+        }
+        "geomean" => {
+            if args.is_empty() {
+                return Err("geomean requires at least one argument".to_string());
+            }
+            if args.iter().any(|&x| x <= 0.0) {
+                return Err("geomean domain: positive numbers".to_string());
+            }
+            (args.iter().map(|x| x.ln()).sum::<f64>() / args.len() as f64).exp()
+        }
+        "harmean" | "harmmean" => {
+            if args.is_empty() {
+                return Err("harmean requires at least one argument".to_string());
+            }
+            if args.iter().any(|&x| x <= 0.0) {
+                return Err("harmean domain: positive numbers".to_string());
+            }
+            args.len() as f64 / args.iter().map(|x| 1.0 / x).sum::<f64>()
+        }
+        "wmean" => {
+            if args.is_empty() || !args.len().is_multiple_of(2) {
+                return Err("wmean requires value, weight pairs".to_string());
+            }
+            if args.iter().skip(1).step_by(2).any(|&w| w < 0.0) {
+                return Err("wmean weights must be non-negative".to_string());
+            }
+            let weight_sum = args.iter().skip(1).step_by(2).sum::<f64>();
+            if weight_sum == 0.0 {
+                return Err("wmean weights must not all be zero".to_string());
+            }
+            args.chunks(2).map(|pair| pair[0] * pair[1]).sum::<f64>() / weight_sum
+        }
+        "skew" | "skewness" => {
+            if args.len() < 3 {
+                return Err("skew requires at least three arguments".to_string());
+            }
+            let n = args.len() as f64;
+            let m = mean(args);
+            let s = variance(args, true).sqrt();
+            if s == 0.0 {
+                return Err("skew undefined for constant data".to_string());
+            }
+            let sum_cubes = args.iter().map(|x| ((x - m) / s).powi(3)).sum::<f64>();
+            n / ((n - 1.0) * (n - 2.0)) * sum_cubes
+        }
+        "kurt" | "kurtosis" => {
+            if args.len() < 4 {
+                return Err("kurt requires at least four arguments".to_string());
+            }
+            let n = args.len() as f64;
+            let m = mean(args);
+            let s = variance(args, true).sqrt();
+            if s == 0.0 {
+                return Err("kurt undefined for constant data".to_string());
+            }
+            let sum_fourths = args.iter().map(|x| ((x - m) / s).powi(4)).sum::<f64>();
+            n * (n + 1.0) / ((n - 1.0) * (n - 2.0) * (n - 3.0)) * sum_fourths
+                - 3.0 * (n - 1.0).powi(2) / ((n - 2.0) * (n - 3.0))
+        }
+        "zscore" => {
+            if args.len() < 3 {
+                return Err("zscore requires x and at least two values".to_string());
+            }
+            let s = variance(&args[1..], true).sqrt();
+            if s == 0.0 {
+                return Err("zscore undefined for constant data".to_string());
+            }
+            (args[0] - mean(&args[1..])) / s
+        }
+
+        // Probability distributions
+        "normpdf" | "normcdf" | "invnorm" |
+        "tpdf" | "tcdf" | "invt" |
+        "chi2pdf" | "chi2cdf" | "invchi2" |
+        "binompdf" | "binomcdf" | "invbinom" |
+        "poisspdf" | "poisscdf" | "invpoiss" |
+        "exppdf" | "expcdf" | "invexp" |
+        "unifpdf" | "unifcdf" | "invunif" => distributions::evaluate(name, args)?,
+
+        _ => return Err(format!("Unknown function: '{}'", name)),
+    };
+    Ok(result)
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Sample (n - 1) or population (n) variance.
+fn variance(values: &[f64], sample: bool) -> f64 {
+    let m = mean(values);
+    let sum_squares = values.iter().map(|x| (x - m).powi(2)).sum::<f64>();
+    let divisor = if sample { values.len() - 1 } else { values.len() };
+    sum_squares / divisor as f64
+}
+
+fn sorted_copy(values: &[f64]) -> Vec<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted
+}
+
+/// Linear interpolation between closest ranks; `p` is in percent.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(input: &str) -> Result<Value, String> {
+        let mut parser = Parser::new(tokenize(input)?, Settings::default());
+        parser.parse(&mut EvaluationTrace::new(false))
+    }
+
+    #[test]
+    fn plus_minus_is_spelled_with_a_slash() {
+        let number = |input: &str| eval(input).and_then(|v| v.to_f64());
+        assert_eq!(number("5+-3"), Ok(2.0));
+        assert_eq!(number("5 + -3"), Ok(2.0));
+        assert_eq!(number("10+-2*3"), Ok(4.0));
+        assert_eq!(number("2*5+-3"), Ok(7.0));
+        assert_eq!(eval("9.81+/-0.02"), Ok(Value::Uncertain(9.81, 0.02)));
+        assert_eq!(eval("9.81 +/- 0.02"), eval("9.81 ± 0.02"));
+    }
+
+    #[test]
+    fn strict_mode_reports_non_finite_results() {
+        let strict = |input: &str| {
+            let mut parser = Parser::new(tokenize(input)?, Settings { strict: true, ..Settings::default() });
+            parser.parse(&mut EvaluationTrace::new(false))
+        };
+        let overflow = strict("det([[1e200, 0], [0, 1e200]])").unwrap_err();
+        assert!(overflow.starts_with("Overflow in det("), "{}", overflow);
+        assert!(strict("[1e200] * 1e200").unwrap_err().starts_with("Overflow in "));
+        let nan = strict("dot([1e200, -1e200], [1e200, 1e200])").unwrap_err();
+        assert!(nan.starts_with("Invalid operation in dot("), "{}", nan);
+        assert_eq!(strict("1e200 * 1e200").map(|v| v.to_string()), eval("1e200 * 1e200").map(|v| v.to_string()));
+
+        // Without strict mode the value is kept and the first issue is noted
+        let mut trace = EvaluationTrace::new(false);
+        let mut parser = Parser::new(tokenize("[1e200] * 1e200 + [1]").unwrap(), Settings::default());
+        assert_eq!(parser.parse(&mut trace), Ok(Value::Vector(vec![f64::INFINITY])));
+        assert!(matches!(trace.first_non_finite, Some(NumericError { issue: NumericIssue::Overflow, .. })));
+
+        let tiny = Value::Number(1e-200);
+        assert!(matches!(numeric_issue(&[&tiny, &tiny], &Value::Number(0.0), true), Some(NumericIssue::Underflow)));
+        assert_eq!(numeric_issue(&[&tiny, &tiny], &Value::Number(0.0), false), None);
+        let zero = Value::Number(0.0);
+        assert_eq!(numeric_issue(&[&zero, &tiny], &Value::Number(0.0), true), None);
+    }
+
+    #[test]
+    fn wide_operands_avoid_false_overflow() {
+        let close = |input: &str, expected: f64| match eval(input) {
+            Ok(Value::Number(x)) => assert!((x / expected - 1.0).abs() < 1e-12, "{}: {}", input, x),
+            other => panic!("{}: {:?}", input, other),
+        };
+        close("10^400 r 2", 1e200);
+        close("(1e-400) r 2", 1e-200);
+        close("floor(-1e-400)", -1.0);
+        close("mean(1e308, 1e308)", 1e308);
+        for input in ["fact(1000) r 3", "floor(10^400)", "max(10^400, 1)", "mean(10^400, 1)", "median(1e400, 3e400, 2)"] {
+            assert!(matches!(eval(input), Ok(Value::Extended(_))), "{}", input);
+        }
+        assert_eq!(eval("max(10^400, 1)").unwrap().to_string(), "1.0000000e400");
+        assert_eq!(eval("mean(10^400, 1)").unwrap().to_string(), "5.0000000e399");
+        for input in ["10^400 ± 1", "stdev(1e400, 1)"] {
+            let error = eval(input).unwrap_err();
+            assert!(error.contains("range of f64"), "{}: {}", input, error);
+        }
+    }
+
+    #[test]
+    fn ode_systems_drop_solver_noise() {
+        for input in ["ode([y2, -y1], [1, 0], 0, pi)", "rk4([y2, -y1], [1, 0], 0, pi)"] {
+            let Ok(Value::Vector(y)) = eval(input) else {
+                panic!("{} failed", input);
+            };
+            assert!((y[0] + 1.0).abs() < 1e-9 && y[1] == 0.0, "{}: {:?}", input, y);
+        }
+        let Ok(Value::Number(y)) = eval("ode(-y, 1e-30, 0, 1)") else {
+            panic!("ode failed");
+        };
+        assert!((y / 1e-30 - (-1f64).exp()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn statistics_accept_lists() {
+        for (list, spread) in [
+            ("mean([1, 2, 3])", "mean(1, 2, 3)"),
+            ("stdev([2, 4, 4, 4, 5, 5, 7, 9])", "stdev(2, 4, 4, 4, 5, 5, 7, 9)"),
+            ("median([3, 1], 2)", "median(3, 1, 2)"),
+            ("percentile(90, [1, 2, 3, 4])", "percentile(90, 1, 2, 3, 4)"),
+            ("zscore(5, [1, 2, 3])", "zscore(5, 1, 2, 3)"),
+            ("wmean([1, 3], [1, 3])", "wmean(1, 1, 3, 3)"),
+        ] {
+            assert_eq!(eval(list), eval(spread), "{}", list);
+        }
+        assert!(eval("wmean([1, 2], [1])").is_err());
+        assert!(eval("mean([])").is_err());
+    }
+
+    #[test]
+    fn ode_solutions_and_limits() {
+        let value = |input: &str| eval(input).and_then(|v| v.to_f64());
+        assert!((value("ode(-y, 1, 0, 1)").unwrap() - (-1f64).exp()).abs() < 1e-9);
+        assert!((value("ode(-y, 1, 1, 0)").unwrap() - 1f64.exp()).abs() < 1e-9);
+        assert!((value("rk4(-y, 1, 0, 1, 10)").unwrap() - (-1f64).exp()).abs() < 1e-6);
+        let Ok(Value::Table(table)) = eval("ode(-y, 1, 0, 1, 3)") else {
+            panic!("ode table failed");
+        };
+        assert_eq!(table.rows.len(), 3);
+        assert!((table.rows[1][1].to_f64().unwrap() - (-0.5f64).exp()).abs() < 1e-9);
+
+        for input in [
+            "rk4(-y, 1, 0, 1, 1)",
+            "rk4(-y, 1, 0, 1, 1000001)",
+            "ode(-y, 1, 0, 1, 10001)",
+            "ode(-y, 1, 0, 1, 2.5)",
+            "ode([y2, -y1], [1], 0, 1)",
+            "ode(-y, 1, 0, 1e400)",
+        ] {
+            assert!(eval(input).is_err(), "{}", input);
+        }
+        let singular = eval("ode(y^2, 1, 0, 2)").unwrap_err();
+        assert!(singular.contains("singular"), "{}", singular);
+    }
+
+    #[test]
+    fn minimize_rejects_poles() {
+        for input in ["minimize(1/x, x, -1, 1)", "maximize(1/x, x, -1, 1)", "minimize(1/x, [x], [0])"] {
+            assert!(eval(input).is_err(), "{}", input);
+        }
+        let Ok(Value::Tuple(result)) = eval("minimize(x^2 - 4x, x, -10, 10)") else {
+            panic!("minimize failed");
+        };
+        assert!((result[0].to_f64().unwrap() - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn numbered_names_are_identifiers() {
+        assert_eq!(tokenize("d1 and r1"), Ok(vec![
+            Token::Ident("d1".to_string()),
+            Token::Ident("and".to_string()),
+            Token::Ident("r1".to_string()),
+        ]));
+        assert_eq!(tokenize("3d6"), Ok(vec![Token::Dice(3, 6)]));
+        for input in ["8 r 3", "8r3", "8 r3"] {
+            assert_eq!(eval(input).and_then(|v| v.to_f64()), Ok(2.0), "{}", input);
+        }
+        assert!(matches!(eval("d20"), Ok(Value::Integer(1..=20))));
+        let Ok(Value::Text(table)) = eval("sat(d1 and not r1)") else {
+            panic!("sat failed");
+        };
+        assert!(table.contains("d1 = 1, r1 = 0"), "{}", table);
+    }
+
+    #[test]
+    fn keywords_after_an_operand_stay_whole() {
+        assert_eq!(eval("7 rem 3"), Ok(Value::Integer(1)));
+        assert_eq!(eval("-7 rem 3"), Ok(Value::Integer(-1)));
+        assert_eq!(eval("(7) rem 3"), Ok(Value::Integer(1)));
+        assert_eq!(eval("7 mod 3"), Ok(Value::Integer(1)));
+        assert!(eval("50% of 8").is_ok());
+    }
+
+    #[test]
+    fn extrema_are_found_in_one_and_several_variables() {
+        let tuple = |input: &str| match eval(input) {
+            Ok(Value::Tuple(result)) => result,
+            other => panic!("{} gave {:?}", input, other),
+        };
+        let peak = tuple("maximize(sin(x), x, 0, 3)");
+        assert!((peak[0].to_f64().unwrap() - std::f64::consts::FRAC_PI_2).abs() < 1e-7);
+        assert!((peak[1].to_f64().unwrap() - 1.0).abs() < 1e-12);
+        // A minimum at an endpoint is included
+        assert_eq!(tuple("minimize(x^2, x, 2, 3)")[0].to_f64(), Ok(2.0));
+
+        for (input, expected) in [
+            ("minimize((1 - x)^2 + 100(y - x^2)^2, [x, y], [-1.2, 1])", [1.0, 1.0]),
+            ("maximize(-((x - 1)^2 + (y + 2)^2), [x, y], [0, 0])", [1.0, -2.0]),
+        ] {
+            let Value::Vector(point) = &tuple(input)[0] else {
+                panic!("{} gave no point", input);
+            };
+            assert!(point.iter().zip(expected).all(|(x, e)| (x - e).abs() < 1e-6), "{}: {:?}", input, point);
+        }
+
+        for input in ["minimize(ln(x), x, -1, 1)", "minimize(x^2 + y^2, [x, y], [1])", "minimize(x^2, x, 1)", "minimize(x^2, x, 3, 2)"] {
+            assert!(eval(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn minimize_rejects_repeated_variables_and_wide_intervals() {
+        for input in ["minimize(x^2, [x, x], [1, 1])", "minimize(x^2, x, -1e308, 1e308)", "minimize(x, [x], [1e300])"] {
+            assert!(eval(input).is_err(), "{}", input);
+        }
+        let Ok(Value::Tuple(result)) = eval("minimize(x^2, x, -1e100, 1e100)") else {
+            panic!("minimize failed");
+        };
+        assert!(result[0].to_f64().unwrap().abs() < 1e-6);
+    }
+
+    #[test]
+    fn percent_closes_a_modular_block() {
+        assert_eq!(eval("mod 7 { 2 % }"), Ok(Value::Integer(1)));
+        assert_eq!(eval("mod 7 { 3 + 2% }"), Ok(Value::Integer(6)));
+    }
+
+    #[test]
+    fn oversized_modular_operands_are_reported() {
+        let error = eval("mod 7 { 100000000000000000000000000000000000000000 }").unwrap_err();
+        assert!(error.contains("too large"), "{}", error);
+    }
+
+    #[test]
+    fn uncertain_values_format_at_any_scale() {
+        let shown = |input: &str| eval(input).map(|v| v.to_string());
+        assert_eq!(shown("9.81 ± 0.02"), Ok("9.810 ± 0.020".to_string()));
+        assert_eq!(shown("1 ± 1e-10"), Ok("1.00000000000 ± 0.00000000010".to_string()));
+        assert_eq!(shown("1e200 ± 1"), Ok("1e200 ± 1e0".to_string()));
+        assert_eq!(shown("0.003 ± 0.0000012"), Ok("(3.0000 ± 0.0012)e-3".to_string()));
+    }
+}
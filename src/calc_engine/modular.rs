@@ -0,0 +1,79 @@
+use super::number_theory::{gcd, mod_inverse, mod_pow, mul_mod};
+use super::value::Value;
+
+/// Checks a modulus written in `mod p { ... }` or `set modulus p`.
+pub fn modulus(value: &Value) -> Result<u64, String> {
+    match value.to_integer() {
+        Some(p) if p >= 2 => u64::try_from(p).map_err(|_| "The modulus must be below 2^64".to_string()),
+        _ => Err("The modulus must be a whole number of at least 2".to_string()),
+    }
+}
+
+/// An operand as a residue in 0..p. Operands are exact integers below 2^127; larger
+/// ones have already been rounded to floating point, so their residue is unknown.
+pub fn residue(value: &Value, p: u64) -> Result<u64, String> {
+    let n = value.to_integer().ok_or_else(|| match value {
+        Value::Extended(_) => too_large(value),
+        _ => match value.to_f64() {
+            Ok(x) if x.fract() == 0.0 && x.is_finite() => too_large(value),
+            _ => format!("Modular arithmetic needs whole numbers, found {}", value),
+        },
+    })?;
+    Ok(n.rem_euclid(p as i128) as u64)
+}
+
+fn too_large(value: &Value) -> String {
+    format!("{} is too large to reduce exactly: operands in modular arithmetic must be below 2^127 (about 1.7e38)", value)
+}
+
+/// `+ - * / ^` in Z/pZ. Division multiplies by the inverse, and a negative exponent
+/// raises the inverse; the exponent itself is an ordinary integer.
+pub fn binary(op: char, a: &Value, b: &Value, p: u64) -> Result<Value, String> {
+    let x = residue(a, p)?;
+    let result = match op {
+        '+' => ((x as u128 + residue(b, p)? as u128) % p as u128) as u64,
+        '-' => ((x as u128 + p as u128 - residue(b, p)? as u128) % p as u128) as u64,
+        '*' => mul_mod(x, residue(b, p)?, p),
+        '/' => mul_mod(x, inverse_of(residue(b, p)?, p)?, p),
+        '^' => {
+            let exponent = b.to_integer().ok_or("Modular powers need a whole-number exponent")?;
+            let base = if exponent < 0 { inverse_of(x, p)? } else { x };
+            mod_pow(base, exponent.unsigned_abs(), p)
+        }
+        _ => return Err(format!("'{}' is not available in modular arithmetic", op)),
+    };
+    Ok(Value::Integer(result as i128))
+}
+
+/// `inverse(x)`: the residue y with x·y ≡ 1 (mod p).
+pub fn inverse(value: &Value, p: u64) -> Result<Value, String> {
+    Ok(Value::Integer(inverse_of(residue(value, p)?, p)? as i128))
+}
+
+fn inverse_of(x: u64, p: u64) -> Result<u64, String> {
+    mod_inverse(x as i128, p).ok_or_else(|| match gcd(x as u128, p as u128) {
+        g if g == p as u128 => format!("Division by 0 mod {}", p),
+        g => format!("{} has no inverse mod {}: both are divisible by {}", x, p, g),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operands_beyond_i128_are_too_large_not_fractional() {
+        let error = residue(&Value::Number(1e41), 7).unwrap_err();
+        assert!(error.contains("too large"), "{}", error);
+        let error = residue(&Value::Number(1.5), 7).unwrap_err();
+        assert!(error.contains("whole numbers"), "{}", error);
+        assert_eq!(residue(&Value::Integer(i128::MAX), 7), Ok((i128::MAX % 7) as u64));
+    }
+
+    #[test]
+    fn division_uses_the_inverse() {
+        assert_eq!(binary('/', &Value::Integer(3), &Value::Integer(4), 7), Ok(Value::Integer(6)));
+        assert_eq!(binary('^', &Value::Integer(2), &Value::Integer(-1), 7), Ok(Value::Integer(4)));
+        assert!(binary('/', &Value::Integer(3), &Value::Integer(5), 10).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use super::value::Value;
+
+const SMALL_PRIMES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+pub fn evaluate(name: &str, args: &[Value]) -> Result<Value, String> {
+    match name {
+        "gcd" => {
+            if args.is_empty() {
+                return Err("gcd requires at least one argument".to_string());
+            }
+            let mut result = 0u128;
+            for arg in args {
+                result = gcd(result, integer_arg(name, arg)?.unsigned_abs());
+            }
+            to_value(name, result)
+        }
+        "lcm" => {
+            if args.is_empty() {
+                return Err("lcm requires at least one argument".to_string());
+            }
+            let mut result = 1u128;
+            for arg in args {
+                let n = integer_arg(name, arg)?.unsigned_abs();
+                if n == 0 {
+                    return Ok(Value::Integer(0));
+                }
+                result = (result / gcd(result, n))
+                    .checked_mul(n)
+                    .ok_or_else(|| "lcm result is too large".to_string())?;
+            }
+            to_value(name, result)
+        }
+        "isprime" => {
+            let n = single_arg(name, args)?;
+            Ok(Value::Integer(if n < 2 { 0 } else { is_prime(machine_arg(name, n)?) as i128 }))
+        }
+        "nextprime" => {
+            let n = single_arg(name, args)?;
+            if n < 2 {
+                return Ok(Value::Integer(2));
+            }
+            let mut candidate = machine_arg(name, n)?;
+            loop {
+                candidate = candidate
+                    .checked_add(1)
+                    .ok_or_else(|| "nextprime result is too large".to_string())?;
+                if is_prime(candidate) {
+                    return Ok(Value::Integer(candidate as i128));
+                }
+            }
+        }
+        "factor" => {
+            let n = single_arg(name, args)?;
+            if n == 0 {
+                return Err("factor domain: non-zero integers".to_string());
+            }
+            let factors = factorize(machine_arg(name, n.abs())?);
+            Ok(Value::Factors(n, factors))
+        }
+        "totient" | "phi" => {
+            let n = single_arg(name, args)?;
+            if n < 1 {
+                return Err("totient domain: positive integers".to_string());
+            }
+            let n = machine_arg(name, n)?;
+            let result = factorize(n)
+                .iter()
+                .fold(n, |acc, &(p, _)| acc / p * (p - 1));
+            Ok(Value::Integer(result as i128))
+        }
+        "modpow" => {
+            if args.len() != 3 {
+                return Err("modpow requires three arguments: base, exponent and modulus".to_string());
+            }
+            let modulus = modulus_arg(name, &args[2])?;
+            let mut base = integer_arg(name, &args[0])?.rem_euclid(modulus as i128) as u64;
+            let exponent = integer_arg(name, &args[1])?;
+            if exponent < 0 {
+                base = mod_inverse(base as i128, modulus)
+                    .ok_or_else(|| "modpow: base is not invertible for a negative exponent".to_string())?;
+            }
+            Ok(Value::Integer(mod_pow(base, exponent.unsigned_abs(), modulus) as i128))
+        }
+        "modinv" => {
+            if args.len() != 2 {
+                return Err("modinv requires two arguments: a and modulus".to_string());
+            }
+            let modulus = modulus_arg(name, &args[1])?;
+            let a = integer_arg(name, &args[0])?;
+            mod_inverse(a, modulus)
+                .map(|inverse| Value::Integer(inverse as i128))
+                .ok_or_else(|| format!("modinv: {} is not invertible modulo {}", a, modulus))
+        }
+        "egcd" => {
+            if args.len() != 2 {
+                return Err("egcd requires two arguments: a and b".to_string());
+            }
+            let (g, x, y) = extended_gcd(integer_arg(name, &args[0])?, integer_arg(name, &args[1])?)
+                .ok_or_else(|| "egcd arguments are too large".to_string())?;
+            Ok(Value::Tuple(vec![Value::Integer(g), Value::Integer(x), Value::Integer(y)]))
+        }
+        "idiv" | "divmod" => {
+            if args.len() != 2 {
+                return Err(format!("{} requires two arguments: a and b", name));
+            }
+            let a = integer_arg(name, &args[0])?;
+            let b = integer_arg(name, &args[1])?;
+            if b == 0 {
+                return Err("Division by zero".to_string());
+            }
+            let (quotient, remainder) = floor_div_mod(a, b)
+                .ok_or_else(|| format!("{} result is too large", name))?;
+            if name == "idiv" {
+                Ok(Value::Integer(quotient))
+            } else {
+                Ok(Value::Tuple(vec![Value::Integer(quotient), Value::Integer(remainder)]))
+            }
+        }
+        _ => Err(format!("Unknown function: '{}'", name)),
+    }
+}
+
+fn integer_arg(name: &str, arg: &Value) -> Result<i128, String> {
+    arg.to_integer()
+        .ok_or_else(|| format!("{} requires integer arguments", name))
+}
+
+fn single_arg(name: &str, args: &[Value]) -> Result<i128, String> {
+    if args.len() != 1 {
+        return Err(format!("{} requires one argument", name));
+    }
+    integer_arg(name, &args[0])
+}
+
+/// Primality and factorisation run on 64-bit integers with 128-bit intermediates.
+fn machine_arg(name: &str, n: i128) -> Result<u64, String> {
+    u64::try_from(n).map_err(|_| format!("{} supports integers below 2^64", name))
+}
+
+fn modulus_arg(name: &str, arg: &Value) -> Result<u64, String> {
+    let m = integer_arg(name, arg)?;
+    if m < 1 {
+        return Err(format!("{} domain: modulus >= 1", name));
+    }
+    machine_arg(name, m)
+}
+
+fn to_value(name: &str, n: u128) -> Result<Value, String> {
+    i128::try_from(n)
+        .map(Value::Integer)
+        .map_err(|_| format!("{} result is too large", name))
+}
+
+pub fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Returns (g, x, y) with a*x + b*y = g = gcd(a, b), or None on overflow.
+pub fn extended_gcd(a: i128, b: i128) -> Option<(i128, i128, i128)> {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    let (mut old_t, mut t) = (0i128, 1i128);
+    while r != 0 {
+        let q = old_r.checked_div(r)?;
+        (old_r, r) = (r, old_r.checked_sub(q.checked_mul(r)?)?);
+        (old_s, s) = (s, old_s.checked_sub(q.checked_mul(s)?)?);
+        (old_t, t) = (t, old_t.checked_sub(q.checked_mul(t)?)?);
+    }
+    if old_r < 0 {
+        Some((old_r.checked_neg()?, old_s.checked_neg()?, old_t.checked_neg()?))
+    } else {
+        Some((old_r, old_s, old_t))
+    }
+}
+
+/// Floored division: the remainder takes the sign of the divisor.
+pub fn floor_div_mod(a: i128, b: i128) -> Option<(i128, i128)> {
+    let mut quotient = a.checked_div(b)?;
+    let mut remainder = a % b;
+    if remainder != 0 && (remainder < 0) != (b < 0) {
+        quotient -= 1;
+        remainder += b;
+    }
+    Some((quotient, remainder))
+}
+
+pub fn mod_inverse(a: i128, modulus: u64) -> Option<u64> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(modulus as i128), modulus as i128)?;
+    if g != 1 {
+        return None;
+    }
+    Some(x.rem_euclid(modulus as i128) as u64)
+}
+
+pub fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    (a as u128 * b as u128 % modulus as u128) as u64
+}
+
+pub fn mod_pow(mut base: u64, mut exponent: u128, modulus: u64) -> u64 {
+    if modulus == 1 {
+        return 0;
+    }
+    let mut result = 1;
+    base %= modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Deterministic Miller-Rabin for all 64-bit integers.
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for &p in &SMALL_PRIMES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let mut d = n - 1;
+    let mut s = 0;
+    while d.is_multiple_of(2) {
+        d /= 2;
+        s += 1;
+    }
+    'witness: for &a in &SMALL_PRIMES {
+        let mut x = mod_pow(a, d as u128, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Prime factorisation in ascending order of primes.
+pub fn factorize(n: u64) -> Vec<(u64, u32)> {
+    let mut primes = Vec::new();
+    collect_prime_factors(n, &mut primes);
+    primes.sort_unstable();
+
+    let mut factors: Vec<(u64, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((last, exponent)) if *last == p => *exponent += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    factors
+}
+
+fn collect_prime_factors(mut n: u64, primes: &mut Vec<u64>) {
+    for p in 2..1000u64 {
+        if p * p > n {
+            break;
+        }
+        while n.is_multiple_of(p) {
+            primes.push(p);
+            n /= p;
+        }
+    }
+    if n == 1 {
+        return;
+    }
+    if is_prime(n) {
+        primes.push(n);
+        return;
+    }
+    let divisor = pollard_rho(n);
+    collect_prime_factors(divisor, primes);
+    collect_prime_factors(n / divisor, primes);
+}
+
+/// Finds a non-trivial divisor of a composite `n` (Pollard's rho, Floyd cycle detection).
+fn pollard_rho(n: u64) -> u64 {
+    for c in 1.. {
+        let f = |x: u64| ((mul_mod(x, x, n) as u128 + c as u128) % n as u128) as u64;
+        let (mut x, mut y, mut d) = (2u64, 2u64, 1u64);
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            d = gcd(x.abs_diff(y) as u128, n as u128) as u64;
+        }
+        if d != n {
+            return d;
+        }
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn integers(values: &[i128]) -> Vec<Value> {
+        values.iter().map(|&n| Value::Integer(n)).collect()
+    }
+
+    #[test]
+    fn primality_holds_for_carmichael_numbers_and_large_primes() {
+        for carmichael in [561, 1105, 1729, 2465, 2821, 6601, 8911, 3_215_031_751, 3_825_123_056_546_413_051] {
+            assert!(!is_prime(carmichael), "{}", carmichael);
+        }
+        for prime in [2, 3, 37, 41, 1_000_000_007, 18_446_744_073_709_551_557] {
+            assert!(is_prime(prime), "{}", prime);
+        }
+        assert!(!is_prime(0) && !is_prime(1) && !is_prime(1_000_000_007 * 998_244_353));
+        assert_eq!(evaluate("nextprime", &integers(&[1_000_000_000])), Ok(Value::Integer(1_000_000_007)));
+    }
+
+    #[test]
+    fn factorisations_are_complete() {
+        assert_eq!(factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factorize(1_000_000_007 * 998_244_353), vec![(998_244_353, 1), (1_000_000_007, 1)]);
+        assert_eq!(evaluate("factor", &integers(&[-12])), Ok(Value::Factors(-12, vec![(2, 2), (3, 1)])));
+        assert_eq!(evaluate("totient", &integers(&[36])), Ok(Value::Integer(12)));
+        assert!(evaluate("factor", &integers(&[0])).is_err());
+    }
+
+    #[test]
+    fn gcd_and_modular_arithmetic() {
+        assert_eq!(evaluate("gcd", &integers(&[12, -18, 30])), Ok(Value::Integer(6)));
+        assert_eq!(evaluate("lcm", &integers(&[4, 6, 10])), Ok(Value::Integer(60)));
+        assert_eq!(evaluate("egcd", &integers(&[240, 46])), Ok(Value::Tuple(integers(&[2, -9, 47]))));
+        assert_eq!(evaluate("modpow", &integers(&[3, 200, 1_000_000_007])), Ok(Value::Integer(136_318_165)));
+        assert_eq!(evaluate("modpow", &integers(&[3, -1, 7])), Ok(Value::Integer(5)));
+        assert_eq!(evaluate("modinv", &integers(&[3, 11])), Ok(Value::Integer(4)));
+        assert!(evaluate("modinv", &integers(&[6, 9])).is_err());
+        assert_eq!(evaluate("divmod", &integers(&[-7, 2])), Ok(Value::Tuple(integers(&[-4, 1]))));
+        assert_eq!(evaluate("idiv", &integers(&[7, 0])), Err("Division by zero".to_string()));
+    }
+}
@@ -0,0 +1,216 @@
+use super::symbolic::Expr;
+use super::value::{self, Table, Value};
+
+/// Steps either solver takes before giving up.
+const MAX_STEPS: usize = 1_000_000;
+
+/// Error allowed in each adaptive step, relative to the solution and absolute.
+const RELATIVE_TOLERANCE: f64 = 1e-10;
+const ABSOLUTE_TOLERANCE: f64 = 1e-12;
+
+/// Fixed steps taken by `rk4` when no count is given.
+const DEFAULT_RK4_STEPS: i128 = 1000;
+
+/// Most rows in a table of samples.
+const MAX_SAMPLES: i128 = 10_000;
+
+/// Dormand–Prince 5(4) tableau: stage times, stage weights, the fifth-order solution
+/// and the difference between the fifth- and fourth-order solutions.
+const C: [f64; 7] = [0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+const A: [&[f64]; 7] = [
+    &[],
+    &[1.0 / 5.0],
+    &[3.0 / 40.0, 9.0 / 40.0],
+    &[44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0],
+    &[19372.0 / 6561.0, -25360.0 / 2187.0, 64448.0 / 6561.0, -212.0 / 729.0],
+    &[9017.0 / 3168.0, -355.0 / 33.0, 46732.0 / 5247.0, 49.0 / 176.0, -5103.0 / 18656.0],
+    &[35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0],
+];
+const B: [f64; 7] = [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0, 0.0];
+const ERROR: [f64; 7] = [
+    71.0 / 57600.0,
+    0.0,
+    -71.0 / 16695.0,
+    71.0 / 1920.0,
+    -17253.0 / 339200.0,
+    22.0 / 525.0,
+    -1.0 / 40.0,
+];
+
+/// The right-hand side of y' = f(t, y). A single equation is written in `t` and `y`;
+/// a system of n equations in `t` and `y1` to `yn`.
+struct System<'a> {
+    equations: &'a [Expr],
+}
+
+impl System<'_> {
+    fn derivative(&self, t: f64, y: &[f64]) -> Result<Vec<f64>, String> {
+        let variable = |name: &str| match name {
+            "t" => Some(t),
+            "y" if y.len() == 1 => Some(y[0]),
+            _ => {
+                let index = name.strip_prefix('y')?.parse::<usize>().ok()?;
+                (1..=y.len()).contains(&index).then(|| y[index - 1])
+            }
+        };
+        let slope = self.equations
+            .iter()
+            .map(|f| f.evaluate(&variable))
+            .collect::<Result<Vec<_>, _>>()?;
+        if slope.iter().any(|s| !s.is_finite()) {
+            return Err(format!("ode: the derivative is not finite at t = {}", t));
+        }
+        Ok(slope)
+    }
+}
+
+/// `ode(f, y0, t0, t1[, samples])` by adaptive Runge–Kutta 4(5), and
+/// `rk4(f, y0, t0, t1[, steps])` by the classic fixed-step method. The result is the
+/// solution at `t1`, or a table of `samples` evenly spaced values from `t0` to `t1`.
+pub fn evaluate(name: &str, equations: &[Expr], args: &[Value]) -> Result<Value, String> {
+    if !(3..=4).contains(&args.len()) {
+        return Err(format!("{} requires f, y0, t0 and t1, e.g. {}(-2y, 1, 0, 1)", name, name));
+    }
+    let y0 = match &args[0] {
+        Value::Vector(v) => v.clone(),
+        other => vec![other.to_f64()?],
+    };
+    if y0.len() != equations.len() {
+        return Err(format!("{}: {} equations but {} initial values", name, equations.len(), y0.len()));
+    }
+    let (t0, t1) = (args[1].to_f64()?, args[2].to_f64()?);
+    if !t0.is_finite() || !t1.is_finite() {
+        return Err(format!("{}: t0 and t1 must be finite", name));
+    }
+    let system = System { equations };
+    let count = |default: i128, max: i128, what: &str| match args.get(3) {
+        None => Ok(default),
+        Some(value) => match value.to_integer() {
+            Some(n) if (2..=max).contains(&n) => Ok(n),
+            _ => Err(format!("{}: the number of {} must be a whole number from 2 to {}", name, what, max)),
+        },
+    };
+
+    match name {
+        "rk4" => {
+            let steps = count(DEFAULT_RK4_STEPS, MAX_STEPS as i128, "steps")?;
+            Ok(into_value(rk4(&system, t0, y0, t1, steps as usize)?))
+        }
+        _ if args.len() == 3 => {
+            let mut step = initial_step(t0, t1);
+            Ok(into_value(rk45(&system, t0, y0, t1, &mut step)?))
+        }
+        _ => {
+            let samples = count(0, MAX_SAMPLES, "samples")?;
+            sample_table(&system, t0, y0, t1, samples as usize).map(Value::Table)
+        }
+    }
+}
+
+fn into_value(y: Vec<f64>) -> Value {
+    match y.as_slice() {
+        [single] => Value::Number(*single),
+        _ => Value::Vector(clean(&y)),
+    }
+}
+
+/// The components of a solution, with those below the solver's accuracy relative to
+/// the largest set to zero, e.g. sin t at t = π.
+fn clean(y: &[f64]) -> Vec<f64> {
+    let largest = y.iter().fold(0.0, |m: f64, x| m.max(x.abs()));
+    y.iter().map(|&x| value::clean_relative(x, largest, RELATIVE_TOLERANCE)).collect()
+}
+
+fn initial_step(t0: f64, t1: f64) -> f64 {
+    (t1 - t0) / 100.0
+}
+
+/// The solution at evenly spaced times, one row per sample.
+fn sample_table(system: &System, t0: f64, y0: Vec<f64>, t1: f64, samples: usize) -> Result<Table, String> {
+    let headers = match y0.len() {
+        1 => vec!["t".to_string(), "y".to_string()],
+        n => std::iter::once("t".to_string()).chain((1..=n).map(|i| format!("y{}", i))).collect(),
+    };
+    let row = |t: f64, y: &[f64]| std::iter::once(t).chain(clean(y)).map(Value::Number).collect();
+
+    let mut rows = vec![row(t0, &y0)];
+    let (mut t, mut y) = (t0, y0);
+    let mut step = initial_step(t0, t1) / samples as f64;
+    for i in 1..samples {
+        let next = t0 + (t1 - t0) * i as f64 / (samples - 1) as f64;
+        y = rk45(system, t, y, next, &mut step)?;
+        t = next;
+        rows.push(row(t, &y));
+    }
+    Ok(Table {
+        title: format!("Solution of y' = f(t, y) at {} times", samples),
+        headers,
+        rows,
+    })
+}
+
+/// Dormand–Prince steps from `t` to `t_end`. `step` carries the step size between calls
+/// so sampling does not restart the step-size search at every sample.
+fn rk45(system: &System, mut t: f64, mut y: Vec<f64>, t_end: f64, step: &mut f64) -> Result<Vec<f64>, String> {
+    if t == t_end {
+        return Ok(y);
+    }
+    let direction = (t_end - t).signum();
+    if *step == 0.0 || step.signum() != direction {
+        *step = initial_step(t, t_end);
+    }
+
+    for _ in 0..MAX_STEPS {
+        let h = if (t + *step - t_end) * direction > 0.0 { t_end - t } else { *step };
+
+        let mut k: Vec<Vec<f64>> = Vec::with_capacity(7);
+        for stage in 0..7 {
+            let point = combine(&y, h, &k, A[stage]);
+            k.push(system.derivative(t + C[stage] * h, &point)?);
+        }
+        let next = combine(&y, h, &k, &B);
+        let error = combine(&vec![0.0; y.len()], h, &k, &ERROR);
+
+        // Largest error relative to what each component allows; at most 1 is accepted
+        let ratio = error
+            .iter()
+            .zip(y.iter().zip(&next))
+            .map(|(e, (a, b))| e.abs() / (ABSOLUTE_TOLERANCE + RELATIVE_TOLERANCE * a.abs().max(b.abs())))
+            .fold(0.0, f64::max);
+
+        if ratio <= 1.0 {
+            t = if h == t_end - t { t_end } else { t + h };
+            y = next;
+            if t == t_end {
+                return Ok(y);
+            }
+        }
+        let factor = if ratio == 0.0 { 5.0 } else { (0.9 * ratio.powf(-0.2)).clamp(0.2, 5.0) };
+        *step = h * factor;
+        if step.abs() <= 1e-14 * t.abs().max(1.0) {
+            return Err(format!("ode: the step size became too small near t = {}; the solution may be singular", t));
+        }
+    }
+    Err(format!("ode: gave up after {} steps near t = {}; the problem may be stiff", MAX_STEPS, t))
+}
+
+/// Classic fourth-order Runge–Kutta with `steps` equal steps.
+fn rk4(system: &System, mut t: f64, mut y: Vec<f64>, t_end: f64, steps: usize) -> Result<Vec<f64>, String> {
+    let h = (t_end - t) / steps as f64;
+    for _ in 0..steps {
+        let k1 = system.derivative(t, &y)?;
+        let k2 = system.derivative(t + h / 2.0, &combine(&y, h, std::slice::from_ref(&k1), &[0.5]))?;
+        let k3 = system.derivative(t + h / 2.0, &combine(&y, h, std::slice::from_ref(&k2), &[0.5]))?;
+        let k4 = system.derivative(t + h, &combine(&y, h, std::slice::from_ref(&k3), &[1.0]))?;
+        y = combine(&y, h, &[k1, k2, k3, k4], &[1.0 / 6.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 6.0]);
+        t += h;
+    }
+    Ok(y)
+}
+
+/// `y + h * Σ weights[i] * k[i]`.
+fn combine(y: &[f64], h: f64, k: &[Vec<f64>], weights: &[f64]) -> Vec<f64> {
+    (0..y.len())
+        .map(|j| y[j] + h * k.iter().zip(weights).map(|(ki, w)| w * ki[j]).sum::<f64>())
+        .collect()
+}
@@ -0,0 +1,274 @@
+use super::symbolic::Expr;
+use super::value::Value;
+
+/// (3 - √5) / 2, the golden-section fraction.
+const GOLDEN: f64 = 0.381_966_011_250_105_1;
+
+/// Relative accuracy of the one-variable search; about √ε, the best a minimum allows.
+const BRENT_TOLERANCE: f64 = 3e-8;
+const BRENT_ITERATIONS: usize = 500;
+
+/// Convergence limits for Nelder–Mead on the spread of the simplex and of its values.
+const SIMPLEX_TOLERANCE: f64 = 1e-10;
+const VALUE_TOLERANCE: f64 = 1e-14;
+const SIMPLEX_ITERATIONS: usize = 20_000;
+
+/// A result is checked by stepping this fraction of a coordinate to either side: at a
+/// minimum the objective barely moves, while beside a pole such as 1/x at 0 it jumps by
+/// more than `JUMP_TOLERANCE` of the values seen.
+const PROBE_STEP: f64 = 1e-6;
+const JUMP_TOLERANCE: f64 = 1e-3;
+
+/// The function being minimized; `maximize` minimizes its negation.
+struct Objective<'a> {
+    name: &'a str,
+    expr: &'a Expr,
+    variables: &'a [String],
+    sign: f64,
+}
+
+impl Objective<'_> {
+    /// The objective at `point`. Domain errors from the functions it calls pass through,
+    /// and a result that is not finite is an error as well.
+    fn at(&self, point: &[f64]) -> Result<f64, String> {
+        if point.iter().any(|x| !x.is_finite()) {
+            return Err(format!("{}: the search left the range of f64, so the objective may be unbounded", self.name));
+        }
+        let value = self.expr.evaluate(&|name| {
+            self.variables.iter().position(|v| v == name).map(|i| point[i])
+        })?;
+        if !value.is_finite() {
+            return Err(format!("{}: the objective is not finite at {}", self.name, self.location(point)));
+        }
+        Ok(self.sign * value)
+    }
+
+    /// Rejects a result that sits beside a pole or a jump rather than at a minimum.
+    /// `scale` is the largest magnitude of the objective seen; probes outside `bounds`
+    /// or where the objective is undefined are skipped.
+    fn check_continuous(&self, point: &[f64], value: f64, scale: f64, bounds: Option<(f64, f64)>) -> Result<(), String> {
+        for i in 0..point.len() {
+            let step = PROBE_STEP * point[i].abs().max(1.0);
+            for side in [-step, step] {
+                let mut probe = point.to_vec();
+                probe[i] += side;
+                if bounds.is_some_and(|(lo, hi)| !(lo..=hi).contains(&probe[i])) {
+                    continue;
+                }
+                let Ok(nearby) = self.at(&probe) else { continue };
+                if (nearby - value).abs() > JUMP_TOLERANCE * scale {
+                    return Err(format!(
+                        "{}: the objective is unbounded or jumps near {}, so there is no minimum to find there",
+                        self.name,
+                        self.location(point)
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn location(&self, point: &[f64]) -> String {
+        self.variables
+            .iter()
+            .zip(point)
+            .map(|(v, x)| format!("{} = {}", v, short(*x)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// A coordinate for an error message: plain when moderate, scientific otherwise.
+fn short(x: f64) -> String {
+    if x == 0.0 || (1e-4..1e9).contains(&x.abs()) {
+        format!("{}", (x * 1e9).round() / 1e9)
+    } else {
+        format!("{:.6e}", x)
+    }
+}
+
+/// `minimize(f, x, lo, hi)` and `maximize(...)` search [lo, hi] by Brent's method;
+/// `minimize(f, [x, y, ...], [x0, y0, ...])` runs Nelder–Mead from a starting point.
+/// Both return the location and the value there.
+pub fn evaluate(name: &str, expr: &Expr, variables: &[String], args: &[Value]) -> Result<Value, String> {
+    if let Some((i, variable)) = variables.iter().enumerate().find(|(i, v)| variables[..*i].contains(v)) {
+        return Err(format!("{}: the variable {} is listed twice (as variable {})", name, variable, i + 1));
+    }
+    let objective = Objective { name, expr, variables, sign: if name == "maximize" { -1.0 } else { 1.0 } };
+    match (variables, args) {
+        ([_], [lo, hi]) => {
+            let (lo, hi) = (lo.to_f64()?, hi.to_f64()?);
+            // The width must be finite too, or the first golden-section point is infinite
+            if !(hi - lo).is_finite() || lo >= hi {
+                return Err(format!("{}: the interval must be finite with lo < hi and hi - lo below 1.8e308", name));
+            }
+            let (x, value) = brent(&objective, lo, hi)?;
+            let scale = [value, objective.at(&[lo])?, objective.at(&[hi])?].iter().fold(1.0f64, |m, v| m.max(v.abs()));
+            objective.check_continuous(&[x], value, scale, Some((lo, hi)))?;
+            Ok(Value::Tuple(vec![Value::Number(x), Value::Number(objective.sign * value)]))
+        }
+        (_, [Value::Vector(start)]) if start.len() == variables.len() => {
+            if start.iter().any(|x| !x.is_finite()) {
+                return Err(format!("{}: the starting point must be finite", name));
+            }
+            let (point, value) = nelder_mead(&objective, start)?;
+            let scale = value.abs().max(objective.at(start)?.abs()).max(1.0);
+            objective.check_continuous(&point, value, scale, None)?;
+            let location = match point.as_slice() {
+                [x] => Value::Number(*x),
+                _ => Value::Vector(point),
+            };
+            Ok(Value::Tuple(vec![location, Value::Number(objective.sign * value)]))
+        }
+        ([_], _) => Err(format!("{} requires f, x, lo and hi, e.g. {}(x^2 - 4x, x, -10, 10)", name, name)),
+        _ => Err(format!(
+            "{} with {} variables requires a starting point with {} coordinates, e.g. [0, 0]",
+            name,
+            variables.len(),
+            variables.len()
+        )),
+    }
+}
+
+/// Brent's method: parabolic interpolation through the three best points, falling back
+/// to golden-section steps when the parabola is not trustworthy. The endpoints are
+/// checked too, since a minimum over the interval may lie on its boundary.
+fn brent(objective: &Objective, lo: f64, hi: f64) -> Result<(f64, f64), String> {
+    let f = |x: f64| objective.at(&[x]);
+    let (mut a, mut b) = (lo, hi);
+    let mut x = a + GOLDEN * (b - a);
+    let (mut w, mut v) = (x, x);
+    let mut fx = f(x)?;
+    let (mut fw, mut fv) = (fx, fx);
+    let (mut d, mut e) = (0.0f64, 0.0f64);
+
+    for _ in 0..BRENT_ITERATIONS {
+        let middle = a + (b - a) / 2.0;
+        let tol1 = BRENT_TOLERANCE * x.abs() + 1e-12;
+        let tol2 = 2.0 * tol1;
+        if (x - middle).abs() <= tol2 - (b - a) / 2.0 {
+            break;
+        }
+
+        let mut golden = true;
+        if e.abs() > tol1 {
+            // Fit a parabola through x, w and v
+            let r = (x - w) * (fx - fv);
+            let mut q = (x - v) * (fx - fw);
+            let mut p = (x - v) * q - (x - w) * r;
+            q = 2.0 * (q - r);
+            if q > 0.0 {
+                p = -p;
+            }
+            q = q.abs();
+            if p.abs() < (0.5 * q * e).abs() && p > q * (a - x) && p < q * (b - x) {
+                e = d;
+                d = p / q;
+                let u = x + d;
+                if u - a < tol2 || b - u < tol2 {
+                    d = tol1.copysign(middle - x);
+                }
+                golden = false;
+            }
+        }
+        if golden {
+            e = if x >= middle { a - x } else { b - x };
+            d = GOLDEN * e;
+        }
+
+        let u = if d.abs() >= tol1 { x + d } else { x + tol1.copysign(d) };
+        let fu = f(u)?;
+        if fu <= fx {
+            if u >= x { a = x } else { b = x }
+            (v, fv) = (w, fw);
+            (w, fw) = (x, fx);
+            (x, fx) = (u, fu);
+        } else {
+            if u < x { a = u } else { b = u }
+            if fu <= fw || w == x {
+                (v, fv) = (w, fw);
+                (w, fw) = (u, fu);
+            } else if fu <= fv || v == x || v == w {
+                (v, fv) = (u, fu);
+            }
+        }
+    }
+
+    let mut best = (x, fx);
+    for end in [lo, hi] {
+        let value = f(end)?;
+        if value < best.1 {
+            best = (end, value);
+        }
+    }
+    Ok(best)
+}
+
+/// The Nelder–Mead downhill simplex, starting from a simplex around `start`.
+fn nelder_mead(objective: &Objective, start: &[f64]) -> Result<(Vec<f64>, f64), String> {
+    let n = start.len();
+    let mut simplex: Vec<(Vec<f64>, f64)> = vec![(start.to_vec(), objective.at(start)?)];
+    for i in 0..n {
+        let mut vertex = start.to_vec();
+        // Steps of 5%, or a small absolute step for coordinates at zero
+        vertex[i] = if vertex[i] == 0.0 { 0.00025 } else { vertex[i] * 1.05 };
+        let value = objective.at(&vertex)?;
+        simplex.push((vertex, value));
+    }
+
+    for _ in 0..SIMPLEX_ITERATIONS {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (best, worst) = (&simplex[0], &simplex[n]);
+        let size = simplex[1..]
+            .iter()
+            .flat_map(|(vertex, _)| vertex.iter().zip(&best.0).map(|(a, b)| (a - b).abs()))
+            .fold(0.0, f64::max);
+        let scale = best.0.iter().fold(1.0f64, |m, x| m.max(x.abs()));
+        if size <= SIMPLEX_TOLERANCE * scale && (worst.1 - best.1).abs() <= VALUE_TOLERANCE * best.1.abs().max(1.0) {
+            let (point, value) = simplex.swap_remove(0);
+            return Ok((point, value));
+        }
+
+        let centroid: Vec<f64> = (0..n)
+            .map(|j| simplex[..n].iter().map(|(vertex, _)| vertex[j]).sum::<f64>() / n as f64)
+            .collect();
+        // centroid + t * (worst - centroid)
+        let towards = |t: f64| -> Vec<f64> {
+            centroid.iter().zip(&simplex[n].0).map(|(c, w)| c + t * (w - c)).collect()
+        };
+
+        let reflected = towards(-1.0);
+        let f_reflected = objective.at(&reflected)?;
+        if f_reflected < simplex[0].1 {
+            let expanded = towards(-2.0);
+            let f_expanded = objective.at(&expanded)?;
+            simplex[n] = if f_expanded < f_reflected { (expanded, f_expanded) } else { (reflected, f_reflected) };
+            continue;
+        }
+        if f_reflected < simplex[n - 1].1 {
+            simplex[n] = (reflected, f_reflected);
+            continue;
+        }
+        let (contracted, f_contracted) = if f_reflected < simplex[n].1 {
+            let outside = towards(-0.5);
+            let value = objective.at(&outside)?;
+            (outside, value)
+        } else {
+            let inside = towards(0.5);
+            let value = objective.at(&inside)?;
+            (inside, value)
+        };
+        if f_contracted < simplex[n].1.min(f_reflected) {
+            simplex[n] = (contracted, f_contracted);
+            continue;
+        }
+
+        // Shrink every vertex halfway towards the best
+        let best = simplex[0].0.clone();
+        for (vertex, value) in simplex.iter_mut().skip(1) {
+            *vertex = vertex.iter().zip(&best).map(|(x, b)| b + 0.5 * (x - b)).collect();
+            *value = objective.at(vertex)?;
+        }
+    }
+    Err(format!("{}: did not converge after {} iterations", objective.name, SIMPLEX_ITERATIONS))
+}
@@ -0,0 +1,426 @@
+use super::complex::Complex;
+use super::matrix::Matrix;
+use super::value::Value;
+use std::f64::consts::PI;
+
+/// Iterations of the Aberth method before giving up.
+const MAX_ITERATIONS: usize = 500;
+
+/// A root of multiplicity m is only found to about ε^(1/m); members of a cluster lie
+/// within this many times that distance of each other.
+const CLUSTER_SLACK: f64 = 10.0;
+
+/// Imaginary parts this small (relative to the root) are rounding noise.
+const REAL_TOLERANCE: f64 = 1e-10;
+
+/// Polynomial functions. Coefficients run from the highest power down, so
+/// `[1, -3, 2]` is x² - 3x + 2.
+pub fn evaluate(name: &str, args: &[Value]) -> Result<Value, String> {
+    match name {
+        "polyroots" => {
+            let coefficients = coefficient_args(name, args)?;
+            Ok(Value::Tuple(roots(&coefficients)?.into_iter().map(Complex::into_value).collect()))
+        }
+        "quadratic" => {
+            if args.len() != 3 {
+                return Err("quadratic requires three arguments: a, b and c of ax² + bx + c".to_string());
+            }
+            let [a, b, c] = [args[0].to_f64()?, args[1].to_f64()?, args[2].to_f64()?];
+            if a == 0.0 {
+                return Err("quadratic: a cannot be zero".to_string());
+            }
+            Ok(Value::Tuple(quadratic(a, b, c).into_iter().map(Complex::into_value).collect()))
+        }
+        "cubic" => {
+            if args.len() != 4 {
+                return Err("cubic requires four arguments: a, b, c and d of ax³ + bx² + cx + d".to_string());
+            }
+            let [a, b, c, d] = [args[0].to_f64()?, args[1].to_f64()?, args[2].to_f64()?, args[3].to_f64()?];
+            if a == 0.0 {
+                return Err("cubic: a cannot be zero".to_string());
+            }
+            Ok(Value::Tuple(cubic(a, b, c, d).into_iter().map(Complex::into_value).collect()))
+        }
+        "polyval" => {
+            if args.len() != 2 {
+                return Err("polyval requires two arguments: coefficients and x, e.g. polyval([1, -3, 2], 5)".to_string());
+            }
+            let coefficients = vector_arg(name, &args[0])?;
+            match &args[1] {
+                Value::Vector(xs) => Ok(Value::Vector(xs.iter().map(|&x| horner(coefficients, x)).collect())),
+                Value::Complex(z) => Ok(horner_complex(coefficients, *z).into_value()),
+                x => Ok(Value::Number(horner(coefficients, x.to_f64()?))),
+            }
+        }
+        "polyfit" => {
+            if args.len() != 3 {
+                return Err("polyfit requires three arguments: xs, ys and the degree".to_string());
+            }
+            let (xs, ys) = (vector_arg(name, &args[0])?, vector_arg(name, &args[1])?);
+            let degree = match args[2].to_integer() {
+                Some(d) if (0..=20).contains(&d) => d as usize,
+                _ => return Err("polyfit: the degree must be a whole number from 0 to 20".to_string()),
+            };
+            Ok(Value::Vector(fit(xs, ys, degree)?))
+        }
+        "polyexpand" => {
+            // The coefficients of (x - r1)(x - r2)...
+            let roots = coefficient_args(name, args)?;
+            let product = roots.iter().fold(vec![1.0], |p, &r| multiply(&p, &[1.0, -r]));
+            Ok(Value::Vector(product))
+        }
+        "polymul" => {
+            if args.len() != 2 {
+                return Err("polymul requires two coefficient vectors".to_string());
+            }
+            Ok(Value::Vector(multiply(vector_arg(name, &args[0])?, vector_arg(name, &args[1])?)))
+        }
+        _ => Err(format!("Unknown polynomial function: {}", name)),
+    }
+}
+
+/// Coefficients given either as one vector or as separate arguments.
+fn coefficient_args(name: &str, args: &[Value]) -> Result<Vec<f64>, String> {
+    match args {
+        [Value::Vector(v)] => Ok(v.clone()),
+        [] => Err(format!("{} requires coefficients, e.g. {}(1, -3, 2)", name, name)),
+        _ => args.iter().map(Value::to_f64).collect(),
+    }
+}
+
+fn vector_arg<'a>(name: &str, value: &'a Value) -> Result<&'a [f64], String> {
+    match value {
+        Value::Vector(v) => Ok(v),
+        _ => Err(format!("{} expects a vector such as [1, -3, 2]", name)),
+    }
+}
+
+fn horner(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().fold(0.0, |acc, &c| acc.mul_add(x, c))
+}
+
+fn horner_complex(coefficients: &[f64], z: Complex) -> Complex {
+    coefficients.iter().fold(Complex::real(0.0), |acc, &c| acc.mul(z).add(Complex::real(c)))
+}
+
+fn multiply(p: &[f64], q: &[f64]) -> Vec<f64> {
+    let mut product = vec![0.0; p.len() + q.len() - 1];
+    for (i, a) in p.iter().enumerate() {
+        for (j, b) in q.iter().enumerate() {
+            product[i + j] += a * b;
+        }
+    }
+    product
+}
+
+/// All roots, real ones first in ascending order, then complex pairs.
+fn roots(coefficients: &[f64]) -> Result<Vec<Complex>, String> {
+    let leading = coefficients.iter().position(|&c| c != 0.0);
+    let coefficients = match leading {
+        Some(i) => &coefficients[i..],
+        None => return Err("polyroots: every coefficient is zero".to_string()),
+    };
+    if coefficients.len() < 2 {
+        return Err("polyroots needs a polynomial of degree 1 or more".to_string());
+    }
+
+    // Trailing zero coefficients are exact roots at 0
+    let zeros = coefficients.iter().rev().take_while(|&&c| c == 0.0).count();
+    let reduced = &coefficients[..coefficients.len() - zeros];
+    let mut roots = vec![Complex::real(0.0); zeros];
+    roots.extend(match reduced.len() {
+        1 => Vec::new(),
+        2 => vec![Complex::real(-reduced[1] / reduced[0])],
+        3 => quadratic(reduced[0], reduced[1], reduced[2]).to_vec(),
+        _ => aberth(reduced)?,
+    });
+
+    sort(&mut roots);
+    Ok(roots)
+}
+
+/// Real roots first in increasing order, then complex roots by real and imaginary part.
+fn sort(roots: &mut [Complex]) {
+    roots.sort_by(|a, b| {
+        (a.im != 0.0).cmp(&(b.im != 0.0))
+            .then(a.re.total_cmp(&b.re))
+            .then(a.im.total_cmp(&b.im))
+    });
+}
+
+/// Simultaneous root finding by the Aberth–Ehrlich method, then merging of roots that
+/// converged to the same multiple root.
+fn aberth(coefficients: &[f64]) -> Result<Vec<Complex>, String> {
+    let n = coefficients.len() - 1;
+    let monic: Vec<f64> = coefficients.iter().map(|c| c / coefficients[0]).collect();
+    let derivative: Vec<f64> = monic[..n].iter().enumerate().map(|(i, c)| c * (n - i) as f64).collect();
+
+    // Start on a circle that encloses every root, off the real axis
+    let radius = monic[1..]
+        .iter()
+        .enumerate()
+        .map(|(k, c)| c.abs().powf(1.0 / (k + 1) as f64))
+        .fold(0.0, f64::max)
+        * 2.0;
+    let radius = if radius > 0.0 { radius } else { 1.0 };
+    let mut z: Vec<Complex> = (0..n)
+        .map(|k| Complex::from_polar(radius, 2.0 * PI * k as f64 / n as f64 + 0.4))
+        .collect();
+
+    let mut converged = false;
+    for _ in 0..MAX_ITERATIONS {
+        let mut largest_step: f64 = 0.0;
+        for k in 0..n {
+            let value = horner_complex(&monic, z[k]);
+            if value.abs() == 0.0 {
+                continue;
+            }
+            let ratio = value.div(horner_complex(&derivative, z[k]));
+            let repulsion = (0..n)
+                .filter(|&j| j != k)
+                .fold(Complex::real(0.0), |acc, j| acc.add(Complex::real(1.0).div(z[k].sub(z[j]))));
+            let step = ratio.div(Complex::real(1.0).sub(ratio.mul(repulsion)));
+            if step.re.is_finite() && step.im.is_finite() {
+                z[k] = z[k].sub(step);
+                largest_step = largest_step.max(step.abs() / z[k].abs().max(1.0));
+            }
+        }
+        if largest_step <= 4.0 * f64::EPSILON {
+            converged = true;
+            break;
+        }
+    }
+    if !converged && z.iter().any(|root| horner_complex(&monic, *root).abs() > 1e-6) {
+        return Err("polyroots: the roots did not converge".to_string());
+    }
+
+    Ok(clean(merge_clusters(&monic, z)))
+}
+
+/// A multiple root converges as a small cluster. Its mean is refined by Newton's method
+/// on the (m-1)th derivative, where a root of multiplicity m is a simple root.
+fn merge_clusters(coefficients: &[f64], mut roots: Vec<Complex>) -> Vec<Complex> {
+    let n = roots.len();
+    let mut merged = vec![false; n];
+    for i in 0..n {
+        if merged[i] {
+            continue;
+        }
+        let scale = roots[i].abs().max(1.0);
+        let mut nearest: Vec<(f64, usize)> = (0..n)
+            .filter(|&j| !merged[j])
+            .map(|j| (roots[j].sub(roots[i]).abs(), j))
+            .collect();
+        nearest.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        // The largest multiplicity m whose m nearest roots fit the expected spread
+        let multiplicity = (2..=nearest.len())
+            .filter(|&m| nearest[m - 1].0 <= CLUSTER_SLACK * f64::EPSILON.powf(1.0 / m as f64) * scale)
+            .max()
+            .unwrap_or(1);
+        if multiplicity > 1 {
+            let members = &nearest[..multiplicity];
+            let spread = CLUSTER_SLACK * f64::EPSILON.powf(1.0 / multiplicity as f64) * scale;
+            let mut root = members
+                .iter()
+                .fold(Complex::real(0.0), |acc, &(_, j)| acc.add(roots[j]))
+                .scale(1.0 / multiplicity as f64);
+            // A conjugate pair cannot hide inside the cluster, so an imaginary part within
+            // its expected spread is noise, even when every member leans the same way
+            if root.im.abs() <= spread {
+                root = Complex::real(root.re);
+            }
+            let derivative = differentiate(coefficients, multiplicity - 1);
+            let slope = differentiate(&derivative, 1);
+            for _ in 0..3 {
+                let step = horner_complex(&derivative, root).div(horner_complex(&slope, root));
+                if !step.re.is_finite() || !step.im.is_finite() || step.abs() > spread {
+                    break;
+                }
+                root = root.sub(step);
+            }
+            for &(_, j) in members {
+                roots[j] = root;
+                merged[j] = true;
+            }
+        }
+    }
+    roots
+}
+
+/// The coefficients of the `order`th derivative.
+fn differentiate(coefficients: &[f64], order: usize) -> Vec<f64> {
+    let degree = coefficients.len() - 1;
+    coefficients[..=degree - order]
+        .iter()
+        .enumerate()
+        .map(|(i, c)| c * ((degree - i - order + 1)..=(degree - i)).product::<usize>() as f64)
+        .collect()
+}
+
+/// Drops imaginary parts that are only rounding noise, and makes each complex root
+/// the exact conjugate of its partner, as real coefficients require.
+fn clean(roots: Vec<Complex>) -> Vec<Complex> {
+    let mut roots: Vec<Complex> = roots
+        .into_iter()
+        .map(|z| if z.im.abs() <= REAL_TOLERANCE * z.abs().max(1.0) { Complex::real(z.re) } else { z })
+        .collect();
+    let mut paired = vec![false; roots.len()];
+    for i in 0..roots.len() {
+        if roots[i].im <= 0.0 || paired[i] {
+            continue;
+        }
+        let conjugate = Complex::new(roots[i].re, -roots[i].im);
+        let partner = (0..roots.len())
+            .filter(|&j| roots[j].im < 0.0 && !paired[j])
+            .min_by(|&a, &b| roots[a].sub(conjugate).abs().total_cmp(&roots[b].sub(conjugate).abs()));
+        if let Some(j) = partner {
+            let average = roots[i].add(Complex::new(roots[j].re, -roots[j].im)).scale(0.5);
+            roots[i] = average;
+            roots[j] = Complex::new(average.re, -average.im);
+            paired[j] = true;
+        }
+    }
+    roots
+}
+
+/// Both roots of ax² + bx + c, avoiding cancellation in the textbook formula.
+fn quadratic(a: f64, b: f64, c: f64) -> [Complex; 2] {
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        let re = -b / (2.0 * a);
+        let im = ((-discriminant).sqrt() / (2.0 * a)).abs();
+        return [Complex::new(re, -im), Complex::new(re, im)];
+    }
+    let q = -(b + discriminant.sqrt().copysign(b)) / 2.0;
+    let (x1, x2) = if q == 0.0 { (0.0, 0.0) } else { (q / a, c / q) };
+    [Complex::real(x1.min(x2)), Complex::real(x1.max(x2))]
+}
+
+/// All three roots of ax³ + bx² + cx + d by Cardano's method, or the trigonometric
+/// form when all three are real. A double root splits by about √ε in either form, so
+/// clusters are merged as in `polyroots`.
+fn cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<Complex> {
+    let (b, c, d) = (b / a, c / a, d / a);
+    let mut roots = clean(merge_clusters(&[1.0, b, c, d], separate_cubic_roots(b, c, d).to_vec()));
+    sort(&mut roots);
+    roots
+}
+
+fn separate_cubic_roots(b: f64, c: f64, d: f64) -> [Complex; 3] {
+    // Depressed cubic t³ + pt + q with x = t - b/3
+    let shift = b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let discriminant = (q / 2.0).powi(2) + (p / 3.0).powi(3);
+
+    if discriminant > 0.0 {
+        let root = discriminant.sqrt();
+        let u = (-q / 2.0 + root).cbrt();
+        let v = (-q / 2.0 - root).cbrt();
+        let real = u + v - shift;
+        let re = -(u + v) / 2.0 - shift;
+        let im = (3f64.sqrt() / 2.0 * (u - v)).abs();
+        return [Complex::real(polish(real, b, c, d)), Complex::new(re, -im), Complex::new(re, im)];
+    }
+    if p == 0.0 {
+        return [Complex::real(-shift); 3];
+    }
+
+    let m = 2.0 * (-p / 3.0).sqrt();
+    let angle = ((3.0 * q / (p * m)).clamp(-1.0, 1.0)).acos() / 3.0;
+    [0, 1, 2].map(|k| Complex::real(polish(m * (angle - 2.0 * PI * k as f64 / 3.0).cos() - shift, b, c, d)))
+}
+
+/// One Newton step on the monic cubic, which removes most of the rounding error.
+fn polish(x: f64, b: f64, c: f64, d: f64) -> f64 {
+    let value = ((x + b) * x + c) * x + d;
+    let slope = (3.0 * x + 2.0 * b) * x + c;
+    if slope == 0.0 { x } else { x - value / slope }
+}
+
+/// Least-squares polynomial of the given degree through the points.
+fn fit(xs: &[f64], ys: &[f64], degree: usize) -> Result<Vec<f64>, String> {
+    if xs.len() != ys.len() {
+        return Err(format!("polyfit: {} x values but {} y values", xs.len(), ys.len()));
+    }
+    if xs.len() <= degree {
+        return Err(format!("polyfit: a degree {} fit needs at least {} points", degree, degree + 1));
+    }
+    let rows: Vec<Vec<f64>> = xs.iter().map(|&x| (0..=degree).rev().map(|k| x.powi(k as i32)).collect()).collect();
+    let vandermonde = Matrix::from_rows(&rows)?;
+    let solution = vandermonde
+        .solve(&Matrix::column(ys))
+        .map_err(|_| "polyfit: the x values must include at least degree + 1 distinct points".to_string())?;
+    Ok(solution.entries().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(values: &[f64]) -> Vec<Value> {
+        values.iter().map(|&x| Value::Number(x)).collect()
+    }
+
+    /// The roots as (re, im) pairs, checked against `expected` to within `tolerance`.
+    fn assert_roots(result: Result<Value, String>, expected: &[(f64, f64)], tolerance: f64) {
+        let Ok(Value::Tuple(roots)) = &result else {
+            panic!("expected roots, got {:?}", result);
+        };
+        let roots: Vec<(f64, f64)> = roots
+            .iter()
+            .map(|root| match root {
+                Value::Complex(z) => (z.re, z.im),
+                other => (other.to_f64().unwrap(), 0.0),
+            })
+            .collect();
+        assert_eq!(roots.len(), expected.len(), "{:?}", roots);
+        for (root, want) in roots.iter().zip(expected) {
+            assert!((root.0 - want.0).abs() < tolerance && (root.1 - want.1).abs() < tolerance, "{:?} is not {:?}", roots, expected);
+        }
+    }
+
+    #[test]
+    fn repeated_roots_stay_real() {
+        assert_roots(evaluate("cubic", &numbers(&[1.0, -3.0, 3.0, -1.0])), &[(1.0, 0.0); 3], 1e-12);
+        assert_roots(evaluate("cubic", &numbers(&[1.0, -4.0, 5.0, -2.0])), &[(1.0, 0.0), (1.0, 0.0), (2.0, 0.0)], 1e-12);
+        assert_roots(evaluate("polyroots", &numbers(&[1.0, -3.0, 3.0, -1.0])), &[(1.0, 0.0); 3], 1e-9);
+        assert_roots(evaluate("polyroots", &numbers(&[1.0, -6.0, 12.0, -8.0])), &[(2.0, 0.0); 3], 1e-9);
+    }
+
+    #[test]
+    fn complex_roots_come_in_pairs() {
+        assert_roots(evaluate("quadratic", &numbers(&[1.0, 2.0, 5.0])), &[(-1.0, -2.0), (-1.0, 2.0)], 1e-12);
+        assert_roots(
+            evaluate("polyroots", &numbers(&[1.0, 0.0, 0.0, 0.0, -1.0])),
+            &[(-1.0, 0.0), (1.0, 0.0), (0.0, -1.0), (0.0, 1.0)],
+            1e-12,
+        );
+    }
+
+    #[test]
+    fn coefficient_arithmetic() {
+        assert_eq!(evaluate("polyval", &[Value::Vector(vec![1.0, 2.0, 3.0]), Value::Number(2.0)]), Ok(Value::Number(11.0)));
+        assert_eq!(evaluate("polyexpand", &numbers(&[1.0, 2.0])), Ok(Value::Vector(vec![1.0, -3.0, 2.0])));
+        assert_eq!(
+            evaluate("polymul", &[Value::Vector(vec![1.0, 1.0]), Value::Vector(vec![1.0, -1.0])]),
+            Ok(Value::Vector(vec![1.0, 0.0, -1.0]))
+        );
+        let Ok(Value::Vector(fitted)) = evaluate(
+            "polyfit",
+            &[Value::Vector(vec![0.0, 1.0, 2.0]), Value::Vector(vec![1.0, 3.0, 7.0]), Value::Integer(2)],
+        ) else {
+            panic!("polyfit failed");
+        };
+        assert!(fitted.iter().all(|c| (c - 1.0).abs() < 1e-12), "{:?}", fitted);
+    }
+
+    #[test]
+    fn degenerate_polynomials_are_rejected() {
+        assert!(evaluate("quadratic", &numbers(&[0.0, 2.0, 5.0])).is_err());
+        assert!(evaluate("cubic", &numbers(&[0.0, 1.0, 2.0, 3.0])).is_err());
+        assert!(evaluate("polyroots", &numbers(&[0.0, 0.0])).is_err());
+        assert!(evaluate("polyfit", &[Value::Vector(vec![0.0, 1.0]), Value::Vector(vec![1.0, 3.0]), Value::Integer(21)]).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use super::value::Value;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// Most dice in a single roll such as `3d6`.
+const MAX_DICE: i128 = 10_000;
+
+/// Rolls listed individually in a trace step; longer rolls are cut short.
+const SHOWN_ROLLS: usize = 20;
+
+/// The session's random number generator, xoshiro256**. It starts from an unpredictable
+/// state; `seed N` restarts it from N so a sequence of draws can be repeated.
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: [u64; 4],
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        // Hasher keys are randomised per process, which is entropy enough for a calculator
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos()));
+        Random::seeded(hasher.finish())
+    }
+}
+
+impl Random {
+    pub fn seeded(seed: u64) -> Self {
+        // SplitMix64 spreads the seed over the whole state, which must not be all zero
+        let mut x = seed;
+        let mut next = || {
+            x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+        Random { state: [next(), next(), next(), next()] }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// Uniform on [0, 1), with 53 random bits.
+    pub fn uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Uniform on 0..n without modulo bias.
+    fn below(&mut self, n: u64) -> u64 {
+        let limit = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < limit {
+                return x % n;
+            }
+        }
+    }
+
+    /// Standard normal by the Box–Muller transform.
+    fn normal(&mut self) -> f64 {
+        // 1 - u lies in (0, 1], so the logarithm is finite
+        let (u1, u2) = (1.0 - self.uniform(), self.uniform());
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+/// `rand()`, `randint(a, b)`, `randn([mu, sigma])` and `choice(list)`.
+pub fn evaluate(name: &str, args: &[Value], random: &mut Random) -> Result<Value, String> {
+    match (name, args) {
+        ("rand", []) => Ok(Value::Number(random.uniform())),
+        ("randint", [a, b]) => {
+            let (a, b) = match (a.to_integer(), b.to_integer()) {
+                (Some(a), Some(b)) if a <= b => (a, b),
+                _ => return Err("randint requires whole numbers a <= b".to_string()),
+            };
+            let span = u64::try_from(b - a).map_err(|_| "randint: the range is too wide".to_string())?;
+            let offset = if span == u64::MAX { random.next_u64() } else { random.below(span + 1) };
+            Ok(Value::Integer(a + offset as i128))
+        }
+        ("randn", _) if args.len() <= 2 => {
+            let mu = args.first().map_or(Ok(0.0), Value::to_f64)?;
+            let sigma = args.get(1).map_or(Ok(1.0), Value::to_f64)?;
+            if sigma < 0.0 {
+                return Err("randn requires sigma >= 0".to_string());
+            }
+            Ok(Value::Number(mu + sigma * random.normal()))
+        }
+        ("choice", [Value::Vector(values)]) if !values.is_empty() => {
+            Ok(Value::Number(values[random.below(values.len() as u64) as usize]))
+        }
+        ("choice", [_, _, ..]) => Ok(args[random.below(args.len() as u64) as usize].clone()),
+        ("choice", _) => Err("choice requires a list such as [1, 5, 10] or several values".to_string()),
+        ("rand", _) => Err("rand takes no arguments; use randint(a, b) for whole numbers".to_string()),
+        ("randint", _) => Err("randint requires two arguments: a and b".to_string()),
+        _ => Err(format!("{} requires at most two arguments: mu and sigma", name)),
+    }
+}
+
+/// Rolls `count` dice with `sides` faces each, as in `3d6`.
+pub fn roll(count: i128, sides: i128, random: &mut Random) -> Result<Vec<i128>, String> {
+    if !(1..=MAX_DICE).contains(&count) {
+        return Err(format!("Dice: roll between 1 and {} dice", MAX_DICE));
+    }
+    let sides = u64::try_from(sides).ok().filter(|&s| s >= 1).ok_or("Dice need at least one side")?;
+    Ok((0..count).map(|_| random.below(sides) as i128 + 1).collect())
+}
+
+/// The individual rolls as a trace shows them, e.g. `4 + 2 + 6`.
+pub fn describe_roll(rolls: &[i128]) -> String {
+    let shown = rolls.iter().take(SHOWN_ROLLS).map(|r| r.to_string()).collect::<Vec<_>>().join(" + ");
+    if rolls.len() > SHOWN_ROLLS {
+        format!("{} + … ({} dice)", shown, rolls.len())
+    } else {
+        shown
+    }
+}
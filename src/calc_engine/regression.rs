@@ -0,0 +1,201 @@
+use super::value::Value;
+
+/// The shape of a fitted curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Model {
+    /// y = a·x + b
+    Linear,
+    /// y = a·e^(b·x)
+    Exponential,
+    /// y = a + b·ln x
+    Logarithmic,
+    /// y = a·x^b
+    Power,
+}
+
+/// A curve fitted to data by least squares, which `predict` can apply to new x values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fit {
+    pub model: Model,
+    pub a: f64,
+    pub b: f64,
+    /// Coefficient of determination, measured on the original (untransformed) data.
+    pub r_squared: f64,
+}
+
+impl Fit {
+    pub fn predict(&self, x: f64) -> f64 {
+        match self.model {
+            Model::Linear => self.a * x + self.b,
+            Model::Exponential => self.a * (self.b * x).exp(),
+            Model::Logarithmic => self.a + self.b * x.ln(),
+            Model::Power => self.a * x.powf(self.b),
+        }
+    }
+
+    /// The equation and r², e.g. `y = 2x + 1, r² = 0.998`.
+    pub fn render(&self, format_number: &dyn Fn(f64) -> String) -> String {
+        // "+ -3" reads better as "- 3"
+        let signed = |x: f64| if x < 0.0 { format!("- {}", format_number(-x)) } else { format!("+ {}", format_number(x)) };
+        let times_x = |k: f64| match format_number(k).as_str() {
+            "1" => "x".to_string(),
+            "-1" => "-x".to_string(),
+            k => format!("{}x", k),
+        };
+        let equation = match self.model {
+            Model::Linear => format!("{} {}", times_x(self.a), signed(self.b)),
+            Model::Exponential => format!("{}*exp({})", format_number(self.a), times_x(self.b)),
+            Model::Logarithmic => format!("{} {}*ln(x)", format_number(self.a), signed(self.b)),
+            Model::Power => format!("{}*x^{}", format_number(self.a), format_number(self.b)),
+        };
+        format!("y = {}, r² = {}", equation, format_number(self.r_squared))
+    }
+}
+
+/// Regression and correlation on two lists of equal length.
+pub fn evaluate(name: &str, args: &[Value]) -> Result<Value, String> {
+    if name == "predict" {
+        return match args {
+            [Value::Fit(fit), Value::Vector(xs)] => Ok(Value::Vector(xs.iter().map(|&x| fit.predict(x)).collect())),
+            [Value::Fit(fit), x] => Ok(Value::Number(fit.predict(x.to_f64()?))),
+            _ => Err("predict requires a fitted model and x, e.g. predict(linreg(xs, ys), 10)".to_string()),
+        };
+    }
+
+    let (xs, ys) = match args {
+        [Value::Vector(xs), Value::Vector(ys)] if xs.len() == ys.len() => (xs, ys),
+        [Value::Vector(xs), Value::Vector(ys)] => {
+            return Err(format!("{}: {} x values but {} y values", name, xs.len(), ys.len()));
+        }
+        _ => return Err(format!("{} requires two lists, e.g. {}([1, 2, 3], [2, 4, 7])", name, name)),
+    };
+    if xs.len() < 2 {
+        return Err(format!("{} requires at least two points", name));
+    }
+
+    match name {
+        "cov" => Ok(Value::Number(covariance(xs, ys))),
+        "corr" => {
+            let (sx, sy) = (covariance(xs, xs).sqrt(), covariance(ys, ys).sqrt());
+            if sx == 0.0 || sy == 0.0 {
+                return Err("corr is undefined when either list is constant".to_string());
+            }
+            Ok(Value::Number((covariance(xs, ys) / (sx * sy)).clamp(-1.0, 1.0)))
+        }
+        _ => fit(name, xs, ys).map(Value::Fit),
+    }
+}
+
+/// Sample covariance, dividing by n - 1.
+fn covariance(xs: &[f64], ys: &[f64]) -> f64 {
+    let (mx, my) = (mean(xs), mean(ys));
+    xs.iter().zip(ys).map(|(x, y)| (x - mx) * (y - my)).sum::<f64>() / (xs.len() - 1) as f64
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Fits the model by a straight line through transformed data: ln y for exponential
+/// growth, ln x for logarithmic curves and both for power laws.
+fn fit(name: &str, xs: &[f64], ys: &[f64]) -> Result<Fit, String> {
+    let positive = |values: &[f64], which: &str| {
+        if values.iter().any(|&v| v <= 0.0) {
+            return Err(format!("{} requires positive {} values", name, which));
+        }
+        Ok(values.iter().map(|v| v.ln()).collect::<Vec<_>>())
+    };
+    let (model, u, v) = match name {
+        "linreg" => (Model::Linear, xs.to_vec(), ys.to_vec()),
+        "expfit" => (Model::Exponential, xs.to_vec(), positive(ys, "y")?),
+        "logfit" => (Model::Logarithmic, positive(xs, "x")?, ys.to_vec()),
+        "powfit" => (Model::Power, positive(xs, "x")?, positive(ys, "y")?),
+        _ => return Err(format!("Unknown regression function: {}", name)),
+    };
+
+    let spread = covariance(&u, &u);
+    if spread == 0.0 {
+        return Err(format!("{}: the x values are all equal", name));
+    }
+    let slope = covariance(&u, &v) / spread;
+    let intercept = mean(&v) - slope * mean(&u);
+    let (a, b) = match model {
+        Model::Linear => (slope, intercept),
+        Model::Exponential | Model::Power => (intercept.exp(), slope),
+        Model::Logarithmic => (intercept, slope),
+    };
+
+    let mut fit = Fit { model, a, b, r_squared: 1.0 };
+    let my = mean(ys);
+    let residual: f64 = xs.iter().zip(ys).map(|(&x, &y)| (y - fit.predict(x)).powi(2)).sum();
+    let total: f64 = ys.iter().map(|y| (y - my).powi(2)).sum();
+    if total > 0.0 {
+        fit.r_squared = 1.0 - residual / total;
+    }
+    Ok(fit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lists(xs: &[f64], ys: &[f64]) -> [Value; 2] {
+        [Value::Vector(xs.to_vec()), Value::Vector(ys.to_vec())]
+    }
+
+    fn fitted(name: &str, xs: &[f64], ys: &[f64]) -> Fit {
+        match evaluate(name, &lists(xs, ys)) {
+            Ok(Value::Fit(fit)) => fit,
+            other => panic!("{} gave {:?}", name, other),
+        }
+    }
+
+    fn close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-12 * expected.abs().max(1.0), "{} is not {}", actual, expected);
+    }
+
+    #[test]
+    fn exact_data_is_fitted_exactly() {
+        let xs = [1.0, 2.0, 3.0, 4.0];
+        let line = fitted("linreg", &xs, &[3.0, 5.0, 7.0, 9.0]);
+        close(line.a, 2.0);
+        close(line.b, 1.0);
+        close(line.r_squared, 1.0);
+        let growth = fitted("expfit", &xs, &xs.map(|x| 3.0 * (0.5 * x).exp()));
+        close(growth.a, 3.0);
+        close(growth.b, 0.5);
+        let curve = fitted("logfit", &xs, &xs.map(|x| 1.0 + 2.0 * x.ln()));
+        close(curve.a, 1.0);
+        close(curve.b, 2.0);
+        let law = fitted("powfit", &xs, &xs.map(|x| 2.0 * x.powf(1.5)));
+        close(law.a, 2.0);
+        close(law.b, 1.5);
+        assert_eq!(evaluate("predict", &[Value::Fit(line), Value::Number(10.0)]), Ok(Value::Number(21.0)));
+    }
+
+    #[test]
+    fn noisy_data_and_correlation() {
+        let xs = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let ys = [2.0, 4.0, 5.0, 4.0, 5.0];
+        let line = fitted("linreg", &xs, &ys);
+        close(line.a, 0.6);
+        close(line.b, 2.2);
+        close(line.r_squared, 0.6);
+        let Ok(Value::Number(r)) = evaluate("corr", &lists(&xs, &ys)) else {
+            panic!("corr failed");
+        };
+        close(r * r, 0.6);
+        assert_eq!(evaluate("cov", &lists(&xs, &ys)), Ok(Value::Number(1.5)));
+    }
+
+    #[test]
+    fn unusable_data_is_rejected() {
+        assert!(evaluate("linreg", &lists(&[1.0, 2.0], &[1.0])).is_err());
+        assert!(evaluate("linreg", &lists(&[1.0], &[1.0])).is_err());
+        assert!(evaluate("linreg", &lists(&[2.0, 2.0], &[1.0, 3.0])).is_err());
+        assert!(evaluate("expfit", &lists(&[1.0, 2.0], &[1.0, -3.0])).is_err());
+        assert!(evaluate("logfit", &lists(&[0.0, 2.0], &[1.0, 3.0])).is_err());
+        assert!(evaluate("corr", &lists(&[1.0, 2.0], &[3.0, 3.0])).is_err());
+        assert!(evaluate("predict", &[Value::Number(1.0), Value::Number(2.0)]).is_err());
+    }
+}
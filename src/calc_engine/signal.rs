@@ -0,0 +1,245 @@
+use super::complex::Complex;
+use super::value::{self, Value};
+use std::f64::consts::PI;
+
+/// Longest list the transforms accept.
+const MAX_LENGTH: usize = 1 << 22;
+
+/// Above this many multiplications, `convolve` goes through the FFT.
+const DIRECT_CONVOLUTION_LIMIT: usize = 1 << 20;
+
+/// Parts this small relative to the largest entry are rounding noise in `fft` and `ifft`.
+const NOISE_TOLERANCE: f64 = 1e-12;
+
+/// Transforms and filters on lists, for quick signal-processing checks.
+pub fn evaluate(name: &str, args: &[Value]) -> Result<Value, String> {
+    match (name, args) {
+        ("fft" | "ifft", [list]) => {
+            let values = complex_list(name, list)?;
+            if values.is_empty() || values.len() > MAX_LENGTH {
+                return Err(format!("{} requires a list of 1 to {} values", name, MAX_LENGTH));
+            }
+            let transformed = clean(fft(&values, name == "ifft"));
+            Ok(if name == "ifft" { real_if_possible(transformed) } else { Value::ComplexVector(transformed) })
+        }
+        ("magnitude" | "phase", [value]) => {
+            let part = |z: Complex| if name == "magnitude" { z.abs() } else { z.im.atan2(z.re) };
+            match value {
+                Value::Complex(z) => Ok(Value::Number(part(*z))),
+                Value::Vector(_) | Value::ComplexVector(_) => {
+                    Ok(Value::Vector(complex_list(name, value)?.into_iter().map(part).collect()))
+                }
+                other => Ok(Value::Number(part(Complex::real(other.to_f64()?)))),
+            }
+        }
+        ("convolve", [a, b]) => {
+            let (a, b) = (real_list(name, a)?, real_list(name, b)?);
+            if a.is_empty() || b.is_empty() {
+                return Err("convolve requires two non-empty lists".to_string());
+            }
+            Ok(Value::Vector(convolve(a, b)))
+        }
+        ("movavg", [list, width]) => {
+            let values = real_list(name, list)?;
+            let width = match width.to_integer() {
+                Some(w) if w >= 1 && w as usize <= values.len() => w as usize,
+                _ => return Err(format!("movavg: the width must be a whole number from 1 to {}", values.len())),
+            };
+            Ok(Value::Vector(values.windows(width).map(|w| w.iter().sum::<f64>() / width as f64).collect()))
+        }
+        ("hann" | "hamming" | "blackman", [Value::Vector(values)]) => {
+            let coefficients = window(name, values.len());
+            Ok(Value::Vector(values.iter().zip(coefficients).map(|(x, w)| x * w).collect()))
+        }
+        ("hann" | "hamming" | "blackman", [n]) => match n.to_integer() {
+            Some(n) if n >= 1 && n as usize <= MAX_LENGTH => Ok(Value::Vector(window(name, n as usize))),
+            _ => Err(format!("{} requires a length from 1 to {} or a list to apply the window to", name, MAX_LENGTH)),
+        },
+        _ => Err(format!("{}: wrong number of arguments", name)),
+    }
+}
+
+fn real_list<'a>(name: &str, value: &'a Value) -> Result<&'a [f64], String> {
+    match value {
+        Value::Vector(v) => Ok(v),
+        _ => Err(format!("{} expects a list such as [1, 2, 3]", name)),
+    }
+}
+
+fn complex_list(name: &str, value: &Value) -> Result<Vec<Complex>, String> {
+    match value {
+        Value::Vector(v) => Ok(v.iter().map(|&x| Complex::real(x)).collect()),
+        Value::ComplexVector(v) => Ok(v.clone()),
+        _ => Err(format!("{} expects a list such as [1, 2, 3]", name)),
+    }
+}
+
+/// The entries of a transform, with real and imaginary parts that are rounding noise
+/// set to zero, e.g. the imaginary part of the first entry of `fft([1, 2, 3])`.
+fn clean(values: Vec<Complex>) -> Vec<Complex> {
+    let largest = values.iter().fold(0.0, |m: f64, z| m.max(z.abs()));
+    let part = |x: f64| value::clean_relative(x, largest, NOISE_TOLERANCE);
+    values.into_iter().map(|z| Complex::new(part(z.re), part(z.im))).collect()
+}
+
+/// A real list when every imaginary part is zero, as for the inverse transform of a
+/// real signal's spectrum once `clean` has removed the rounding noise.
+fn real_if_possible(values: Vec<Complex>) -> Value {
+    if values.iter().all(|z| z.im == 0.0) {
+        Value::Vector(values.iter().map(|z| z.re).collect())
+    } else {
+        Value::ComplexVector(values)
+    }
+}
+
+/// Discrete Fourier transform of any length, unnormalised forward and scaled by 1/n
+/// in reverse.
+pub fn fft(input: &[Complex], inverse: bool) -> Vec<Complex> {
+    let n = input.len();
+    let mut output = if n.is_power_of_two() { radix2(input, inverse) } else { bluestein(input, inverse) };
+    if inverse {
+        output.iter_mut().for_each(|z| *z = z.scale(1.0 / n as f64));
+    }
+    output
+}
+
+/// Iterative Cooley–Tukey for lengths that are powers of two.
+fn radix2(input: &[Complex], inverse: bool) -> Vec<Complex> {
+    let n = input.len();
+    let bits = n.trailing_zeros();
+    let mut data = vec![Complex::real(0.0); n];
+    for (i, &z) in input.iter().enumerate() {
+        let j = if bits == 0 { 0 } else { i.reverse_bits() >> (usize::BITS - bits) };
+        data[j] = z;
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut length = 2;
+    while length <= n {
+        let half = length / 2;
+        for start in (0..n).step_by(length) {
+            for k in 0..half {
+                // Twiddle factors computed directly, which keeps rounding from accumulating
+                let twiddle = Complex::from_polar(1.0, sign * 2.0 * PI * k as f64 / length as f64);
+                let even = data[start + k];
+                let odd = data[start + k + half].mul(twiddle);
+                data[start + k] = even.add(odd);
+                data[start + k + half] = even.sub(odd);
+            }
+        }
+        length *= 2;
+    }
+    data
+}
+
+/// Bluestein's algorithm: a transform of any length as a convolution, which a
+/// power-of-two FFT then computes.
+fn bluestein(input: &[Complex], inverse: bool) -> Vec<Complex> {
+    let n = input.len();
+    let m = (2 * n - 1).next_power_of_two();
+    let sign = if inverse { 1.0 } else { -1.0 };
+    // e^(∓iπk²/n), with k² reduced mod 2n so large k keep their accuracy
+    let chirp: Vec<Complex> = (0..n)
+        .map(|k| {
+            let k2 = (k as u128 * k as u128 % (2 * n as u128)) as f64;
+            Complex::from_polar(1.0, sign * PI * k2 / n as f64)
+        })
+        .collect();
+
+    let mut a = vec![Complex::real(0.0); m];
+    for k in 0..n {
+        a[k] = input[k].mul(chirp[k]);
+    }
+    let mut b = vec![Complex::real(0.0); m];
+    b[0] = conjugate(chirp[0]);
+    for k in 1..n {
+        b[k] = conjugate(chirp[k]);
+        b[m - k] = conjugate(chirp[k]);
+    }
+
+    let (fa, fb) = (radix2(&a, false), radix2(&b, false));
+    let product: Vec<Complex> = fa.iter().zip(&fb).map(|(x, y)| x.mul(*y)).collect();
+    let convolved = radix2(&product, true);
+    (0..n).map(|k| convolved[k].scale(1.0 / m as f64).mul(chirp[k])).collect()
+}
+
+fn conjugate(z: Complex) -> Complex {
+    Complex::new(z.re, -z.im)
+}
+
+/// Full linear convolution, of length `a.len() + b.len() - 1`.
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let length = a.len() + b.len() - 1;
+    if a.len().saturating_mul(b.len()) <= DIRECT_CONVOLUTION_LIMIT {
+        let mut result = vec![0.0; length];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                result[i + j] += x * y;
+            }
+        }
+        return result;
+    }
+    let m = length.next_power_of_two();
+    let padded = |v: &[f64]| {
+        let mut p: Vec<Complex> = v.iter().map(|&x| Complex::real(x)).collect();
+        p.resize(m, Complex::real(0.0));
+        radix2(&p, false)
+    };
+    let product: Vec<Complex> = padded(a).iter().zip(&padded(b)).map(|(x, y)| x.mul(*y)).collect();
+    radix2(&product, true)[..length].iter().map(|z| z.re / m as f64).collect()
+}
+
+/// Symmetric window coefficients of length `n`.
+fn window(name: &str, n: usize) -> Vec<f64> {
+    if n == 1 {
+        return vec![1.0];
+    }
+    let phase = |k: usize| 2.0 * PI * k as f64 / (n - 1) as f64;
+    (0..n)
+        .map(|k| match name {
+            "hann" => 0.5 - 0.5 * phase(k).cos(),
+            "hamming" => 0.54 - 0.46 * phase(k).cos(),
+            _ => 0.42 - 0.5 * phase(k).cos() + 0.08 * (2.0 * phase(k)).cos(),
+        })
+        // Coefficients are at most 1, so the Blackman endpoints' -1.4e-17 is rounding noise
+        .map(|w| value::clean_noise(w, 1.0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transform_noise_is_cleaned() {
+        let Ok(Value::ComplexVector(spectrum)) = evaluate("fft", &[Value::Vector(vec![1.0, 2.0, 3.0])]) else {
+            panic!("fft failed");
+        };
+        assert_eq!(spectrum[0], Complex::real(6.0));
+        assert!((spectrum[1].im - 0.75f64.sqrt()).abs() < 1e-12);
+        let roundtrip = evaluate("ifft", &[Value::ComplexVector(spectrum)]).unwrap();
+        let Value::Vector(values) = roundtrip else {
+            panic!("ifft of a real signal's spectrum is not real");
+        };
+        assert!(values.iter().zip([1.0, 2.0, 3.0]).all(|(x, y)| (x - y).abs() < 1e-12));
+    }
+
+    #[test]
+    fn small_parts_relative_to_their_own_scale_are_kept() {
+        let Ok(Value::ComplexVector(spectrum)) = evaluate("fft", &[Value::Vector(vec![1e-20, 2e-20])]) else {
+            panic!("fft failed");
+        };
+        assert!((spectrum[0].re - 3e-20).abs() < 1e-32 && (spectrum[1].re + 1e-20).abs() < 1e-32);
+    }
+
+    #[test]
+    fn window_endpoints_are_exact() {
+        let blackman = window("blackman", 4);
+        assert_eq!((blackman[0], blackman[3]), (0.0, 0.0));
+        assert!((blackman[1] - 0.63).abs() < 1e-15);
+        let hann = window("hann", 5);
+        assert!(hann.iter().zip([0.0, 0.5, 1.0, 0.5, 0.0]).all(|(w, e)| (w - e).abs() < 1e-15), "{:?}", hann);
+        assert_eq!((hann[0], hann[4]), (0.0, 0.0));
+        assert_eq!(window("hamming", 1), vec![1.0]);
+    }
+}
@@ -0,0 +1,772 @@
+use super::{apply_function, Token};
+use std::f64::consts::{E, PI};
+use std::fmt;
+
+/// Highest derivative order `diff` accepts.
+const MAX_ORDER: i128 = 20;
+
+/// Most nodes a derivative may have. Repeated differentiation of expressions such as
+/// x^x roughly triples their size each time, so the order alone does not bound the work.
+const MAX_NODES: usize = 2000;
+
+/// Symbolic expression in one or more variables, e.g. the result of
+/// `diff(x^2 * sin(x), x)`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    /// A variable, or the constants `pi` and `e`.
+    Symbol(String),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+fn number(n: f64) -> Expr {
+    Expr::Number(n)
+}
+
+fn neg(a: Expr) -> Expr {
+    Expr::Neg(Box::new(a))
+}
+
+fn add(a: Expr, b: Expr) -> Expr {
+    Expr::Add(Box::new(a), Box::new(b))
+}
+
+fn sub(a: Expr, b: Expr) -> Expr {
+    Expr::Sub(Box::new(a), Box::new(b))
+}
+
+fn mul(a: Expr, b: Expr) -> Expr {
+    Expr::Mul(Box::new(a), Box::new(b))
+}
+
+fn div(a: Expr, b: Expr) -> Expr {
+    Expr::Div(Box::new(a), Box::new(b))
+}
+
+fn pow(a: Expr, b: Expr) -> Expr {
+    Expr::Pow(Box::new(a), Box::new(b))
+}
+
+fn call(name: &str, arg: Expr) -> Expr {
+    Expr::Call(name.to_string(), vec![arg])
+}
+
+impl Expr {
+    /// Whether `variable` occurs anywhere in the expression.
+    pub fn contains(&self, variable: &str) -> bool {
+        match self {
+            Expr::Number(_) => false,
+            Expr::Symbol(name) => name == variable,
+            Expr::Neg(a) => a.contains(variable),
+            Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) | Expr::Div(a, b) | Expr::Pow(a, b) => {
+                a.contains(variable) || b.contains(variable)
+            }
+            Expr::Call(_, args) => args.iter().any(|arg| arg.contains(variable)),
+        }
+    }
+
+    /// The number of nodes in the expression tree.
+    pub fn size(&self) -> usize {
+        1 + match self {
+            Expr::Number(_) | Expr::Symbol(_) => 0,
+            Expr::Neg(a) => a.size(),
+            Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) | Expr::Div(a, b) | Expr::Pow(a, b) => {
+                a.size() + b.size()
+            }
+            Expr::Call(_, args) => args.iter().map(Expr::size).sum(),
+        }
+    }
+
+    /// The value of the expression, with `variable` supplying the value of each name.
+    pub fn evaluate(&self, variable: &dyn Fn(&str) -> Option<f64>) -> Result<f64, String> {
+        let eval = |e: &Expr| e.evaluate(variable);
+        Ok(match self {
+            Expr::Number(n) => *n,
+            Expr::Symbol(name) => match variable(name) {
+                Some(value) => value,
+                None if name == "pi" => PI,
+                None if name == "e" => E,
+                None => return Err(format!("Unknown variable '{}'", name)),
+            },
+            Expr::Neg(a) => -eval(a)?,
+            Expr::Add(a, b) => eval(a)? + eval(b)?,
+            Expr::Sub(a, b) => eval(a)? - eval(b)?,
+            Expr::Mul(a, b) => eval(a)? * eval(b)?,
+            Expr::Div(a, b) => {
+                let (numerator, denominator) = (eval(a)?, eval(b)?);
+                if denominator == 0.0 {
+                    return Err("Division by zero".to_string());
+                }
+                numerator / denominator
+            }
+            Expr::Pow(a, b) => {
+                let (base, exponent) = (eval(a)?, eval(b)?);
+                if base == 0.0 && exponent < 0.0 {
+                    return Err("Division by zero".to_string());
+                }
+                base.powf(exponent)
+            }
+            Expr::Call(name, args) => {
+                let args = args.iter().map(eval).collect::<Result<Vec<_>, _>>()?;
+                apply_function(name, &args)?
+            }
+        })
+    }
+
+    /// The expression as calculator input, with numbers written by `format_number`.
+    /// Parentheses appear only where precedence needs them.
+    pub fn render(&self, format_number: &dyn Fn(f64) -> String) -> String {
+        match self {
+            Expr::Number(n) if *n < 0.0 => format!("-{}", format_number(-n)),
+            Expr::Number(n) => format_number(*n),
+            Expr::Symbol(name) => name.clone(),
+            Expr::Call(name, args) => {
+                let args = args.iter().map(|arg| arg.render(format_number)).collect::<Vec<_>>();
+                format!("{}({})", name, args.join(", "))
+            }
+            Expr::Neg(a) => match a.as_ref() {
+                // Push the sign into the leftmost factor, so -(2x*y) reads -2x*y
+                Expr::Number(n) => number(-n).render(format_number),
+                Expr::Mul(left, right) => mul(negate_leftmost(left), (**right).clone()).render(format_number),
+                Expr::Div(left, right) => div(negate_leftmost(left), (**right).clone()).render(format_number),
+                Expr::Symbol(_) | Expr::Call(..) => format!("-{}", a.render(format_number)),
+                // -x^2 would read as (-x)^2
+                _ => format!("-({})", a.render(format_number)),
+            },
+            Expr::Add(a, b) => format!("{} + {}", a.render(format_number), b.operand(2, format_number)),
+            Expr::Sub(a, b) => format!("{} - {}", a.render(format_number), b.operand(2, format_number)),
+            Expr::Mul(a, b) => {
+                let (left, right) = (a.operand(2, format_number), b.operand(4, format_number));
+                // A coefficient stands directly before a variable: 2x, 3x^2
+                let variable = match b.as_ref() {
+                    Expr::Pow(base, _) => base.as_ref(),
+                    other => other,
+                };
+                match (a.as_ref(), variable) {
+                    (Expr::Number(_), Expr::Symbol(name)) if !name.starts_with(['e', 'E']) => format!("{}{}", left, right),
+                    _ => format!("{}*{}", left, right),
+                }
+            }
+            Expr::Div(a, b) => format!("{}/{}", a.operand(2, format_number), b.operand(4, format_number)),
+            Expr::Pow(a, b) => format!("{}^{}", a.operand(5, format_number), b.operand(3, format_number)),
+        }
+    }
+
+    /// Rendered as an operand that needs at least `precedence`, in parentheses if it binds
+    /// more loosely.
+    fn operand(&self, precedence: u8, format_number: &dyn Fn(f64) -> String) -> String {
+        if self.precedence() < precedence {
+            format!("({})", self.render(format_number))
+        } else {
+            self.render(format_number)
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Add(..) | Expr::Sub(..) => 1,
+            Expr::Mul(..) | Expr::Div(..) => 2,
+            Expr::Neg(_) => 3,
+            Expr::Number(n) if *n < 0.0 => 3,
+            Expr::Pow(..) => 4,
+            Expr::Number(_) | Expr::Symbol(_) | Expr::Call(..) => 5,
+        }
+    }
+}
+
+fn negate_leftmost(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Number(n) => number(-n),
+        Expr::Mul(a, b) => mul(negate_leftmost(a), (**b).clone()),
+        Expr::Div(a, b) => div(negate_leftmost(a), (**b).clone()),
+        other => neg(other.clone()),
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(&|x| x.to_string()))
+    }
+}
+
+/// Reads an expression from `tokens` starting at `start`, without evaluating it, and
+/// returns it with the index of the first token after it.
+pub fn parse(tokens: &[Token], start: usize) -> Result<(Expr, usize), String> {
+    let mut parser = ExprParser { tokens, current: start };
+    let expr = parser.expr()?;
+    parser.finish()?;
+    Ok((expr, parser.current))
+}
+
+/// Like `parse`, but also accepts a bracketed list such as `[y2, -y1]` for a system of
+/// equations.
+pub fn parse_list(tokens: &[Token], start: usize) -> Result<(Vec<Expr>, usize), String> {
+    if tokens.get(start) != Some(&Token::LBracket) {
+        return parse(tokens, start).map(|(expr, end)| (vec![expr], end));
+    }
+    let mut parser = ExprParser { tokens, current: start + 1 };
+    let mut exprs = vec![parser.expr()?];
+    while tokens.get(parser.current) == Some(&Token::Comma) {
+        parser.current += 1;
+        exprs.push(parser.expr()?);
+    }
+    parser.expect(&Token::RBracket, "Expected comma or closing bracket")?;
+    parser.finish()?;
+    Ok((exprs, parser.current))
+}
+
+/// The same grammar as the evaluating parser, so an expression means the same thing in
+/// both: `-x^2` is `(-x)^2`. A number directly before a name or parenthesis multiplies,
+/// so results such as `2x*sin(x)` can be read back.
+struct ExprParser<'a> {
+    tokens: &'a [Token],
+    current: usize,
+}
+
+impl ExprParser<'_> {
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut left = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.tokens.get(self.current) {
+            let op = *op;
+            self.current += 1;
+            let right = self.term()?;
+            left = if op == '+' { add(left, right) } else { sub(left, right) };
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut left = self.root()?;
+        loop {
+            match self.tokens.get(self.current) {
+                Some(Token::Op(op @ ('*' | '/'))) => {
+                    let op = *op;
+                    self.current += 1;
+                    let right = self.root()?;
+                    left = if op == '*' { mul(left, right) } else { div(left, right) };
+                }
+                Some(Token::Ident(_) | Token::LParen) if matches!(left, Expr::Number(_)) => {
+                    left = mul(left, self.root()?);
+                }
+                _ => return Ok(left),
+            }
+        }
+    }
+
+    /// `x r n`, the nth root of x, read as `x^(1/n)`.
+    fn root(&mut self) -> Result<Expr, String> {
+        let base = self.power()?;
+        if self.tokens.get(self.current) == Some(&Token::Op('r')) {
+            self.current += 1;
+            return Ok(pow(base, div(number(1.0), self.power()?)));
+        }
+        Ok(base)
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.unary()?;
+        if self.tokens.get(self.current) == Some(&Token::Op('^')) {
+            self.current += 1;
+            return Ok(pow(base, self.power()?));
+        }
+        Ok(base)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.tokens.get(self.current) {
+            Some(Token::Op('+')) => {
+                self.current += 1;
+                self.unary()
+            }
+            Some(Token::Op('-')) => {
+                self.current += 1;
+                // A negative literal stays a number, so -2y multiplies like 2y
+                Ok(match self.unary()? {
+                    Expr::Number(n) => number(-n),
+                    operand => neg(operand),
+                })
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let token = self.tokens.get(self.current).ok_or("Unexpected end of input")?;
+        self.current += 1;
+        match token {
+            Token::Number(n) => Ok(number(*n)),
+            Token::Integer(i) => Ok(number(*i as f64)),
+            Token::LParen => {
+                let inner = self.expr()?;
+                self.expect(&Token::RParen, "Missing closing parenthesis")?;
+                Ok(inner)
+            }
+            Token::Ident(name) if self.tokens.get(self.current) == Some(&Token::LParen) => {
+                self.current += 1;
+                let mut args = vec![self.expr()?];
+                while self.tokens.get(self.current) == Some(&Token::Comma) {
+                    self.current += 1;
+                    args.push(self.expr()?);
+                }
+                self.expect(&Token::RParen, "Missing closing parenthesis for function")?;
+                Ok(Expr::Call(name.to_lowercase(), args))
+            }
+            Token::Ident(name) if ["mod", "rem", "of", "as"].iter().any(|k| name.eq_ignore_ascii_case(k)) => {
+                Err(format!("'{}' is not supported in symbolic expressions", name))
+            }
+            Token::Ident(name) => Ok(Expr::Symbol(name.clone())),
+            Token::Op(op) => Err(format!("'{}' is not supported in symbolic expressions", op)),
+            _ => Err("Symbolic expressions may only contain numbers, variables and functions".to_string()),
+        }
+    }
+
+    /// Checks that the expression ends where an argument may end.
+    fn finish(&self) -> Result<(), String> {
+        match self.tokens.get(self.current) {
+            None | Some(Token::Comma | Token::RParen) => Ok(()),
+            Some(Token::Op(op)) => Err(format!("'{}' is not supported in symbolic expressions", op)),
+            Some(Token::Ident(word)) => Err(format!("'{}' is not supported in symbolic expressions", word)),
+            Some(_) => Err("Unexpected token in symbolic expression".to_string()),
+        }
+    }
+
+    fn expect(&mut self, token: &Token, message: &str) -> Result<(), String> {
+        if self.tokens.get(self.current) != Some(token) {
+            return Err(message.to_string());
+        }
+        self.current += 1;
+        Ok(())
+    }
+}
+
+/// The `order`th derivative of `expr` with respect to `variable`, simplified.
+pub fn derivative(expr: &Expr, variable: &str, order: i128) -> Result<Expr, String> {
+    if variable == "pi" || variable == "e" {
+        return Err(format!("'{}' is a constant, not a variable", variable));
+    }
+    if !(1..=MAX_ORDER).contains(&order) {
+        return Err(format!("diff: the order must be a whole number from 1 to {}", MAX_ORDER));
+    }
+    let too_large = |n: i128| format!("diff: derivative {} is too large to show; try a lower order", n);
+    let mut result = simplify(expr)?;
+    for n in 1..=order {
+        // Checked before simplifying too, since simplifying a huge tree is slow
+        let derived = differentiate(&result, variable)?;
+        if derived.size() > MAX_NODES {
+            return Err(too_large(n));
+        }
+        result = simplify(&derived)?;
+        if result.size() > MAX_NODES {
+            return Err(too_large(n));
+        }
+    }
+    Ok(result)
+}
+
+fn differentiate(expr: &Expr, x: &str) -> Result<Expr, String> {
+    if !expr.contains(x) {
+        return Ok(number(0.0));
+    }
+    let d = |e: &Expr| differentiate(e, x);
+    Ok(match expr {
+        Expr::Number(_) => number(0.0),
+        Expr::Symbol(_) => number(1.0),
+        Expr::Neg(a) => neg(d(a)?),
+        Expr::Add(a, b) => add(d(a)?, d(b)?),
+        Expr::Sub(a, b) => sub(d(a)?, d(b)?),
+        Expr::Mul(a, b) => add(mul(d(a)?, (**b).clone()), mul((**a).clone(), d(b)?)),
+        Expr::Div(a, b) => div(
+            sub(mul(d(a)?, (**b).clone()), mul((**a).clone(), d(b)?)),
+            pow((**b).clone(), number(2.0)),
+        ),
+        Expr::Pow(a, b) if !b.contains(x) => {
+            // Power rule: (u^n)' = n u^(n-1) u'
+            mul(mul((**b).clone(), pow((**a).clone(), sub((**b).clone(), number(1.0)))), d(a)?)
+        }
+        Expr::Pow(a, b) => {
+            // (u^v)' = u^v (v' ln u + v u'/u)
+            let chain = add(mul(d(b)?, call("ln", (**a).clone())), div(mul((**b).clone(), d(a)?), (**a).clone()));
+            mul(expr.clone(), chain)
+        }
+        Expr::Call(name, args) => {
+            // Chain rule through each argument that depends on x
+            let mut total = number(0.0);
+            for (i, arg) in args.iter().enumerate().filter(|(_, arg)| arg.contains(x)) {
+                total = add(total, mul(partial_derivative(name, args, i)?, d(arg)?));
+            }
+            total
+        }
+    })
+}
+
+/// ∂f/∂(argument i) for a built-in function f of several arguments.
+fn partial_derivative(name: &str, args: &[Expr], i: usize) -> Result<Expr, String> {
+    let arg = |j: usize| args[j].clone();
+    let f = || Expr::Call(name.to_string(), args.to_vec());
+    let digamma = |e: Expr| call("digamma", e);
+    Ok(match (name, args.len(), i) {
+        (_, 1, 0) if !matches!(name, "normpdf" | "normcdf") => outer_derivative(name, &args[0])?,
+        // B(a, b) (ψ(a) - ψ(a + b)), and likewise for b
+        ("beta", 2, _) => mul(f(), sub(digamma(arg(i)), digamma(add(arg(0), arg(1))))),
+        // J_n' = (J_(n-1) - J_(n+1)) / 2, and the same for Y_n
+        ("besselj" | "bessely", 2, 1) => div(
+            sub(
+                Expr::Call(name.to_string(), vec![sub(arg(0), number(1.0)), arg(1)]),
+                Expr::Call(name.to_string(), vec![add(arg(0), number(1.0)), arg(1)]),
+            ),
+            number(2.0),
+        ),
+        ("besselj" | "bessely", 2, 0) => return Err(format!("No derivative rule for '{}' with respect to its order", name)),
+        // min(a, b) = (a + b - |a - b|) / 2 and max(a, b) = (a + b + |a - b|) / 2
+        ("min" | "max", 2, _) => {
+            let difference = sub(arg(0), arg(1));
+            let sign = div(difference.clone(), call("abs", difference));
+            let toward = if (name == "max") == (i == 0) { add(number(1.0), sign) } else { sub(number(1.0), sign) };
+            div(toward, number(2.0))
+        }
+        ("normpdf" | "normcdf", 1 | 3, _) => {
+            let (x, mu, sigma) = match args.len() {
+                1 => (arg(0), number(0.0), number(1.0)),
+                _ => (arg(0), arg(1), arg(2)),
+            };
+            let pdf = || Expr::Call("normpdf".to_string(), args.to_vec());
+            let z = || div(sub(x.clone(), mu.clone()), sigma.clone());
+            match (name, i) {
+                ("normcdf", 0) => pdf(),
+                ("normcdf", 1) => neg(pdf()),
+                ("normcdf", _) => neg(mul(z(), pdf())),
+                ("normpdf", 0 | 1) => {
+                    let slope = div(mul(z(), pdf()), sigma.clone());
+                    if i == 0 { neg(slope) } else { slope }
+                }
+                _ => mul(div(sub(pow(z(), number(2.0)), number(1.0)), sigma.clone()), pdf()),
+            }
+        }
+        _ => return Err(no_rule(name)),
+    })
+}
+
+fn no_rule(name: &str) -> String {
+    format!("No derivative rule for '{}'", name)
+}
+
+/// f'(u) for a built-in function f; the chain rule supplies u'. Inverse trigonometric
+/// functions return degrees, which scales their derivatives by 180/π.
+fn outer_derivative(name: &str, u: &Expr) -> Result<Expr, String> {
+    let u = || u.clone();
+    let degrees = || div(number(180.0), Expr::Symbol("pi".to_string()));
+    let one_minus_square = || sub(number(1.0), pow(u(), number(2.0)));
+    Ok(match name {
+        "sin" => call("cos", u()),
+        "cos" => neg(call("sin", u())),
+        "tan" => pow(call("cos", u()), number(-2.0)),
+        "asin" => div(degrees(), call("sqrt", one_minus_square())),
+        "acos" => neg(div(degrees(), call("sqrt", one_minus_square()))),
+        "atan" => div(degrees(), add(number(1.0), pow(u(), number(2.0)))),
+        "sinh" => call("cosh", u()),
+        "cosh" => call("sinh", u()),
+        "tanh" => pow(call("cosh", u()), number(-2.0)),
+        "asinh" => div(number(1.0), call("sqrt", add(pow(u(), number(2.0)), number(1.0)))),
+        "acosh" => div(number(1.0), call("sqrt", sub(pow(u(), number(2.0)), number(1.0)))),
+        "atanh" => div(number(1.0), one_minus_square()),
+        "ln" => div(number(1.0), u()),
+        "log" => div(number(1.0), mul(u(), call("ln", number(10.0)))),
+        "exp" => call("exp", u()),
+        "sqrt" => div(number(1.0), mul(number(2.0), call("sqrt", u()))),
+        "abs" => div(u(), call("abs", u())),
+        // Piecewise constant wherever the derivative exists
+        "floor" | "ceil" | "round" => number(0.0),
+        "erf" | "erfc" => {
+            let slope = div(
+                mul(number(2.0), call("exp", neg(pow(u(), number(2.0))))),
+                call("sqrt", Expr::Symbol("pi".to_string())),
+            );
+            if name == "erf" { slope } else { neg(slope) }
+        }
+        "gamma" => mul(call("gamma", u()), call("digamma", u())),
+        "lgamma" => call("digamma", u()),
+        "fact" | "factorial" => mul(call(name, u()), call("digamma", add(u(), number(1.0)))),
+        "lambertw" => div(
+            call("lambertw", u()),
+            mul(u(), add(number(1.0), call("lambertw", u()))),
+        ),
+        _ => return Err(no_rule(name)),
+    })
+}
+
+/// Term of a sum: a coefficient times a product of powers.
+struct Term {
+    coefficient: f64,
+    /// (base, exponent) pairs with distinct bases, in order of appearance.
+    factors: Vec<(Expr, Expr)>,
+}
+
+impl Term {
+    /// The factors as sorted text, so `x*y` and `y*x` count as like terms.
+    fn key(&self) -> Vec<String> {
+        let mut key: Vec<String> = self.factors.iter().map(|(base, exponent)| format!("{}^{}", base, exponent)).collect();
+        key.sort();
+        key
+    }
+}
+
+/// Folds constants, collects like terms and like factors, and removes identities such
+/// as `0 + x`, `1 * x` and `x^1`. Dividing by a constant zero is an error, as it is
+/// for numbers.
+pub fn simplify(expr: &Expr) -> Result<Expr, String> {
+    Ok(match expr {
+        Expr::Number(_) | Expr::Symbol(_) => expr.clone(),
+        Expr::Pow(a, b) => simplify_power(simplify(a)?, simplify(b)?)?,
+        Expr::Call(name, args) => simplify_call(name, args.iter().map(simplify).collect::<Result<_, _>>()?),
+        _ => {
+            let mut terms = Vec::new();
+            collect_terms(expr, 1.0, &mut terms)?;
+            build_sum(terms)
+        }
+    })
+}
+
+fn collect_terms(expr: &Expr, sign: f64, terms: &mut Vec<Term>) -> Result<(), String> {
+    match expr {
+        Expr::Add(a, b) => {
+            collect_terms(a, sign, terms)?;
+            collect_terms(b, sign, terms)?;
+        }
+        Expr::Sub(a, b) => {
+            collect_terms(a, sign, terms)?;
+            collect_terms(b, -sign, terms)?;
+        }
+        Expr::Neg(a) => collect_terms(a, -sign, terms)?,
+        _ => {
+            let mut term = Term { coefficient: sign, factors: Vec::new() };
+            multiply_into(&mut term, expr, false)?;
+            term.factors.retain(|(_, exponent)| *exponent != number(0.0));
+            if term.coefficient == 0.0 {
+                return Ok(());
+            }
+            let key = term.key();
+            match terms.iter_mut().find(|t| t.key() == key) {
+                Some(like) => like.coefficient += term.coefficient,
+                None => terms.push(term),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Multiplies `term` by `expr`, or divides it when `invert` is set.
+fn multiply_into(term: &mut Term, expr: &Expr, invert: bool) -> Result<(), String> {
+    match expr {
+        Expr::Mul(a, b) => {
+            multiply_into(term, a, invert)?;
+            multiply_into(term, b, invert)?;
+        }
+        Expr::Div(a, b) => {
+            multiply_into(term, a, invert)?;
+            multiply_into(term, b, !invert)?;
+        }
+        Expr::Neg(a) => {
+            term.coefficient = -term.coefficient;
+            multiply_into(term, a, invert)?;
+        }
+        Expr::Number(n) if invert && *n == 0.0 => return Err("Division by zero".to_string()),
+        Expr::Number(n) => {
+            term.coefficient = if invert { term.coefficient / n } else { term.coefficient * n };
+        }
+        _ => match simplify(expr)? {
+            simplified @ (Expr::Number(_) | Expr::Mul(..) | Expr::Div(..) | Expr::Neg(_)) => {
+                multiply_into(term, &simplified, invert)?
+            }
+            Expr::Pow(base, exponent) => {
+                let exponent = if invert { negate(*exponent)? } else { *exponent };
+                add_factor(term, *base, exponent)?;
+            }
+            other => add_factor(term, other, number(if invert { -1.0 } else { 1.0 }))?,
+        },
+    }
+    Ok(())
+}
+
+fn add_factor(term: &mut Term, base: Expr, exponent: Expr) -> Result<(), String> {
+    match term.factors.iter_mut().find(|(b, _)| *b == base) {
+        Some((_, existing)) => {
+            *existing = match (&*existing, &exponent) {
+                (Expr::Number(p), Expr::Number(q)) => number(p + q),
+                _ => simplify(&add(existing.clone(), exponent))?,
+            }
+        }
+        None => term.factors.push((base, exponent)),
+    }
+    Ok(())
+}
+
+fn negate(expr: Expr) -> Result<Expr, String> {
+    match expr {
+        Expr::Number(n) => Ok(number(-n)),
+        other => simplify(&neg(other)),
+    }
+}
+
+fn build_sum(terms: Vec<Term>) -> Expr {
+    let mut terms = terms.into_iter().filter(|t| t.coefficient != 0.0);
+    let first = match terms.next() {
+        Some(term) => build_term(term.coefficient, &term.factors),
+        None => return number(0.0),
+    };
+    terms.fold(first, |sum, term| {
+        if term.coefficient < 0.0 {
+            sub(sum, build_term(-term.coefficient, &term.factors))
+        } else {
+            add(sum, build_term(term.coefficient, &term.factors))
+        }
+    })
+}
+
+/// A product with negative powers written as a denominator; a coefficient such as 1/2
+/// becomes a divisor too, so `x*0.5` reads `x/2`.
+fn build_term(coefficient: f64, factors: &[(Expr, Expr)]) -> Expr {
+    let power = |base: &Expr, exponent: Expr| match exponent {
+        Expr::Number(1.0) => base.clone(),
+        exponent => pow(base.clone(), exponent),
+    };
+    let mut numerator = Vec::new();
+    let mut denominator = Vec::new();
+    for (base, exponent) in factors {
+        match exponent {
+            Expr::Number(n) if *n < 0.0 => denominator.push(power(base, number(-n))),
+            _ => numerator.push(power(base, exponent.clone())),
+        }
+    }
+
+    let reciprocal = 1.0 / coefficient.abs();
+    let (coefficient, divisor) = if coefficient.abs() < 1.0 && reciprocal.fract() == 0.0 {
+        (coefficient.signum(), reciprocal)
+    } else {
+        (coefficient, 1.0)
+    };
+
+    let numerator = match (coefficient, product(numerator)) {
+        (c, None) => number(c),
+        (1.0, Some(p)) => p,
+        (-1.0, Some(p)) => neg(p),
+        (c, Some(p)) => prepend(number(c), p),
+    };
+    let denominator = match (divisor, product(denominator)) {
+        (1.0, None) => return numerator,
+        (d, None) => number(d),
+        (1.0, Some(p)) => p,
+        (d, Some(p)) => prepend(number(d), p),
+    };
+    div(numerator, denominator)
+}
+
+fn product(factors: Vec<Expr>) -> Option<Expr> {
+    factors.into_iter().reduce(mul)
+}
+
+/// `first * rest`, keeping the product left-associated so a coefficient sits next to
+/// the first factor: 2x*sin(x).
+fn prepend(first: Expr, rest: Expr) -> Expr {
+    match rest {
+        Expr::Mul(a, b) => mul(prepend(first, *a), *b),
+        other => mul(first, other),
+    }
+}
+
+fn simplify_power(base: Expr, exponent: Expr) -> Result<Expr, String> {
+    Ok(match (&base, &exponent) {
+        (_, Expr::Number(n)) if *n == 0.0 => number(1.0),
+        (_, Expr::Number(n)) if *n == 1.0 => base,
+        (Expr::Number(b), _) if *b == 1.0 => number(1.0),
+        (Expr::Number(b), Expr::Number(n)) if *b == 0.0 && *n > 0.0 => number(0.0),
+        (Expr::Number(b), Expr::Number(n)) if *b == 0.0 && *n < 0.0 => return Err("Division by zero".to_string()),
+        // Whole powers of numbers are exact enough to fold; 2^0.5 stays as written
+        (Expr::Number(b), Expr::Number(n)) if n.fract() == 0.0 && b.powf(*n).is_finite() => number(b.powf(*n)),
+        (Expr::Neg(inner), Expr::Number(n)) if n.fract() == 0.0 => {
+            let power = simplify_power((**inner).clone(), exponent.clone())?;
+            if n % 2.0 == 0.0 { power } else { simplify(&neg(power))? }
+        }
+        // (u^a)^n = u^(an) holds for whole n
+        (Expr::Pow(inner, a), Expr::Number(n)) if n.fract() == 0.0 => {
+            simplify_power((**inner).clone(), simplify(&mul((**a).clone(), exponent.clone()))?)?
+        }
+        _ => pow(base, exponent),
+    })
+}
+
+fn simplify_call(name: &str, args: Vec<Expr>) -> Expr {
+    match (name, args.as_slice()) {
+        ("ln", [Expr::Symbol(e)]) if e == "e" => return number(1.0),
+        ("ln", [Expr::Call(inner, x)]) | ("exp", [Expr::Call(inner, x)])
+            if x.len() == 1 && ((name, inner.as_str()) == ("ln", "exp") || (name, inner.as_str()) == ("exp", "ln")) =>
+        {
+            return x[0].clone();
+        }
+        _ => {}
+    }
+    // Fold calls on numbers only when the result is whole, so sin(1) stays exact
+    let numbers: Option<Vec<f64>> = args.iter().map(|arg| match arg {
+        Expr::Number(n) => Some(*n),
+        _ => None,
+    }).collect();
+    if let Some(Ok(value)) = numbers.map(|n| apply_function(name, &n)) {
+        if value.is_finite() && value.fract() == 0.0 {
+            return number(value);
+        }
+    }
+    Expr::Call(name.to_string(), args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc_engine::tokenize;
+
+    fn expr(input: &str) -> Expr {
+        parse(&tokenize(input).unwrap(), 0).unwrap().0
+    }
+
+    fn diff(input: &str) -> Result<String, String> {
+        derivative(&expr(input), "x", 1).map(|d| d.to_string())
+    }
+
+    #[test]
+    fn constant_division_by_zero_is_an_error() {
+        for input in ["x/0", "0*x/0", "1/0", "0^-1"] {
+            assert_eq!(simplify(&expr(input)), Err("Division by zero".to_string()), "{}", input);
+        }
+    }
+
+    #[test]
+    fn several_argument_functions_differentiate() {
+        assert_eq!(diff("normcdf(x)").as_deref(), Ok("normpdf(x)"));
+        assert_eq!(diff("besselj(2, x)").as_deref(), Ok("(besselj(1, x) - besselj(3, x))/2"));
+        assert_eq!(diff("beta(x, 2)").as_deref(), Ok("beta(x, 2)*(digamma(x) - digamma(x + 2))"));
+        assert_eq!(diff("x r 2").as_deref(), Ok("x^(-1/2)/2"));
+        assert!(diff("min(x, 3)").is_ok());
+    }
+
+    #[test]
+    fn high_order_derivatives_are_bounded() {
+        for input in ["x^x", "tan(x)^x"] {
+            let start = std::time::Instant::now();
+            let result = derivative(&expr(input), "x", 20);
+            assert!(result.is_err_and(|e| e.contains("try a lower order")), "{}", input);
+            assert!(start.elapsed().as_secs() < 5, "{}", input);
+        }
+        assert!(derivative(&expr("x^x"), "x", 3).is_ok());
+        assert!(derivative(&expr("x^5"), "x", 20).is_ok());
+    }
+
+    #[test]
+    fn functions_without_a_rule_say_so() {
+        assert_eq!(diff("zeta(x)"), Err("No derivative rule for 'zeta'".to_string()));
+        assert_eq!(diff("digamma(x)"), Err("No derivative rule for 'digamma'".to_string()));
+    }
+}
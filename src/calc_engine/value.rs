@@ -0,0 +1,673 @@
+use super::complex::{self, Complex};
+use super::datetime::{format_duration, DateTime};
+use super::extended::Extended;
+use super::interval::Interval;
+use super::matrix::{self, Matrix};
+use super::regression::Fit;
+use super::symbolic::Expr;
+use std::fmt;
+
+/// Largest magnitude below which every integer is exactly representable as an `f64`.
+pub const MAX_EXACT_F64_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Rounding error, in units in the last place of the inputs, within which a result
+/// that should be exactly zero is taken to be zero.
+const NOISE_ULPS: f64 = 4.0;
+
+/// `x`, or 0 when it is no larger than the rounding error of a computation on inputs
+/// of magnitude `scale`, e.g. `sin(pi)`, which is 1.2e-16 only because π is rounded.
+pub fn clean_noise(x: f64, scale: f64) -> f64 {
+    clean_relative(x, scale, NOISE_ULPS * f64::EPSILON)
+}
+
+/// `x`, or 0 when it is within `tolerance` of zero relative to `scale`. Approximate
+/// methods such as ODE solvers and transforms pass the accuracy they work to.
+pub fn clean_relative(x: f64, scale: f64, tolerance: f64) -> f64 {
+    if x.abs() <= tolerance * scale.abs() { 0.0 } else { x }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    /// Exact integer; produced by integer literals and kept exact while it fits.
+    Integer(i128),
+    /// A number together with its prime factorisation as (prime, exponent) pairs.
+    Factors(i128, Vec<(u64, u32)>),
+    /// Several results at once, e.g. quotient and remainder from `divmod`.
+    Tuple(Vec<Value>),
+    Table(Table),
+    Date(DateTime),
+    /// Length of time in seconds.
+    Duration(f64),
+    /// Text shown as it is, such as a day name or an amount with its cents in a table.
+    Text(String),
+    /// A measurement and its standard uncertainty, e.g. `9.81 ± 0.02`.
+    Uncertain(f64, f64),
+    /// Rigorous enclosure of a result, produced in interval mode.
+    Interval(Interval),
+    /// A number beyond the range of `f64`, e.g. `fact(1000)`.
+    Extended(Extended),
+    /// A list of numbers such as `[1, 2, 3]`; a column in matrix products.
+    Vector(Vec<f64>),
+    Matrix(Matrix),
+    /// A complex number, such as a root of `x² + 1`.
+    Complex(Complex),
+    /// A list with complex entries, such as the spectrum from `fft`.
+    ComplexVector(Vec<Complex>),
+    /// A symbolic expression, such as a derivative from `diff`.
+    Expression(Expr),
+    /// A curve fitted to data, e.g. by `linreg`.
+    Fit(Fit),
+}
+
+/// Tabular result such as an amortization schedule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub title: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    /// Renders the header and rows as right-aligned columns.
+    pub fn lines(&self, format_cell: impl Fn(&Value) -> String) -> Vec<String> {
+        let cells: Vec<Vec<String>> = self.rows
+            .iter()
+            .map(|row| row.iter().map(&format_cell).collect())
+            .collect();
+
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in &cells {
+            for (i, cell) in row.iter().enumerate() {
+                if i < widths.len() {
+                    widths[i] = widths[i].max(cell.chars().count());
+                }
+            }
+        }
+
+        let format_row = |row: &[String]| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ")
+        };
+
+        let mut lines = vec![format_row(&self.headers)];
+        lines.extend(cells.iter().map(|row| format_row(row)));
+        lines
+    }
+}
+
+impl Value {
+    pub fn to_f64(&self) -> Result<f64, String> {
+        match self {
+            Value::Number(n) => Ok(*n),
+            Value::Integer(i) | Value::Factors(i, _) => Ok(*i as f64),
+            Value::Tuple(_) => Err("Expected a number, found a tuple".to_string()),
+            Value::Table(_) => Err("Expected a number, found a table".to_string()),
+            Value::Date(_) => Err("Expected a number, found a date".to_string()),
+            Value::Duration(_) => Err("Expected a number, found a duration".to_string()),
+            Value::Text(_) => Err("Expected a number, found text".to_string()),
+            Value::Uncertain(..) => Err("Expected an exact number, found an uncertain value".to_string()),
+            Value::Interval(_) => Err("Expected a number, found an interval".to_string()),
+            Value::Vector(_) => Err("Expected a number, found a vector".to_string()),
+            Value::Matrix(_) => Err("Expected a number, found a matrix".to_string()),
+            Value::Complex(_) => Err("Expected a real number, found a complex number".to_string()),
+            Value::ComplexVector(_) => Err("Expected a number, found a complex list".to_string()),
+            Value::Expression(_) => Err("Expected a number, found an expression".to_string()),
+            Value::Fit(_) => Err("Expected a number, found a fitted model".to_string()),
+            // Saturates to infinity or zero; strict mode reports what that leads to
+            Value::Extended(x) => Ok(x.to_f64()),
+        }
+    }
+
+    /// Central value and standard uncertainty; exact numbers have zero uncertainty.
+    pub fn center_and_uncertainty(&self) -> Result<(f64, f64), String> {
+        match self {
+            Value::Uncertain(center, uncertainty) => Ok((*center, *uncertainty)),
+            other => Ok((other.to_f64()?, 0.0)),
+        }
+    }
+
+    /// The exact integer value, if there is one.
+    pub fn to_integer(&self) -> Option<i128> {
+        match self {
+            Value::Integer(i) | Value::Factors(i, _) => Some(*i),
+            Value::Number(n) if n.fract() == 0.0 && n.abs() <= MAX_EXACT_F64_INTEGER => Some(*n as i128),
+            _ => None,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Value::Number(n) => *n == 0.0,
+            Value::Integer(i) | Value::Factors(i, _) => *i == 0,
+            Value::Duration(seconds) => *seconds == 0.0,
+            Value::Uncertain(center, _) => *center == 0.0,
+            Value::Interval(interval) => interval.lo == 0.0 && interval.hi == 0.0,
+            _ => false,
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Value::Number(n) => *n < 0.0,
+            Value::Integer(i) | Value::Factors(i, _) => *i < 0,
+            Value::Duration(seconds) => *seconds < 0.0,
+            Value::Uncertain(center, _) => *center < 0.0,
+            Value::Interval(interval) => interval.hi < 0.0,
+            Value::Extended(x) => x.is_negative(),
+            _ => false,
+        }
+    }
+
+    /// Integer value of an exact (integer-typed) operand; plain numbers take the `f64` path.
+    fn exact(&self) -> Option<i128> {
+        match self {
+            Value::Integer(i) | Value::Factors(i, _) => Some(*i),
+            _ => None,
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Number(n)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Integer(i) => write!(f, "{}", i),
+            Value::Factors(n, factors) => {
+                let mut parts = Vec::new();
+                if *n < 0 {
+                    parts.push("-1".to_string());
+                }
+                for (prime, exponent) in factors {
+                    if *exponent == 1 {
+                        parts.push(prime.to_string());
+                    } else {
+                        parts.push(format!("{}^{}", prime, exponent));
+                    }
+                }
+                if factors.is_empty() {
+                    parts.push("1".to_string());
+                }
+                write!(f, "{}", parts.join(" * "))
+            }
+            Value::Tuple(items) => {
+                let items = items.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+                write!(f, "({})", items.join(", "))
+            }
+            Value::Table(table) => {
+                write!(f, "{}", table.title)?;
+                for line in table.lines(|v| v.to_string()) {
+                    write!(f, "\n{}", line)?;
+                }
+                Ok(())
+            }
+            Value::Date(date) => write!(f, "{}", date),
+            Value::Duration(seconds) => write!(f, "{}", format_duration(*seconds)),
+            Value::Text(text) => write!(f, "{}", text),
+            Value::Uncertain(center, uncertainty) => write!(f, "{}", format_uncertain(*center, *uncertainty)),
+            Value::Interval(interval) => write!(f, "{}", interval),
+            Value::Extended(x) => write!(f, "{}", x),
+            Value::Vector(v) => write!(f, "{}", format_vector(v, ", ", &|x| x.to_string())),
+            Value::Matrix(m) => write!(f, "{}", format_matrix(m, ", ", &|x| x.to_string())),
+            Value::Complex(z) => write!(f, "{}", format_complex(*z, &|x| x.to_string())),
+            Value::ComplexVector(v) => write!(f, "{}", format_complex_vector(v, ", ", &|x| x.to_string())),
+            Value::Expression(expr) => write!(f, "{}", expr),
+            Value::Fit(fit) => write!(f, "{}", fit.render(&|x| x.to_string())),
+        }
+    }
+}
+
+/// A vector written inline, e.g. `[1, 2, 3]`.
+pub fn format_vector(values: &[f64], separator: &str, format_entry: &dyn Fn(f64) -> String) -> String {
+    format!("[{}]", values.iter().map(|&x| format_entry(x)).collect::<Vec<_>>().join(separator))
+}
+
+/// A complex list written inline, e.g. `[1 + 2i, 3, -i]`.
+pub fn format_complex_vector(values: &[Complex], separator: &str, format_part: &dyn Fn(f64) -> String) -> String {
+    format!("[{}]", values.iter().map(|&z| format_complex(z, format_part)).collect::<Vec<_>>().join(separator))
+}
+
+/// A matrix written inline, row by row, e.g. `[[1, 2], [3, 4]]`.
+pub fn format_matrix(m: &Matrix, separator: &str, format_entry: &dyn Fn(f64) -> String) -> String {
+    let rows = (0..m.rows).map(|i| format_vector(m.row(i), separator, format_entry)).collect::<Vec<_>>();
+    format!("[{}]", rows.join(separator))
+}
+
+/// A complex number as `a + bi`, `a - bi` or `bi`; parts that format as zero are left out.
+pub fn format_complex(z: Complex, format_part: &dyn Fn(f64) -> String) -> String {
+    let is_zero = |text: &str| text.trim_start_matches('-').chars().all(|c| matches!(c, '0' | '.' | ','));
+    let re = format_part(z.re);
+    let im = format_part(z.im.abs());
+    match (is_zero(&re), is_zero(&im)) {
+        (_, true) => re,
+        (true, false) => format!("{}{}i", if z.im < 0.0 { "-" } else { "" }, im),
+        (false, false) => format!("{} {} {}i", re, if z.im < 0.0 { '-' } else { '+' }, im),
+    }
+}
+
+/// Formats a measurement with the uncertainty rounded to two significant figures and
+/// the central value rounded to the same decimal place, e.g. `11.77 ± 0.49`.
+pub fn format_uncertain(center: f64, uncertainty: f64) -> String {
+    if uncertainty == 0.0 || !uncertainty.is_finite() || !center.is_finite() {
+        return format!("{} ± {}", center, uncertainty);
+    }
+
+    // An uncertainty below the precision of the center cannot set its last digit
+    if uncertainty < center.abs() * f64::EPSILON {
+        return format!("{:e} ± {:e}", center, uncertainty);
+    }
+
+    let magnitude = center.abs().max(uncertainty);
+    let exponent = magnitude.log10().floor() as i32;
+    if (magnitude >= 1e10 || uncertainty < 1e-5) && exponent != 0 {
+        // Factor out a common power of ten so both parts stay readable
+        let scale = 10f64.powi(exponent);
+        return format!("({})e{}", format_uncertain(center / scale, uncertainty / scale), exponent);
+    }
+
+    let last_digit = uncertainty.log10().floor() as i32 - 1;
+    let quantum = 10f64.powi(last_digit);
+    let round = |x: f64| (x / quantum).round() * quantum;
+    let decimals = (-last_digit).max(0) as usize;
+    format!("{:.*} ± {:.*}", decimals, round(center), decimals, round(uncertainty))
+}
+
+/// Builds `center ± uncertainty` from two exact numbers; in interval mode the
+/// tolerance widens the interval instead.
+pub fn plus_minus(center: &Value, uncertainty: &Value) -> Result<Value, String> {
+    if let Some((center, tolerance)) = intervals(center, uncertainty)? {
+        if tolerance.lo < 0.0 {
+            return Err("Uncertainty must be non-negative".to_string());
+        }
+        let tolerance = Interval { lo: -tolerance.hi, hi: tolerance.hi };
+        return Ok(Value::Interval(center.add(tolerance)));
+    }
+    if matches!(center, Value::Uncertain(..)) || matches!(uncertainty, Value::Uncertain(..)) {
+        return Err("± expects exact numbers on both sides".to_string());
+    }
+    if matches!(center, Value::Extended(_)) || matches!(uncertainty, Value::Extended(_)) {
+        return Err("± works on values within the range of f64 (about 1.8e308)".to_string());
+    }
+    let (center, uncertainty) = (center.to_f64()?, uncertainty.to_f64()?);
+    if uncertainty < 0.0 {
+        return Err("Uncertainty must be non-negative".to_string());
+    }
+    Ok(Value::Uncertain(center, uncertainty))
+}
+
+/// Both operands as intervals when either of them is one.
+fn intervals(a: &Value, b: &Value) -> Result<Option<(Interval, Interval)>, String> {
+    if matches!(a, Value::Interval(_)) || matches!(b, Value::Interval(_)) {
+        Ok(Some((Interval::from_value(a)?, Interval::from_value(b)?)))
+    } else {
+        Ok(None)
+    }
+}
+
+fn is_uncertain(a: &Value, b: &Value) -> bool {
+    matches!(a, Value::Uncertain(..)) || matches!(b, Value::Uncertain(..))
+}
+
+/// First-order propagation for `f(x, y)` given its partial derivatives at the centers.
+fn propagate(a: &Value, b: &Value, f: impl Fn(f64, f64) -> (f64, f64, f64)) -> Result<Value, String> {
+    let (x, dx) = a.center_and_uncertainty()?;
+    let (y, dy) = b.center_and_uncertainty()?;
+    let (result, df_dx, df_dy) = f(x, y);
+    let uncertainty = if dy == 0.0 {
+        (df_dx * dx).abs()
+    } else if dx == 0.0 {
+        (df_dy * dy).abs()
+    } else {
+        (df_dx * dx).hypot(df_dy * dy)
+    };
+    Ok(Value::Uncertain(result, uncertainty))
+}
+
+/// Floating-point arithmetic that moves to the wide-exponent type when the result
+/// leaves the range of `f64` or an operand is already there.
+fn widen(
+    a: &Value,
+    b: &Value,
+    f: impl Fn(f64, f64) -> f64,
+    wide: impl Fn(Extended, Extended) -> Extended,
+    may_underflow: bool,
+) -> Result<Value, String> {
+    if !matches!(a, Value::Extended(_)) && !matches!(b, Value::Extended(_)) {
+        let (x, y) = (a.to_f64()?, b.to_f64()?);
+        let result = f(x, y);
+        if !out_of_range(x, y, result, may_underflow) {
+            return Ok(Value::Number(result));
+        }
+    }
+    wide(Extended::from_value(a)?, Extended::from_value(b)?).into_value()
+}
+
+/// Whether finite operands gave an infinite result, or nonzero ones a result too small
+/// for full precision.
+fn out_of_range(x: f64, y: f64, result: f64, may_underflow: bool) -> bool {
+    if !x.is_finite() || !y.is_finite() {
+        return false;
+    }
+    result.is_infinite() || (may_underflow && x != 0.0 && y != 0.0 && (result == 0.0 || result.is_subnormal()))
+}
+
+pub fn add(a: &Value, b: &Value) -> Result<Value, String> {
+    match (a, b) {
+        (Value::Date(date), Value::Duration(seconds)) | (Value::Duration(seconds), Value::Date(date)) => {
+            return Ok(Value::Date(date.plus_seconds(*seconds)?));
+        }
+        (Value::Duration(x), Value::Duration(y)) => return Ok(Value::Duration(x + y)),
+        (Value::Date(_), _) | (_, Value::Date(_)) => {
+            return Err("Only durations can be added to a date (e.g., 3d or 4h)".to_string());
+        }
+        (Value::Duration(_), _) | (_, Value::Duration(_)) => {
+            return Err("Durations can only be added to durations or dates".to_string());
+        }
+        _ => {}
+    }
+    if let Some(result) = matrix::binary('+', a, b)? {
+        return Ok(result);
+    }
+    if let Some(result) = complex::binary('+', a, b)? {
+        return Ok(result);
+    }
+    if let Some((x, y)) = intervals(a, b)? {
+        return Ok(Value::Interval(x.add(y)));
+    }
+    if is_uncertain(a, b) {
+        return propagate(a, b, |x, y| (x + y, 1.0, 1.0));
+    }
+    if let (Some(x), Some(y)) = (a.exact(), b.exact()) {
+        if let Some(sum) = x.checked_add(y) {
+            return Ok(Value::Integer(sum));
+        }
+    }
+    widen(a, b, |x, y| x + y, Extended::add, false)
+}
+
+pub fn sub(a: &Value, b: &Value) -> Result<Value, String> {
+    match (a, b) {
+        (Value::Date(x), Value::Date(y)) => return Ok(Value::Duration((x.timestamp - y.timestamp) as f64)),
+        (Value::Date(date), Value::Duration(seconds)) => return Ok(Value::Date(date.plus_seconds(-seconds)?)),
+        (Value::Duration(x), Value::Duration(y)) => return Ok(Value::Duration(x - y)),
+        (Value::Date(_), _) => {
+            return Err("Only durations or dates can be subtracted from a date".to_string());
+        }
+        (Value::Duration(_), _) | (_, Value::Duration(_)) => {
+            return Err("Durations can only be combined with durations or dates".to_string());
+        }
+        _ => {}
+    }
+    if let Some(result) = matrix::binary('-', a, b)? {
+        return Ok(result);
+    }
+    if let Some(result) = complex::binary('-', a, b)? {
+        return Ok(result);
+    }
+    if let Some((x, y)) = intervals(a, b)? {
+        return Ok(Value::Interval(x.sub(y)));
+    }
+    if is_uncertain(a, b) {
+        return propagate(a, b, |x, y| (x - y, 1.0, -1.0));
+    }
+    if let (Some(x), Some(y)) = (a.exact(), b.exact()) {
+        if let Some(difference) = x.checked_sub(y) {
+            return Ok(Value::Integer(difference));
+        }
+    }
+    widen(a, b, |x, y| x - y, Extended::sub, false)
+}
+
+pub fn mul(a: &Value, b: &Value) -> Result<Value, String> {
+    match (a, b) {
+        (Value::Duration(seconds), factor) | (factor, Value::Duration(seconds))
+            if !matches!(factor, Value::Duration(_)) =>
+        {
+            return Ok(Value::Duration(seconds * factor.to_f64()?));
+        }
+        _ => {}
+    }
+    if let Some(result) = matrix::binary('*', a, b)? {
+        return Ok(result);
+    }
+    if let Some(result) = complex::binary('*', a, b)? {
+        return Ok(result);
+    }
+    if let Some((x, y)) = intervals(a, b)? {
+        return Ok(Value::Interval(x.mul(y)));
+    }
+    if is_uncertain(a, b) {
+        return propagate(a, b, |x, y| (x * y, y, x));
+    }
+    if let (Some(x), Some(y)) = (a.exact(), b.exact()) {
+        if let Some(product) = x.checked_mul(y) {
+            return Ok(Value::Integer(product));
+        }
+    }
+    widen(a, b, |x, y| x * y, Extended::mul, true)
+}
+
+pub fn div(a: &Value, b: &Value) -> Result<Value, String> {
+    if b.is_zero() {
+        return Err("Division by zero".to_string());
+    }
+    match (a, b) {
+        (Value::Duration(x), Value::Duration(y)) => return Ok(Value::Number(x / y)),
+        (Value::Duration(seconds), divisor) => return Ok(Value::Duration(seconds / divisor.to_f64()?)),
+        _ => {}
+    }
+    if let Some(result) = matrix::binary('/', a, b)? {
+        return Ok(result);
+    }
+    if let Some(result) = complex::binary('/', a, b)? {
+        return Ok(result);
+    }
+    if let Some((x, y)) = intervals(a, b)? {
+        return Ok(Value::Interval(x.div(y)?));
+    }
+    if is_uncertain(a, b) {
+        return propagate(a, b, |x, y| (x / y, 1.0 / y, -x / (y * y)));
+    }
+    if let (Some(x), Some(y)) = (a.exact(), b.exact()) {
+        if x.checked_rem(y) == Some(0) {
+            return Ok(Value::Integer(x / y));
+        }
+    }
+    widen(a, b, |x, y| x / y, Extended::div, true)
+}
+
+/// Which sign a remainder takes when the operands differ in sign.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ModuloKind {
+    /// `mod`: takes the sign of the divisor, so `-7 mod 3 = 2`.
+    #[default]
+    Floored,
+    /// `rem` and `fmod`: takes the sign of the dividend, so `-7 rem 3 = -1`.
+    Truncated,
+    /// `emod`: never negative, so `-7 emod -3 = 2`.
+    Euclidean,
+}
+
+impl ModuloKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ModuloKind::Floored => "mod",
+            ModuloKind::Truncated => "rem",
+            ModuloKind::Euclidean => "emod",
+        }
+    }
+
+    /// The quotient that goes with this kind of remainder.
+    fn quotient(self, x: f64, y: f64) -> f64 {
+        match self {
+            ModuloKind::Truncated => (x / y).trunc(),
+            ModuloKind::Floored => (x / y).floor(),
+            ModuloKind::Euclidean => if y > 0.0 { (x / y).floor() } else { (x / y).ceil() },
+        }
+    }
+
+    fn apply_f64(self, x: f64, y: f64) -> f64 {
+        let r = x % y;
+        match self {
+            ModuloKind::Truncated => r,
+            ModuloKind::Floored if r != 0.0 && (r < 0.0) != (y < 0.0) => r + y,
+            ModuloKind::Floored => r,
+            ModuloKind::Euclidean => x.rem_euclid(y),
+        }
+    }
+
+    fn apply_i128(self, x: i128, y: i128) -> i128 {
+        // Only i128::MIN by -1 overflows, and its remainder is 0
+        let r = x.checked_rem(y).unwrap_or(0);
+        match self {
+            ModuloKind::Truncated => r,
+            ModuloKind::Floored if r != 0 && (r < 0) != (y < 0) => r + y,
+            ModuloKind::Floored => r,
+            // r - y rather than r + |y|, since |i128::MIN| does not fit
+            ModuloKind::Euclidean if r < 0 => if y < 0 { r - y } else { r + y },
+            ModuloKind::Euclidean => r,
+        }
+    }
+}
+
+/// Remainder of `a / b`; exact for integers and computed without truncating reals.
+pub fn modulo(a: &Value, b: &Value, kind: ModuloKind) -> Result<Value, String> {
+    if intervals(a, b)?.is_some() {
+        return Err(format!("{} is not available in interval mode", kind.name()));
+    }
+    if b.is_zero() {
+        return Err("Division by zero".to_string());
+    }
+    if let (Value::Duration(x), Value::Duration(y)) = (a, b) {
+        return Ok(Value::Duration(kind.apply_f64(*x, *y)));
+    }
+    if matches!(a, Value::Extended(_)) || matches!(b, Value::Extended(_)) {
+        return Err(format!("{} is not available beyond the range of f64 (about 1.8e308)", kind.name()));
+    }
+    if is_uncertain(a, b) {
+        // The remainder moves one-for-one with the dividend between jumps
+        let (x, y) = (a.center_and_uncertainty()?.0, b.center_and_uncertainty()?.0);
+        let quotient = kind.quotient(x, y);
+        return propagate(a, b, |x, y| (kind.apply_f64(x, y), 1.0, -quotient));
+    }
+    if let (Some(x), Some(y)) = (a.exact(), b.exact()) {
+        return Ok(Value::Integer(kind.apply_i128(x, y)));
+    }
+    let (x, y) = (a.to_f64()?, b.to_f64()?);
+    // Past 2^53 a float has already lost the low digits that decide the remainder
+    if let Some(inexact) = [x, y].into_iter().find(|v| v.abs() > MAX_EXACT_F64_INTEGER) {
+        return Err(format!(
+            "{} needs exact operands, but {:e} is beyond 2^53 where floats skip whole numbers; \
+             use modpow(b, e, m) or mod m {{ ... }}",
+            kind.name(),
+            inexact
+        ));
+    }
+    Ok(Value::Number(kind.apply_f64(x, y)))
+}
+
+pub fn pow(base: &Value, exponent: &Value) -> Result<Value, String> {
+    if let Some(result) = matrix::binary('^', base, exponent)? {
+        return Ok(result);
+    }
+    if let Some(result) = complex::binary('^', base, exponent)? {
+        return Ok(result);
+    }
+    if let Some((x, y)) = intervals(base, exponent)? {
+        return Ok(Value::Interval(x.pow(y)?));
+    }
+    if is_uncertain(base, exponent) {
+        let (x, _) = base.center_and_uncertainty()?;
+        let (_, dy) = exponent.center_and_uncertainty()?;
+        if dy > 0.0 && x <= 0.0 {
+            return Err("Uncertain exponents need a positive base".to_string());
+        }
+        return propagate(base, exponent, |x, y| {
+            let power = x.powf(y);
+            let df_dx = if y == 0.0 { 0.0 } else { y * x.powf(y - 1.0) };
+            let df_dy = if dy > 0.0 { power * x.ln() } else { 0.0 };
+            (power, df_dx, df_dy)
+        });
+    }
+    if let (Some(x), Some(y)) = (base.exact(), exponent.exact()) {
+        if let Some(power) = u32::try_from(y).ok().and_then(|y| x.checked_pow(y)) {
+            return Ok(Value::Integer(power));
+        }
+    }
+    let y = exponent.to_f64()?;
+    match base {
+        Value::Extended(x) => x.powf(y)?.into_value(),
+        _ => {
+            let x = base.to_f64()?;
+            let power = x.powf(y);
+            if out_of_range(x, y, power, true) {
+                return Extended::from_f64(x).powf(y)?.into_value();
+            }
+            Ok(Value::Number(power))
+        }
+    }
+}
+
+pub fn neg(a: &Value) -> Result<Value, String> {
+    match a {
+        Value::Duration(seconds) => return Ok(Value::Duration(-seconds)),
+        Value::Uncertain(center, uncertainty) => return Ok(Value::Uncertain(-center, *uncertainty)),
+        Value::Interval(interval) => return Ok(Value::Interval(interval.neg())),
+        Value::Extended(x) => return Ok(Value::Extended(x.neg())),
+        Value::Vector(_) | Value::Matrix(_) => return Ok(matrix::neg(a)),
+        Value::Complex(z) => return Ok(Value::Complex(z.neg())),
+        _ => {}
+    }
+    if let Some(x) = a.exact() {
+        if let Some(negated) = x.checked_neg() {
+            return Ok(Value::Integer(negated));
+        }
+    }
+    Ok(Value::Number(-a.to_f64()?))
+}
+
+pub fn abs(a: &Value) -> Result<Value, String> {
+    if a.is_negative() { neg(a) } else { Ok(a.clone()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_is_relative_to_the_inputs() {
+        assert_eq!(clean_noise(std::f64::consts::PI.sin(), std::f64::consts::PI), 0.0);
+        assert_eq!(clean_noise(1e-20_f64.sin(), 1e-20), 1e-20);
+        assert_eq!(clean_noise(1e-10, 1.0), 1e-10);
+        assert_eq!(clean_noise(6.626e-34, 0.0), 6.626e-34);
+    }
+
+    #[test]
+    fn remainders_at_the_i128_boundary() {
+        let (min, max) = (Value::Integer(i128::MIN), Value::Integer(i128::MAX));
+        let minus_one = Value::Integer(-1);
+        assert_eq!(modulo(&minus_one, &min, ModuloKind::Euclidean), Ok(max.clone()));
+        assert_eq!(modulo(&minus_one, &min, ModuloKind::Floored), Ok(minus_one.clone()));
+        assert_eq!(modulo(&minus_one, &min, ModuloKind::Truncated), Ok(minus_one.clone()));
+        assert_eq!(modulo(&min, &minus_one, ModuloKind::Euclidean), Ok(Value::Integer(0)));
+        assert_eq!(modulo(&min, &max, ModuloKind::Euclidean), Ok(Value::Integer(i128::MAX - 1)));
+    }
+
+    #[test]
+    fn remainders_of_inexact_floats_are_refused() {
+        let big = pow(&Value::Integer(3), &Value::Integer(100)).unwrap();
+        assert!(modulo(&big, &Value::Integer(1_000_000_007), ModuloKind::Floored).is_err());
+        let exact = pow(&Value::Integer(2), &Value::Integer(100)).unwrap();
+        assert_eq!(modulo(&exact, &Value::Integer(7), ModuloKind::Floored), Ok(Value::Integer(2)));
+        assert_eq!(modulo(&Value::Number(10.5), &Value::Integer(3), ModuloKind::Floored), Ok(Value::Number(1.5)));
+    }
+}
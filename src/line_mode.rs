@@ -1,7 +1,7 @@
-use crate::calc_engine::{tokenize, Parser, EvaluationTrace};
+use crate::calc_engine::{tokenize, Parser, EvaluationTrace, Settings};
 use anyhow::Result;
 
-pub fn evaluate_expression(expression: &str) -> Result<()> {
+pub fn evaluate_expression(expression: &str, settings: Settings) -> Result<()> {
     let tokens = match tokenize(expression) {
         Ok(t) => t,
         Err(e) => {
@@ -10,12 +10,25 @@ pub fn evaluate_expression(expression: &str) -> Result<()> {
         }
     };
 
-    let mut parser = Parser::new(tokens);
+    let display = settings.display.clone();
+    let mut parser = Parser::new(tokens, settings);
     let mut trace = EvaluationTrace::new(false); // No detailed trace for line mode
 
     match parser.parse(&mut trace) {
         Ok(result) => {
-            println!("{}", result);
+            match display.annotation(&result) {
+                Some(annotation) => println!("{} {}", display.format_result(&result), annotation),
+                None => println!("{}", display.format_result(&result)),
+            }
+            for line in display.block_lines(&result) {
+                println!("{}", line);
+            }
+            // There is no way to expand a result afterwards, so long lists are listed in full
+            if display.is_compacted(&result) {
+                for line in display.list_lines(&result) {
+                    println!("{}", line);
+                }
+            }
         }
         Err(e) => {
             eprintln!("Error evaluating expression: {}", e);
@@ -5,6 +5,7 @@ mod line_mode;
 mod render_help; // Declare render_help as a module
 
 use anyhow::Result;
+use calc_engine::Settings;
 use std::env;
 
 fn print_help() {
@@ -12,8 +13,20 @@ fn print_help() {
     println!("Usage: rustcalc [OPTION] [EXPRESSION]");
     println!();
     println!("Options:");
-    println!("  --tui, -t    Run in TUI mode");
-    println!("  --help, -h   Show this help");
+    println!("  --tui, -t        Run in TUI mode");
+    println!("  --interval, -i   Evaluate with interval arithmetic (guaranteed bounds)");
+    println!("  --help, -h       Show this help");
+    println!();
+    println!("Display and evaluation options (before the expression):");
+    println!("  --decimals N             Show exactly N decimal places");
+    println!("  --sig N                  Show N significant figures");
+    println!("  --notation auto|sci|eng|si  Number notation (si uses prefixes such as 4.7k)");
+    println!("  --thousands on|off       Group digits with thousands separators");
+    println!("  --decimal point|comma    Decimal mark");
+    println!("  --percent percent|mod|rem|emod|fmod  Meaning of %, percent by default");
+    println!("  --strict on|off          Report overflow, underflow and NaN as errors (on by default)");
+    println!("  --seed N                 Seed random numbers so results can be repeated");
+    println!("  --modulus P              Evaluate + - * / ^ in the integers mod P");
     println!("\nIf no options are provided, or if an expression is given directly, it will be evaluated.");
 }
 
@@ -37,9 +50,28 @@ fn main() -> Result<()> {
                     Ok(())
                 }
                 _ => {
-                    // Treat remaining arguments as an expression
-                    let expression = args[1..].join(" ");
-                    line_mode::evaluate_expression(&expression)
+                    // Leading options configure the evaluation; the rest is the expression
+                    let mut settings = Settings { strict: true, ..Settings::default() };
+                    let mut rest = &args[1..];
+                    while let Some(option) = rest.first().map(|s| s.as_str()) {
+                        match option {
+                            "--interval" | "-i" => {
+                                settings.interval = true;
+                                rest = &rest[1..];
+                            }
+                            _ if option.starts_with("--") && Settings::is_option(&option[2..]) => {
+                                let name = &option[2..];
+                                let value = rest.get(1).map(|s| s.as_str()).unwrap_or("");
+                                if let Err(e) = settings.set(name, value) {
+                                    eprintln!("Error: {}", e);
+                                    return Ok(());
+                                }
+                                rest = &rest[2.min(rest.len())..];
+                            }
+                            _ => break,
+                        }
+                    }
+                    line_mode::evaluate_expression(&rest.join(" "), settings)
                 }
             }
         }
@@ -25,9 +25,24 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
         Line::from("  - : Subtraction     (e.g., 10 - 4 = 6)"),
         Line::from("  * : Multiplication  (e.g., 6 * 7 = 42)"),
         Line::from("  / : Division        (e.g., 15 / 3 = 5)"),
-        Line::from("  % : Modulo          (e.g., 10 % 3 = 1)"),
+        Line::from("  mod : Modulo, sign of divisor  (e.g., -7 mod 3 = 2, 5.5 mod 2 = 1.5)"),
+        Line::from("  rem : Remainder, sign of dividend (e.g., -7 rem 3 = -1)"),
         Line::from("  ^ : Exponentiation  (e.g., 2 ^ 3 = 8)"),
         Line::from("  r : Root            (e.g., 8 r 3 = 2)"),
+        Line::from("  ± : Uncertainty     (e.g., 9.81 ± 0.02, also typed as +-)"),
+        Line::from(""),
+        Line::from(Span::styled("Percentages:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  x%             : x / 100        (e.g., 80 * 15% = 12)"),
+        Line::from("  a + x%, a - x% : Add or subtract x% of a (e.g., 200 + 15% = 230)"),
+        Line::from("  x% of y        : x percent of y (e.g., 50% of 80 = 40)"),
+        Line::from("  x as % of y    : y's share that x is (e.g., 30 as % of 120 = 25)"),
+        Line::from("  pctchange(old, new) : Percent change (e.g., pctchange(80, 100) = 25)"),
+        Line::from("  % is percent by default: 10 % 3 is an error, write 10 mod 3,"),
+        Line::from("  or make % a remainder with set percent mod|rem|emod|fmod."),
+        Line::from("  Only a bare percentage after + or - is taken of the left side;"),
+        Line::from("  200 + 2 * 15% is 200.3, and 10% + 5% is 0.15."),
+        Line::from("      Uncertainties propagate through operators and functions;"),
+        Line::from("      results are rounded to what the uncertainty supports"),
         Line::from(""),
         Line::from(Span::styled("Functions:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
         Line::from("  sin(x)   : Sine (x in radians)"),
@@ -44,6 +59,8 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
         Line::from("  ceil(x)  : Round up to nearest integer"),
         Line::from("  round(x) : Round to nearest integer"),
         Line::from("  sqrt(x)  : Square root"),
+        Line::from("  emod(x, y) : Euclidean modulo, never negative"),
+        Line::from("  fmod(x, y) : Real remainder with the sign of x (like C's fmod)"),
         Line::from(""),
         Line::from(Span::styled("Hyperbolic Functions:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
         Line::from("  sinh(x)  : Hyperbolic sine"),
@@ -54,14 +71,158 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
         Line::from("  atanh(x) : Inverse hyperbolic tangent (|x| < 1)"),
         Line::from(""),
         Line::from(Span::styled("Combinatorics:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
-        Line::from("  fact(n) or factorial(n) : Factorial (n integer >=0)"),
+        Line::from("  fact(n) or factorial(n) : Factorial (real n via gamma(n + 1))"),
         Line::from("  perm(n, k) or npr(n, k) : Permutations (n choose k)"),
         Line::from("  comb(n, k) or ncr(n, k) : Combinations (n choose k)"),
+        Line::from("  Huge results keep a wide exponent: fact(1000) = 4.0238726e2567"),
+        Line::from(""),
+        Line::from(Span::styled("Special Functions:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  gamma(x), lgamma(x) : Gamma function, log of |gamma(x)|"),
+        Line::from("  beta(a,b) : Beta function (a, b > 0)"),
+        Line::from("  erf(x), erfc(x) : Error function and its complement"),
+        Line::from("  digamma(x) or psi(x) : Digamma function"),
+        Line::from("  besselj(n,x), bessely(n,x) : Bessel functions, integer order n"),
+        Line::from("  zeta(s) : Riemann zeta function (s != 1)"),
+        Line::from("  lambertw(x[,branch]) : Lambert W, branch 0 or -1 (x >= -1/e)"),
         Line::from(""),
         Line::from(Span::styled("Statistical:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
         Line::from("  mean(a,b,...) : Arithmetic mean"),
         Line::from("  median(a,b,...) : Median"),
-        Line::from("  stdev(a,b,...) or stddev(a,b,...) : Standard deviation"),
+        Line::from("  stdev(a,b,...) or stddev(a,b,...) : Standard deviation (sample)"),
+        Line::from("  stdevs(a,b,...) / stdevp(a,b,...) : Sample / population standard deviation"),
+        Line::from("  var(a,b,...) or vars(a,b,...) : Sample variance"),
+        Line::from("  varp(a,b,...) : Population variance"),
+        Line::from("  mode(a,b,...) : Most frequent value (smallest on ties)"),
+        Line::from("  min(a,b,...) / max(a,b,...) : Smallest / largest value"),
+        Line::from("  range(a,b,...) : max - min"),
+        Line::from("  percentile(p,a,b,...) : p-th percentile (0 <= p <= 100)"),
+        Line::from("  quartile(q,a,b,...) : q-th quartile (q = 0..4)"),
+        Line::from("  iqr(a,b,...) : Interquartile range"),
+        Line::from("  geomean(a,b,...) : Geometric mean (positive values)"),
+        Line::from("  harmean(a,b,...) : Harmonic mean (positive values)"),
+        Line::from("  wmean(x1,w1,x2,w2,...) : Weighted mean"),
+        Line::from("  skew(a,b,...) : Sample skewness (at least 3 values)"),
+        Line::from("  kurt(a,b,...) : Excess kurtosis (at least 4 values)"),
+        Line::from("  zscore(x,a,b,...) : z-score of x within a,b,..."),
+        Line::from("  linreg(xs, ys) : Line y = ax + b with r², e.g. linreg([1,2,3], [2,4,7])"),
+        Line::from("  expfit, logfit, powfit(xs, ys) : y = a*exp(bx), a + b*ln(x), a*x^b"),
+        Line::from("  predict(fit, x) : Fitted model at x (or a list of x)"),
+        Line::from("  corr(xs, ys), cov(xs, ys) : Pearson correlation, sample covariance"),
+        Line::from(""),
+        Line::from(Span::styled("Probability Distributions:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  normpdf/normcdf(x[,mu,sigma]), invnorm(p[,mu,sigma]) : Normal"),
+        Line::from("  tpdf/tcdf(x,df), invt(p,df) : Student t"),
+        Line::from("  chi2pdf/chi2cdf(x,df), invchi2(p,df) : Chi-squared"),
+        Line::from("  binompdf/binomcdf(k,n,p), invbinom(q,n,p) : Binomial"),
+        Line::from("  poisspdf/poisscdf(k,lambda), invpoiss(q,lambda) : Poisson"),
+        Line::from("  exppdf/expcdf(x,lambda), invexp(p,lambda) : Exponential"),
+        Line::from("  unifpdf/unifcdf(x,a,b), invunif(p,a,b) : Uniform on [a, b]"),
+        Line::from(""),
+        Line::from(Span::styled("Number Theory:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  gcd(a,b,...) / lcm(a,b,...) : Greatest common divisor / least common multiple"),
+        Line::from("  isprime(n) : 1 if n is prime, otherwise 0"),
+        Line::from("  nextprime(n) : Smallest prime greater than n"),
+        Line::from("  factor(n) : Prime factorisation (e.g., 2^3 * 5)"),
+        Line::from("  totient(n) or phi(n) : Euler's totient"),
+        Line::from("  modpow(b,e,m) : b^e mod m"),
+        Line::from("  modinv(a,m) : Modular inverse of a mod m"),
+        Line::from("  egcd(a,b) : (g, x, y) with a*x + b*y = g"),
+        Line::from("  idiv(a,b) / divmod(a,b) : Floored quotient / (quotient, remainder)"),
+        Line::from("  Integer literals are exact; number theory works on integers below 2^64"),
+        Line::from("  mod p { expr } : + - * / ^ in the integers mod p, e.g. mod 7 { 3 / 4 } = 6"),
+        Line::from("  inverse(x) : Inverse of x inside a mod block; set modulus p|off for a session"),
+        Line::from(""),
+        Line::from(Span::styled("Matrices and Vectors:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  [1, 2, 3] : Vector;  [[1, 2], [3, 4]] : Matrix, row by row"),
+        Line::from("  A + B, A - B, A * B, A * v, 2 * A, A / 2, A^n : Matrix arithmetic (A^-1 inverts)"),
+        Line::from("  transpose(A), det(A), inv(A), rank(A), trace(A) : Basic matrix functions"),
+        Line::from("  solve(A, b) : Solve A x = b (least squares for tall A)"),
+        Line::from("  lu(A) : (L, U, P) with P A = L U;  qr(A) : (Q, R) with A = Q R"),
+        Line::from("  eig(A) : Real eigenvalues of a square matrix"),
+        Line::from("  dot(u, v), cross(u, v), norm(v) : Vector products and length"),
+        Line::from("  identity(n) : n×n identity matrix"),
+        Line::from(""),
+        Line::from(Span::styled("Polynomials:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  Coefficients run from the highest power: [1, -3, 2] is x² - 3x + 2"),
+        Line::from("  polyroots(1, -3, 2) or polyroots([1, -3, 2]) : All roots, complex as a ± bi"),
+        Line::from("  quadratic(a,b,c), cubic(a,b,c,d) : Closed-form roots"),
+        Line::from("  polyval(p, x) : p at x (x may be a vector)"),
+        Line::from("  polyfit(xs, ys, deg) : Least-squares coefficients"),
+        Line::from("  polyexpand(r1, r2, ...) : Coefficients of (x - r1)(x - r2)...;  polymul(p, q)"),
+        Line::from(""),
+        Line::from(Span::styled("Symbolic:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  diff(f, x) : Derivative of f with respect to x, as an expression"),
+        Line::from("  diff(f, x, n) : nth derivative"),
+        Line::from("  simplify(f) : Fold constants and collect like terms"),
+        Line::from("  A number before a name multiplies: 2x, 3x^2"),
+        Line::from(""),
+        Line::from(Span::styled("Differential Equations:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  ode(f, y0, t0, t1) : y(t1) for y' = f(t, y), y(t0) = y0, by adaptive RK45"),
+        Line::from("  ode(f, y0, t0, t1, n) : Table of y at n evenly spaced times"),
+        Line::from("  rk4(f, y0, t0, t1[, steps]) : Classic RK4 with fixed steps (default 1000)"),
+        Line::from("  Systems: ode([y2, -y1], [0, 1], 0, pi) with variables y1, y2, ..."),
+        Line::from(""),
+        Line::from(Span::styled("Optimization:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  minimize(f, x, lo, hi) : (x, f(x)) at the minimum on [lo, hi] (Brent)"),
+        Line::from("  minimize(f, [x, y], [x0, y0]) : ([x, y], f) from a starting point (Nelder-Mead)"),
+        Line::from("  maximize(...) : The same for the maximum"),
+        Line::from(""),
+        Line::from(Span::styled("Signal Processing:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  fft(list), ifft(list) : Discrete Fourier transform and its inverse (any length)"),
+        Line::from("  magnitude(z), phase(z) : |z| and angle in radians, for each entry of a list"),
+        Line::from("  convolve(a, b) : Full convolution, e.g. convolve([1,2,3], [0,1,0.5])"),
+        Line::from("  movavg(list, n) : Moving average over windows of n values"),
+        Line::from("  hann(n), hamming(n), blackman(n) : Window of length n, or applied to a list"),
+        Line::from("  Lists over 8 values show their ends; Ctrl+E lists the selected one in full"),
+        Line::from(""),
+        Line::from(Span::styled("Boolean Logic:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  Formulas use variables, 0/1, and, or, xor, not, implies, iff and parentheses"),
+        Line::from("  truthtable(f, g, ...) : Table of every assignment (up to 10 variables)"),
+        Line::from("  sat(f) : A satisfying assignment, or Unsatisfiable (up to 20 variables)"),
+        Line::from("  equiv(f, g) : Equivalent, or an assignment where f and g differ"),
+        Line::from(""),
+        Line::from(Span::styled("Chemistry:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  Formulas go in quotes: \"Ca(OH)2\", \"K4[Fe(CN)6]\", hydrates \"CuSO4*5H2O\""),
+        Line::from("  molar(f) : Molar mass in g/mol"),
+        Line::from("  composition(f) : Atoms, mass and mass % of each element"),
+        Line::from("  moles(g, f), mass(mol, f) : Convert between grams and moles"),
+        Line::from("  molarity(g, f, L) : mol/L of g grams in L litres"),
+        Line::from("  solute(mol/L, L, f) : Grams needed for a solution"),
+        Line::from("  dilute(c1, V1, c2) : Final volume V2 from c1·V1 = c2·V2"),
+        Line::from("  A molar mass in g/mol may be given in place of a formula"),
+        Line::from(""),
+        Line::from(Span::styled("Random Numbers:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  rand() : Uniform on [0, 1)"),
+        Line::from("  randint(a, b) : Whole number from a to b inclusive"),
+        Line::from("  randn([mu, sigma]) : Normally distributed (standard normal by default)"),
+        Line::from("  choice(list) : One entry of a list, e.g. choice([1, 5, 10])"),
+        Line::from("  3d6 + 2, d20 : Dice rolls (a number of dice, d, sides)"),
+        Line::from("  simulate(n, expr) : Mean ± standard error of expr over n random trials"),
+        Line::from("  seed N / seed off : Repeatable draws from seed N / unpredictable draws"),
+        Line::from(""),
+        Line::from(Span::styled("Finance:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  Money paid out is negative, money received is positive"),
+        Line::from("  pmt(rate,n,pv[,fv]) : Payment per period"),
+        Line::from("  fv(rate,n,pmt[,pv]) / pv(rate,n,pmt[,fv]) : Future / present value"),
+        Line::from("  nper(rate,pmt,pv[,fv]) : Number of periods"),
+        Line::from("  rate(n,pmt,pv[,fv]) : Interest rate per period (solved iteratively)"),
+        Line::from("  npv(rate,c1,c2,...) : Net present value of cash flows"),
+        Line::from("  irr(c0,c1,...) : Internal rate of return"),
+        Line::from("  compound(p,r,n,t) : p compounded n times a year at rate r for t years"),
+        Line::from("  amort(rate,n,pv) : Amortization schedule table"),
+        Line::from(""),
+        Line::from(Span::styled("Dates and Durations:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
+        Line::from("  2026-10-16, 2026-10-16T14:30, 2026-10-16T14:30+02:00 : Date literals (UTC by default)"),
+        Line::from("  3d 4h, 90min, 2w, 45s : Durations (w, d, h, min, s)"),
+        Line::from("  date - date = duration, date + duration = date"),
+        Line::from("  today(), now() : Current date / date and time (UTC)"),
+        Line::from("  date(y,m,d) : Build a date"),
+        Line::from("  weekday(d) / dayname(d) : ISO weekday (Monday = 1) / day name"),
+        Line::from("  year(d), month(d), day(d) : Date parts"),
+        Line::from("  bizdays(d1,d2) : Business days from d1 up to (not including) d2"),
+        Line::from("  addbizdays(d,n) : Date n business days after d"),
+        Line::from("  tz(d,hours) : Show d at a fixed UTC offset"),
+        Line::from("  days(t), hours(t), minutes(t), seconds(t) : Duration as a number"),
         Line::from(""),
         Line::from(Span::styled("Constants:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
         Line::from("  pi : π (3.14159...)"),
@@ -69,8 +230,22 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
         Line::from(""),
         Line::from(Span::styled("Advanced Features:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
         Line::from("  details <expression> : Show step-by-step evaluation with time"),
+        Line::from("  set decimals N : Show exactly N decimal places"),
+        Line::from("  set sig N : Show N significant figures (set precision auto to undo)"),
+        Line::from("  set notation auto|sci|eng|si : Number notation; si shows 4.7k, 22µ"),
+        Line::from("  set thousands on|off : Group digits, e.g. 1,234,567"),
+        Line::from("  set decimal point|comma : Decimal mark (lists then use ;)"),
+        Line::from("  set annotate on|off : Show exact forms, e.g. 0.707107 ≈ √2/2"),
+        Line::from("  set zerosnap off|on|1e-12 : Show numbers below 1e-15 or the given size as 0 (default off)"),
+        Line::from("  set percent percent|mod|rem|emod|fmod : Meaning of the % sign"),
+        Line::from("  set strict on|off : Report overflow, underflow and NaN as errors"),
+        Line::from("  set modulus p|off : Evaluate every input in the integers mod p"),
+        Line::from("  set default : Restore the default display, % meaning and strict mode"),
+        Line::from("  interval on / off : Evaluate every number as a guaranteed [lo, hi] interval"),
+        Line::from("      x ± t is the range [x - t, x + t]; interval(lo, hi) enters one directly"),
         Line::from("  clear : Clear calculation history"),
         Line::from("  Ctrl+U : Clear current input"),
+        Line::from("  Ctrl+E : Expand or shorten the selected list result"),
         Line::from("  help : Show this help screen"),
         Line::from("  quit : Exit the calculator"),
         Line::from(""),
@@ -85,10 +260,32 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
         Line::from(Span::styled("Examples:", Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED))),
         Line::from("  sinh(1.5)"),
         Line::from("  fact(5)"),
+        Line::from("  gamma(0.5)^2"),
         Line::from("  perm(10, 3)"),
         Line::from("  mean(1, 2, 3, 4, 5)"),
         Line::from("  details comb(8, 3)"),
         Line::from("  stdev(10, 12, 23, 23, 16)"),
+        Line::from("  percentile(90, 3, 7, 8, 12, 20)"),
+        Line::from("  1 - normcdf(2.1)"),
+        Line::from("  binomcdf(3, 10, 0.5)"),
+        Line::from("  factor(360)"),
+        Line::from("  modpow(3, 100, 1000000007)"),
+        Line::from("  mod 1000000007 { 3^100 + 7*inverse(5) }"),
+        Line::from("  solve([[2, 1], [1, 3]], [3, 5])"),
+        Line::from("  polyroots(1, 0, 1)"),
+        Line::from("  diff(x^2 * sin(x), x)"),
+        Line::from("  ode(-2y, 1, 0, 1)"),
+        Line::from("  minimize(x^2 - 4x, x, -10, 10)"),
+        Line::from("  magnitude(fft(hann(16)))"),
+        Line::from("  simulate(10000, 3d6 + 2)"),
+        Line::from("  truthtable(a xor b, a and b)"),
+        Line::from("  solute(0.1, 0.25, \"NaCl\")"),
+        Line::from("  pmt(0.05/12, 360, 200000)"),
+        Line::from("  amort(0.01, 12, 5000)"),
+        Line::from("  2026-12-25 - today()"),
+        Line::from("  2026-10-16 + 3d 4h"),
+        Line::from("  (9.81 +- 0.02) * (1.2 +- 0.05)"),
+        Line::from("  200 + 15%"),
     ];
 
     let paragraph = Paragraph::new(help_text)
@@ -3,10 +3,14 @@ use anyhow::Result;
 
 pub struct HistoryEntry {
     pub input: String,
-    pub result: Result<f64, String>,
+    pub result: Result<Value, String>,
     pub detailed_steps: Vec<Step>,
     pub detailed_mode: bool,
+    /// The first step that overflowed, underflowed or produced NaN outside strict mode.
+    pub non_finite: Option<NumericError>,
     pub duration: std::time::Duration,
+    /// Whether a long list result is listed in full rather than by its ends.
+    pub expanded: bool,
 }
 
 pub struct App {
@@ -23,6 +27,7 @@ pub struct App {
     pub history_scroll: usize,
     pub scroll_to_bottom: bool,
     pub terminal_too_small: bool,
+    pub settings: Settings,
 }
 
 impl App {
@@ -41,6 +46,7 @@ impl App {
             history_scroll: 0,
             scroll_to_bottom: false,
             terminal_too_small: false,
+            settings: Settings::default(),
         }
     }
 
@@ -87,9 +93,46 @@ impl App {
                 self.input_scroll = 0;
                 return;
             }
+            "interval on" | "interval off" => {
+                self.settings.interval = input.to_lowercase().ends_with("on");
+                self.clear_input();
+                return;
+            }
             _ => {}
         }
 
+        // `seed 42` is short for `set seed 42`
+        let lowercase = input.to_lowercase();
+        let setting = lowercase.strip_prefix("set ").or(lowercase.starts_with("seed ").then_some(lowercase.as_str()));
+        if let Some(setting) = setting {
+            let result = match setting.split_whitespace().collect::<Vec<_>>()[..] {
+                ["default"] => {
+                    self.settings = Settings {
+                        interval: self.settings.interval,
+                        random: self.settings.random.clone(),
+                        ..Settings::default()
+                    };
+                    Ok(())
+                }
+                [name, value] => self.settings.set(name, value),
+                _ => Err("Usage: set <option> <value>, e.g. set decimals 2".to_string()),
+            };
+            if let Err(e) = result {
+                self.history.push(HistoryEntry {
+                    input: input.to_string(),
+                    result: Err(e),
+                    detailed_steps: Vec::new(),
+                    detailed_mode: false,
+                    non_finite: None,
+                    duration: std::time::Duration::ZERO,
+                    expanded: false,
+                });
+                self.scroll_to_bottom = true;
+            }
+            self.clear_input();
+            return;
+        }
+
         let (detailed_mode, processed_input) = if input.to_lowercase().starts_with("details ") {
             (true, input[8..].trim())
         } else if input.to_lowercase().ends_with(" details") {
@@ -104,7 +147,9 @@ impl App {
                 result: Err("Please enter a valid expression after 'details'".to_string()),
                 detailed_steps: Vec::new(),
                 detailed_mode: false,
+                non_finite: None,
                 duration: std::time::Duration::ZERO,
+                expanded: false,
             });
             self.input.clear();
             self.cursor_position = 0;
@@ -116,8 +161,10 @@ impl App {
         let mut trace = EvaluationTrace::new(detailed_mode);
         let result = match tokenize(processed_input) {
             Ok(tokens) => {
-                let mut parser = Parser::new(tokens);
-                parser.parse(&mut trace)
+                let mut parser = Parser::new(tokens, self.settings.clone());
+                let result = parser.parse(&mut trace);
+                self.settings.random = parser.random().clone();
+                result
             }
             Err(e) => Err(e),
         };
@@ -128,7 +175,9 @@ impl App {
             result,
             detailed_steps: trace.steps,
             detailed_mode,
+            non_finite: trace.first_non_finite,
             duration,
+            expanded: false,
         });
 
         self.cursor_history = self.history.len().saturating_sub(1);
@@ -211,6 +260,13 @@ impl App {
         self.scroll_to_bottom = false;
     }
 
+    /// Shows the selected entry's list result in full, or shortens it again.
+    pub fn toggle_expanded(&mut self) {
+        if let Some(entry) = self.history.get_mut(self.cursor_history) {
+            entry.expanded = !entry.expanded;
+        }
+    }
+
     pub fn clear_input(&mut self) {
         self.input.clear();
         self.cursor_position = 0;
@@ -65,25 +65,100 @@ pub fn wrap_text(text: &str, width: usize) -> Vec<String> {
     lines
 }
 
-pub fn format_number(x: f64) -> String {
-    if x.abs() > 1e10 || (x.abs() < 1e-5 && x != 0.0) {
-        format!("{:.6e}", x)
-    } else {
-        let s = format!("{:.6}", x);
-        s.trim_end_matches('0')
-            .trim_end_matches('.')
-            .to_string()
+/// Length of a date literal (`2026-10-16`, optionally with `T14:30[:00]` and
+/// a `Z` or `+02:00` offset) at the start of `chars`, or 0 if there is none.
+pub fn date_literal_len(chars: &[char]) -> usize {
+    let matches_pattern = |start: usize, pattern: &str| {
+        pattern.chars().enumerate().all(|(i, p)| match chars.get(start + i) {
+            Some(c) if p == 'd' => c.is_ascii_digit(),
+            Some(&c) => c == p,
+            None => false,
+        })
+    };
+
+    if !matches_pattern(0, "dddd-dd-dd") {
+        return 0;
+    }
+    let mut len = 10;
+    if matches_pattern(len, "Tdd:dd") {
+        len += 6;
+        if matches_pattern(len, ":dd") {
+            len += 3;
+        }
+        if chars.get(len) == Some(&'Z') {
+            len += 1;
+        } else if matches_pattern(len, "+dd:dd") || matches_pattern(len, "-dd:dd") {
+            len += 6;
+        }
     }
+    len
 }
 
 pub fn format_with_spaces(expr: &str) -> String {
     let mut result = String::new();
     let mut last_char = '\0';
     let mut in_function = false;
+    let chars: Vec<char> = expr.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+
+        let date_len = if c.is_ascii_digit() && !last_char.is_ascii_digit() {
+            date_literal_len(&chars[i - 1..])
+        } else {
+            0
+        };
+        if date_len > 0 {
+            if last_char == ')' {
+                result.push(' ');
+            }
+            result.extend(&chars[i - 1..i - 1 + date_len]);
+            i += date_len - 1;
+            last_char = chars[i - 1];
+            continue;
+        }
+
+        // `r` is the root operator only when it is not part of a name such as `sqrt` or
+        // `r1`; after a number or a closing bracket, `r3` as in `8 r3` is the operator
+        let after_operand = chars[..i - 1]
+            .iter()
+            .rev()
+            .find(|c| !c.is_whitespace())
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '.' | ')' | ']'));
+        let is_root = c == 'r'
+            && !last_char.is_alphabetic()
+            && match chars.get(i) {
+                Some(next) if next.is_ascii_digit() => after_operand,
+                Some(next) => !next.is_alphanumeric() && *next != '_',
+                None => true,
+            };
 
-    for c in expr.chars() {
         match c {
-            '+' | '-' | '*' | '/' | '^' | '%' | 'r' => {
+            // `+/-` is shown as the `±` it stands for
+            '+' if chars.get(i) == Some(&'/') && chars.get(i + 1) == Some(&'-') => {
+                if last_char != ' ' && last_char != '\0' {
+                    result.push(' ');
+                }
+                result.push_str("± ");
+                i += 2;
+                last_char = ' ';
+            }
+            // Percent is a postfix operator: `15% of 80`
+            '%' => {
+                result.push(c);
+                last_char = c;
+            }
+            '+' | '-' | '*' | '/' | '^' | '±' => {
+                if last_char != ' ' && last_char != '\0' {
+                    result.push(' ');
+                }
+                result.push(c);
+                result.push(' ');
+                last_char = ' ';
+            }
+            'r' if is_root => {
                 if last_char != ' ' && last_char != '\0' {
                     result.push(' ');
                 }
@@ -113,6 +188,16 @@ pub fn format_with_spaces(expr: &str) -> String {
             _ if c.is_whitespace() => {
                 continue;
             }
+            _ if c.is_alphabetic() && starts_word(&chars, i - 1) && is_operator_word(&word_at(&chars, i - 1)) => {
+                let word = word_at(&chars, i - 1);
+                if last_char != ' ' && last_char != '\0' {
+                    result.push(' ');
+                }
+                result.push_str(&word);
+                result.push(' ');
+                i += word.chars().count() - 1;
+                last_char = ' ';
+            }
             _ => {
                 if c.is_alphabetic() {
                     in_function = true;
@@ -128,17 +213,62 @@ pub fn format_with_spaces(expr: &str) -> String {
     result.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
+fn starts_word(chars: &[char], index: usize) -> bool {
+    index == 0 || !(chars[index - 1].is_alphanumeric() || chars[index - 1] == '_')
+}
+
+/// The identifier starting at `start`.
+fn word_at(chars: &[char], start: usize) -> String {
+    chars[start..]
+        .iter()
+        .take_while(|c| c.is_alphanumeric() || **c == '_')
+        .collect()
+}
+
 pub fn is_math_function(word: &str) -> bool {
     matches!(
         word.to_lowercase().as_str(),
         "sin" | "cos" | "tan" | "asin" | "acos" | "atan" |
         "sinh" | "cosh" | "tanh" | "asinh" | "acosh" | "atanh" |
-        "ln" | "log" | "exp" | "abs" | "sqrt" | "floor" | "ceil" | "round" |
+        "ln" | "log" | "exp" | "abs" | "sqrt" | "floor" | "ceil" | "round" | "interval" |
         "fact" | "factorial" | "perm" | "npr" | "comb" | "ncr" | "mean" | "median" | "stdev" | "stddev" |
+        "stdevs" | "stdevp" | "var" | "vars" | "varp" | "mode" | "min" | "max" | "range" |
+        "percentile" | "quartile" | "iqr" | "geomean" | "harmean" | "harmmean" | "wmean" |
+        "skew" | "skewness" | "kurt" | "kurtosis" | "zscore" |
+        "linreg" | "expfit" | "logfit" | "powfit" | "corr" | "cov" | "predict" |
+        "normpdf" | "normcdf" | "invnorm" | "tpdf" | "tcdf" | "invt" |
+        "chi2pdf" | "chi2cdf" | "invchi2" | "binompdf" | "binomcdf" | "invbinom" |
+        "poisspdf" | "poisscdf" | "invpoiss" | "exppdf" | "expcdf" | "invexp" |
+        "unifpdf" | "unifcdf" | "invunif" |
+        "gcd" | "lcm" | "isprime" | "nextprime" | "factor" | "totient" | "phi" |
+        "modpow" | "modinv" | "egcd" | "idiv" | "divmod" |
+        "pmt" | "fv" | "pv" | "nper" | "rate" | "npv" | "irr" | "compound" | "amort" |
+        "today" | "now" | "date" | "weekday" | "dayname" | "year" | "month" | "day" | "tz" |
+        "bizdays" | "addbizdays" | "days" | "hours" | "minutes" | "seconds" |
+        "gamma" | "lgamma" | "beta" | "erf" | "erfc" | "digamma" | "psi" |
+        "besselj" | "bessely" | "zeta" | "lambertw" |
+        "pctchange" | "percentchange" | "emod" | "fmod" |
+        "transpose" | "det" | "inv" | "rank" | "trace" | "solve" | "lu" | "qr" | "eig" | "eigenvalues" |
+        "dot" | "cross" | "norm" | "identity" |
+        "polyroots" | "quadratic" | "cubic" | "polyval" | "polyfit" | "polyexpand" | "polymul" |
+        "diff" | "simplify" | "ode" | "rk4" | "minimize" | "maximize" |
+        "fft" | "ifft" | "magnitude" | "phase" | "convolve" | "movavg" | "hann" | "hamming" | "blackman" |
+        "rand" | "randint" | "randn" | "choice" | "simulate" | "inverse" |
+        "truthtable" | "sat" | "equiv" |
+        "molar" | "composition" | "moles" | "mass" | "molarity" | "solute" | "dilute" |
         "pi" | "e"
     )
 }
 
+/// Words that act as operators: `r` (root), `mod`, `rem`, the percent keywords `of` and
+/// `as`, and the connectives of logic formulas.
+pub fn is_operator_word(word: &str) -> bool {
+    matches!(
+        word.to_lowercase().as_str(),
+        "r" | "mod" | "rem" | "of" | "as" | "and" | "or" | "xor" | "not" | "implies" | "iff"
+    )
+}
+
 pub fn highlight_functions(expr: &str, base_style: Style) -> Vec<Span<'static>> {
     let function_style = Style::default()
         .fg(Color::LightBlue)
@@ -151,13 +281,35 @@ pub fn highlight_functions(expr: &str, base_style: Style) -> Vec<Span<'static>>
     let number_style = Style::default()
         .fg(Color::LightGreen);
 
+    let text_style = Style::default()
+        .fg(Color::LightMagenta);
+
+    let word_style = |word: &str| {
+        if is_math_function(word) {
+            function_style
+        } else if is_operator_word(word) {
+            operator_style
+        } else {
+            base_style
+        }
+    };
+
     let mut spans = Vec::new();
     let mut current = String::new();
     let mut in_function = false;
     let mut in_number = false;
+    let mut in_text = false;
 
     for c in expr.chars() {
-        if c.is_alphabetic() {
+        // Quoted text such as a chemical formula is shown as is, up to the closing quote
+        if in_text {
+            current.push(c);
+            if c == '"' {
+                spans.push(Span::styled(current.clone(), text_style));
+                current.clear();
+                in_text = false;
+            }
+        } else if c.is_alphabetic() {
             if in_number {
                 spans.push(Span::styled(current.clone(), number_style));
                 current.clear();
@@ -168,11 +320,7 @@ pub fn highlight_functions(expr: &str, base_style: Style) -> Vec<Span<'static>>
             in_function = true;
         } else if c.is_numeric() || c == '.' || c == 'e' || c == 'E' || (in_number && (c == '-' || c == '+')) {
             if in_function {
-                if is_math_function(&current) {
-                    spans.push(Span::styled(current.clone(), function_style));
-                } else {
-                    spans.push(Span::styled(current.clone(), base_style));
-                }
+                spans.push(Span::styled(current.clone(), word_style(&current)));
                 current.clear();
                 in_function = false;
             }
@@ -181,11 +329,7 @@ pub fn highlight_functions(expr: &str, base_style: Style) -> Vec<Span<'static>>
             in_number = true;
         } else {
             if in_function {
-                if is_math_function(&current) {
-                    spans.push(Span::styled(current.clone(), function_style));
-                } else {
-                    spans.push(Span::styled(current.clone(), base_style));
-                }
+                spans.push(Span::styled(current.clone(), word_style(&current)));
                 current.clear();
                 in_function = false;
             } else if in_number {
@@ -195,19 +339,22 @@ pub fn highlight_functions(expr: &str, base_style: Style) -> Vec<Span<'static>>
             }
 
             match c {
+                '(' | ')' if in_function => {
+                    spans.push(Span::styled(c.to_string(), function_style));
+                }
                 '(' | ')' => {
-                    if in_function {
-                        spans.push(Span::styled(c.to_string(), function_style));
-                    } else {
-                        spans.push(Span::styled(c.to_string(), base_style));
-                    }
+                    spans.push(Span::styled(c.to_string(), base_style));
                 }
-                '+' | '-' | '*' | '/' | '^' | '%' | 'r' => {
+                '+' | '-' | '*' | '/' | '^' | '%' | '±' | 'r' => {
                     spans.push(Span::styled(c.to_string(), operator_style));
                 }
                 ',' => {
                     spans.push(Span::styled(c.to_string(), base_style));
                 }
+                '"' => {
+                    current.push(c);
+                    in_text = true;
+                }
                 ' ' => {
                     spans.push(Span::raw(" "));
                 }
@@ -218,12 +365,11 @@ pub fn highlight_functions(expr: &str, base_style: Style) -> Vec<Span<'static>>
         }
     }
 
-    if in_function {
-        if is_math_function(&current) {
-            spans.push(Span::styled(current, function_style));
-        } else {
-            spans.push(Span::styled(current, base_style));
-        }
+    if in_text {
+        spans.push(Span::styled(current, text_style));
+    } else if in_function {
+        let style = word_style(&current);
+        spans.push(Span::styled(current, style));
     } else if in_number {
         spans.push(Span::styled(current, number_style));
     }
@@ -11,7 +11,8 @@ use ratatui::{
 };
 use std::time::Duration;
 use unicode_width::UnicodeWidthStr;
-use super::helpers::{format_number, format_with_spaces, highlight_functions, wrap_text};
+use super::helpers::{format_with_spaces, highlight_functions, wrap_text};
+use crate::calc_engine::Value;
 use crate::render_help::render_help; // Import the centralized render_help function
 
 const MIN_TERMINAL_WIDTH: u16 = 50;
@@ -36,7 +37,7 @@ pub fn run_ui_loop(
 
         if crossterm::event::poll(Duration::from_millis(50))? {
             match crossterm::event::read()? {
-                Event::Key(KeyEvent { code, modifiers, kind, .. }) if kind == KeyEventKind::Press => {
+                Event::Key(KeyEvent { code, modifiers, kind: KeyEventKind::Press, .. }) => {
                     handle_key_event(app, code, modifiers);
                 }
                 Event::Mouse(event) => {
@@ -69,15 +70,13 @@ fn handle_key_event(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
                 app.input.insert(byte_idx, c);
                 app.cursor_position += 1;
             }
-            KeyCode::Backspace => {
-                if app.cursor_position > 0 {
-                    app.cursor_position -= 1;
-                    let byte_idx = App::char_index_to_byte_index(&app.input, app.cursor_position);
-                    let next_char = app.input[byte_idx..].chars().next();
-                    if let Some(c) = next_char {
-                        let end = byte_idx + c.len_utf8();
-                        app.input.drain(byte_idx..end);
-                    }
+            KeyCode::Backspace if app.cursor_position > 0 => {
+                app.cursor_position -= 1;
+                let byte_idx = App::char_index_to_byte_index(&app.input, app.cursor_position);
+                let next_char = app.input[byte_idx..].chars().next();
+                if let Some(c) = next_char {
+                    let end = byte_idx + c.len_utf8();
+                    app.input.drain(byte_idx..end);
                 }
             }
             KeyCode::Delete => {
@@ -116,6 +115,9 @@ fn handle_key_event(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
             KeyCode::Char('u') | KeyCode::Char('U') if modifiers.contains(KeyModifiers::CONTROL) => {
                 app.clear_input();
             }
+            KeyCode::Char('e') | KeyCode::Char('E') if modifiers.contains(KeyModifiers::CONTROL) => {
+                app.toggle_expanded();
+            }
             _ => {}
         }
     }
@@ -250,12 +252,30 @@ fn render_history(frame: &mut Frame, app: &mut App, area: Rect) {
             if line_idx == 0 {
                 match &entry.result {
                     Ok(val) => {
-                        let result_str = format_number(*val);
+                        let result_str = app.settings.display.format_result(val);
+                        let result_style = Style::default().fg(Color::LightMagenta).add_modifier(Modifier::BOLD);
                         result_spans.push(Span::styled(" = ", Style::default().fg(Color::Gray)));
-                        result_spans.push(Span::styled(
-                            result_str,
-                            Style::default().fg(Color::LightMagenta).add_modifier(Modifier::BOLD)
-                        ));
+                        // Expressions are highlighted like input, since they can be typed back in
+                        if let Value::Expression(_) = val {
+                            result_spans.extend(highlight_functions(&result_str, result_style));
+                        } else {
+                            result_spans.push(Span::styled(result_str, result_style));
+                        }
+                        if app.settings.display.is_compacted(val) && !entry.expanded && is_selected {
+                            result_spans.push(Span::styled("  Ctrl+E expands", Style::default().fg(Color::DarkGray)));
+                        }
+                        if let Some(annotation) = app.settings.display.annotation(val) {
+                            result_spans.push(Span::styled(
+                                format!("  {}", annotation),
+                                Style::default().fg(Color::DarkGray)
+                            ));
+                        }
+                        if let Some(issue) = &entry.non_finite {
+                            result_spans.push(Span::styled(
+                                format!("  ({})", issue),
+                                Style::default().fg(Color::Yellow)
+                            ));
+                        }
                     }
                     Err(e) => {
                         result_spans.push(Span::styled(" = ", Style::default().fg(Color::Gray)));
@@ -270,18 +290,35 @@ fn render_history(frame: &mut Frame, app: &mut App, area: Rect) {
             items.push(ListItem::new(Line::from(result_spans)));
         }
 
+        if let Ok(value) = &entry.result {
+            let mut lines = app.settings.display.block_lines(value);
+            if entry.expanded {
+                lines.extend(app.settings.display.list_lines(value));
+            }
+            for line in lines {
+                let span = Span::styled(
+                    format!("    {}", line),
+                    Style::default().fg(Color::LightMagenta)
+                );
+                items.push(ListItem::new(Line::from(span)));
+            }
+        }
+
         if entry.detailed_mode {
             if !entry.detailed_steps.is_empty() {
                 for (j, step) in entry.detailed_steps.iter().enumerate() {
-                    let step_result = format_number(step.result);
+                    let step_result = app.settings.display.format_value(&step.result);
                     let step_text = format!("   Step {}: {} = {}", j + 1, step.operation, step_result);
                     let step_lines = wrap_text(&step_text, wrap_width);
+                    // Highlight the step where the result first went non-finite
+                    let went_non_finite = entry.non_finite.as_ref().and_then(|issue| issue.step) == Some(j);
+                    let step_color = if went_non_finite { Color::Yellow } else { Color::DarkGray };
 
                     for (step_idx, line) in step_lines.into_iter().enumerate() {
                         let prefix = if step_idx == 0 { "    - " } else { "      " };
                         let span = Span::styled(
                             format!("{}{}", prefix, line),
-                            Style::default().fg(Color::DarkGray)
+                            Style::default().fg(step_color)
                         );
                         items.push(ListItem::new(Line::from(span)));
                     }
@@ -387,10 +424,11 @@ fn render_status(frame: &mut Frame, area: Rect) {
 }
 
 fn render_input(frame: &mut Frame, app: &mut App, area: Rect) {
+    let title = if app.settings.interval { " Expression [interval] " } else { " Expression " };
     let block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::DarkGray))
-        .title(" Expression ")
+        .title(title)
         .title_alignment(Alignment::Center);
 
     let inner_area = block.inner(area);